@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use bellperson::multicore::Worker;
+use bellperson::multiexp::{multiexp, FullDensity};
+use blstrs::Bls12;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ff::{Field, PrimeField};
+use group::{Curve, Group};
+use pairing::Engine;
+use rand::Rng;
+
+type Fr = <Bls12 as Engine>::Fr;
+
+const SAMPLES: usize = 1 << 14;
+
+fn multiexp_benchmark(c: &mut Criterion) {
+    let rng = &mut rand::thread_rng();
+    let pool = Worker::new();
+
+    let bases = Arc::new(
+        (0..SAMPLES)
+            .map(|_| <Bls12 as Engine>::G1::random(&mut *rng).to_affine())
+            .collect::<Vec<_>>(),
+    );
+    let full = Arc::new(
+        (0..SAMPLES)
+            .map(|_| Fr::random(&mut *rng).to_repr())
+            .collect::<Vec<_>>(),
+    );
+    // Typical R1CS coefficients are small.
+    let small = Arc::new(
+        (0..SAMPLES)
+            .map(|_| Fr::from(rng.gen::<u32>() as u64).to_repr())
+            .collect::<Vec<_>>(),
+    );
+
+    let mut group = c.benchmark_group("multiexp");
+    group.sample_size(10);
+    for (name, exponents) in [("full scalars", full), ("32-bit scalars", small)].iter() {
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let result = multiexp::<_, _, _, Bls12, _>(
+                    &pool,
+                    (bases.clone(), 0),
+                    FullDensity,
+                    exponents.clone(),
+                    &mut None,
+                )
+                .wait()
+                .unwrap();
+                black_box(result);
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, multiexp_benchmark);
+criterion_main!(benches);
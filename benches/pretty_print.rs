@@ -0,0 +1,41 @@
+use bellperson::gadgets::test::TestConstraintSystem;
+use bellperson::ConstraintSystem;
+use blstrs::{Bls12, Scalar as Fr};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+fn circuit() -> TestConstraintSystem<Bls12> {
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let mut prev = cs.alloc(|| "x 0", || Ok(Fr::from(1u64))).unwrap();
+    for i in 1..100 {
+        let cur = cs
+            .alloc(|| format!("x {}", i), || Ok(Fr::from(1u64 << (i % 64))))
+            .unwrap();
+        cs.enforce(
+            || format!("double {}", i),
+            |lc| lc + (Fr::from(2u64), prev),
+            |lc| lc + TestConstraintSystem::<Bls12>::one(),
+            |lc| lc + cur,
+        );
+        prev = cur;
+    }
+    cs
+}
+
+fn pretty_print_benchmark(c: &mut Criterion) {
+    c.bench_function("TestConstraintSystem::pretty_print (first call)", |b| {
+        b.iter_batched(
+            circuit,
+            |cs| black_box(cs.pretty_print()),
+            BatchSize::SmallInput,
+        );
+    })
+    .bench_function("TestConstraintSystem::pretty_print (cached)", |b| {
+        let cs = circuit();
+        cs.pretty_print();
+
+        b.iter(|| black_box(cs.pretty_print()));
+    });
+}
+
+criterion_group!(benches, pretty_print_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use bellperson::groth16::{prepare_verifying_key, verify_proof, Proof, VerifyingKey};
+use bellperson::multicore::Worker;
+use bellperson::multiexp::{multiexp, FullDensity};
+use blstrs::Bls12;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ff::{Field, PrimeField};
+use group::{Curve, Group};
+use pairing::{Engine, MillerLoopResult, MultiMillerLoop};
+
+type Fr = <Bls12 as Engine>::Fr;
+type G1 = <Bls12 as Engine>::G1;
+type G2 = <Bls12 as Engine>::G2;
+type G2Prepared = <Bls12 as MultiMillerLoop>::G2Prepared;
+
+/// Compares `verify_proof`, which multiplies the public inputs with the
+/// windowed tables of `ic` precomputed by `prepare_verifying_key`, against the
+/// same verification with the generic multiexp over `ic`.
+fn verify_benchmark(c: &mut Criterion) {
+    let rng = &mut rand::thread_rng();
+    let pool = Worker::new();
+
+    let mut group = c.benchmark_group("verify_proof");
+    group.sample_size(10);
+    for &num_inputs in &[8, 64, 512] {
+        // Random points, so the proof does not verify, which does not change
+        // the work done.
+        let vk = VerifyingKey::<Bls12> {
+            alpha_g1: G1::random(&mut *rng).to_affine(),
+            beta_g1: G1::random(&mut *rng).to_affine(),
+            beta_g2: G2::random(&mut *rng).to_affine(),
+            gamma_g2: G2::random(&mut *rng).to_affine(),
+            delta_g1: G1::random(&mut *rng).to_affine(),
+            delta_g2: G2::random(&mut *rng).to_affine(),
+            ic: (0..=num_inputs)
+                .map(|_| G1::random(&mut *rng).to_affine())
+                .collect(),
+        };
+        let proof = Proof::<Bls12> {
+            a: G1::random(&mut *rng).to_affine(),
+            b: G2::random(&mut *rng).to_affine(),
+            c: G1::random(&mut *rng).to_affine(),
+        };
+        let inputs = (0..num_inputs)
+            .map(|_| Fr::random(&mut *rng))
+            .collect::<Vec<_>>();
+
+        let pvk = prepare_verifying_key(&vk);
+        group.bench_with_input(
+            BenchmarkId::new("precomputed ic", num_inputs),
+            &inputs,
+            |b, inputs| b.iter(|| black_box(verify_proof(&pvk, &proof, inputs).unwrap())),
+        );
+
+        // Everything but the input accumulation is prepared up front, as in
+        // `prepare_verifying_key`.
+        let alpha_g1_beta_g2 = Bls12::pairing(&vk.alpha_g1, &vk.beta_g2);
+        let neg_gamma_g2 = G2Prepared::from(-vk.gamma_g2);
+        let neg_delta_g2 = G2Prepared::from(-vk.delta_g2);
+        let bases = Arc::new(vk.ic[1..].to_vec());
+        group.bench_with_input(
+            BenchmarkId::new("generic multiexp", num_inputs),
+            &inputs,
+            |b, inputs| {
+                b.iter(|| {
+                    let exponents = Arc::new(inputs.iter().map(Fr::to_repr).collect::<Vec<_>>());
+                    let mut acc = multiexp::<_, _, _, Bls12, _>(
+                        &pool,
+                        (bases.clone(), 0),
+                        FullDensity,
+                        exponents,
+                        &mut None,
+                    )
+                    .wait()
+                    .unwrap();
+                    acc += &vk.ic[0];
+
+                    let ml = Bls12::multi_miller_loop(&[
+                        (&proof.a, &proof.b.into()),
+                        (&acc.to_affine(), &neg_gamma_g2),
+                        (&proof.c, &neg_delta_g2),
+                    ]);
+                    black_box(ml.final_exponentiation() == alpha_g1_beta_g2)
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verify_benchmark);
+criterion_main!(benches);
@@ -79,6 +79,18 @@ impl<E: Engine + gpu::GpuEngine> EvaluationDomain<E> {
         })
     }
 
+    /// Builds a zeroed domain sized exactly as the prover would size it for a
+    /// circuit with `num_constraints` constraints and `num_inputs` public
+    /// inputs (not counting the implicit "one" input). The prover adds an
+    /// `x * 0 = 0` constraint for every input, so the domain has
+    /// `1 << ceil(log2(num_constraints + num_inputs + 1))` elements.
+    pub fn for_constraint_count(
+        num_constraints: usize,
+        num_inputs: usize,
+    ) -> Result<EvaluationDomain<E>, SynthesisError> {
+        Self::from_coeffs(vec![E::Fr::zero(); num_constraints + num_inputs + 1])
+    }
+
     pub fn fft(
         &mut self,
         worker: &Worker,
@@ -222,6 +234,8 @@ impl<E: Engine + gpu::GpuEngine> EvaluationDomain<E> {
     }
 
     /// Perform O(n) multiplication of two polynomials in the domain.
+    ///
+    /// Panics if the domains are not of the same size.
     pub fn mul_assign(&mut self, worker: &Worker, other: &EvaluationDomain<E>) {
         assert_eq!(self.coeffs.len(), other.coeffs.len());
 
@@ -241,6 +255,8 @@ impl<E: Engine + gpu::GpuEngine> EvaluationDomain<E> {
     }
 
     /// Perform O(n) subtraction of one polynomial from another in the domain.
+    ///
+    /// Panics if the domains are not of the same size.
     pub fn sub_assign(&mut self, worker: &Worker, other: &EvaluationDomain<E>) {
         assert_eq!(self.coeffs.len(), other.coeffs.len());
 
@@ -444,6 +460,62 @@ fn polynomial_arith() {
     test_mul::<Bls12, _>(rng);
 }
 
+#[test]
+fn pointwise_mul_sub() {
+    use blstrs::{Bls12, Scalar as Fr};
+    use rand_core::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    let rng = &mut XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+    let worker = Worker::new();
+
+    // A and B of degree 3, C of degree 5, so A*B - C fits into 8 coefficients.
+    let a: Vec<_> = (0..4).map(|_| Fr::random(&mut *rng)).collect();
+    let b: Vec<_> = (0..4).map(|_| Fr::random(&mut *rng)).collect();
+    let c: Vec<_> = (0..6).map(|_| Fr::random(&mut *rng)).collect();
+
+    let mut expected = vec![Fr::zero(); 8];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            expected[i + j] += *a * b;
+        }
+    }
+    for (e, c) in expected.iter_mut().zip(c.iter()) {
+        *e -= c;
+    }
+
+    let domain = |mut coeffs: Vec<Fr>| {
+        coeffs.resize(8, Fr::zero());
+        let mut domain = EvaluationDomain::<Bls12>::from_coeffs(coeffs).unwrap();
+        domain.fft(&worker, &mut None).unwrap();
+        domain
+    };
+    let mut a = domain(a);
+    let b = domain(b);
+    let c = domain(c);
+
+    a.mul_assign(&worker, &b);
+    a.sub_assign(&worker, &c);
+    a.ifft(&worker, &mut None).unwrap();
+
+    assert_eq!(a.into_coeffs(), expected);
+}
+
+#[test]
+#[should_panic]
+fn pointwise_size_mismatch() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let worker = Worker::new();
+    let mut a = EvaluationDomain::<Bls12>::from_coeffs(vec![Fr::one(); 4]).unwrap();
+    let b = EvaluationDomain::<Bls12>::from_coeffs(vec![Fr::one(); 8]).unwrap();
+
+    a.sub_assign(&worker, &b);
+}
+
 #[test]
 fn fft_composition() {
     use blstrs::Bls12;
@@ -2,14 +2,26 @@
 
 pub mod test;
 
+pub mod blake2b;
 pub mod blake2s;
 pub mod boolean;
+pub mod commit;
+pub mod keccak;
 pub mod lookup;
+pub mod merkle;
 pub mod multieq;
 pub mod multipack;
 pub mod num;
+pub mod pedersen;
+pub mod pedersen_hash;
+pub mod poseidon;
+pub mod sbox;
 pub mod sha256;
+pub mod sha512;
 pub mod uint32;
+pub mod uint64;
+pub mod uint8;
+pub mod util;
 
 use crate::SynthesisError;
 
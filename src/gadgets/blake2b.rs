@@ -0,0 +1,336 @@
+//! The [BLAKE2b] hash function with personalization support.
+//!
+//! This follows the structure of the [`blake2s`](super::blake2s) gadget, with
+//! 64-bit words, 12 rounds and 128-byte blocks.
+//!
+//! [BLAKE2b]: https://tools.ietf.org/html/rfc7693
+
+#![allow(clippy::many_single_char_names)]
+
+use super::{boolean::Boolean, multieq::MultiEq, uint64::UInt64};
+use crate::{ConstraintSystem, SynthesisError};
+use pairing::Engine;
+
+// Rotation constants of BLAKE2b, see section 2.1 of the RFC.
+const R1: usize = 32;
+const R2: usize = 24;
+const R3: usize = 16;
+const R4: usize = 63;
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+#[allow(clippy::unreadable_literal)]
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// The mixing function G, see section 3.1 of the RFC.
+#[allow(clippy::too_many_arguments)]
+fn mixing_g<E: Engine, CS: ConstraintSystem<E>, M>(
+    mut cs: M,
+    v: &mut [UInt64],
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    x: &UInt64,
+    y: &UInt64,
+) -> Result<(), SynthesisError>
+where
+    M: ConstraintSystem<E, Root = MultiEq<E, CS>>,
+{
+    v[a] = UInt64::addmany(
+        cs.namespace(|| "mixing step 1"),
+        &[v[a].clone(), v[b].clone(), x.clone()],
+    )?;
+    v[d] = v[d].xor(cs.namespace(|| "mixing step 2"), &v[a])?.rotr(R1);
+    v[c] = UInt64::addmany(
+        cs.namespace(|| "mixing step 3"),
+        &[v[c].clone(), v[d].clone()],
+    )?;
+    v[b] = v[b].xor(cs.namespace(|| "mixing step 4"), &v[c])?.rotr(R2);
+    v[a] = UInt64::addmany(
+        cs.namespace(|| "mixing step 5"),
+        &[v[a].clone(), v[b].clone(), y.clone()],
+    )?;
+    v[d] = v[d].xor(cs.namespace(|| "mixing step 6"), &v[a])?.rotr(R3);
+    v[c] = UInt64::addmany(
+        cs.namespace(|| "mixing step 7"),
+        &[v[c].clone(), v[d].clone()],
+    )?;
+    v[b] = v[b].xor(cs.namespace(|| "mixing step 8"), &v[c])?.rotr(R4);
+
+    Ok(())
+}
+
+/// The compression function F, see section 3.2 of the RFC.
+fn blake2b_compression<E: Engine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    h: &mut [UInt64],
+    m: &[UInt64],
+    t: u128,
+    f: bool,
+) -> Result<(), SynthesisError> {
+    assert_eq!(h.len(), 8);
+    assert_eq!(m.len(), 16);
+
+    let mut v = Vec::with_capacity(16);
+    v.extend_from_slice(h);
+    v.extend(IV.iter().map(|&iv| UInt64::constant(iv)));
+
+    assert_eq!(v.len(), 16);
+
+    v[12] = v[12].xor(cs.namespace(|| "first xor"), &UInt64::constant(t as u64))?;
+    v[13] = v[13].xor(
+        cs.namespace(|| "second xor"),
+        &UInt64::constant((t >> 64) as u64),
+    )?;
+
+    if f {
+        v[14] = v[14].xor(
+            cs.namespace(|| "third xor"),
+            &UInt64::constant(u64::max_value()),
+        )?;
+    }
+
+    {
+        let mut cs = MultiEq::new(&mut cs);
+
+        for i in 0..12 {
+            let mut cs = cs.namespace(|| format!("round {}", i));
+
+            let s = SIGMA[i % 10];
+
+            let columns_and_diagonals = [
+                (0, 4, 8, 12),
+                (1, 5, 9, 13),
+                (2, 6, 10, 14),
+                (3, 7, 11, 15),
+                (0, 5, 10, 15),
+                (1, 6, 11, 12),
+                (2, 7, 8, 13),
+                (3, 4, 9, 14),
+            ];
+            for (j, &(a, b, c, d)) in columns_and_diagonals.iter().enumerate() {
+                mixing_g(
+                    cs.namespace(|| format!("mixing invocation {}", j + 1)),
+                    &mut v,
+                    a,
+                    b,
+                    c,
+                    d,
+                    &m[s[2 * j]],
+                    &m[s[2 * j + 1]],
+                )?;
+            }
+        }
+    }
+
+    for i in 0..8 {
+        let mut cs = cs.namespace(|| format!("h[{i}] ^ v[{i}] ^ v[{i} + 8]", i = i));
+
+        h[i] = h[i].xor(cs.namespace(|| "first xor"), &v[i])?;
+        h[i] = h[i].xor(cs.namespace(|| "second xor"), &v[i + 8])?;
+    }
+
+    Ok(())
+}
+
+/// BLAKE2b-512 of `input`, whose bits are in little-endian order within each
+/// byte, as are those of the output.
+pub fn blake2b<E: Engine, CS: ConstraintSystem<E>>(
+    cs: CS,
+    input: &[Boolean],
+    personalization: &[u8; 16],
+) -> Result<Vec<Boolean>, SynthesisError> {
+    blake2b_with_length(cs, input, personalization, 64)
+}
+
+/// BLAKE2b of `input` with an output of `output_len` bytes, between 1 and 64.
+///
+/// The output length is part of the parameters of the hash, so this is not a
+/// truncation of [`blake2b`].
+pub fn blake2b_with_length<E: Engine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    input: &[Boolean],
+    personalization: &[u8; 16],
+    output_len: usize,
+) -> Result<Vec<Boolean>, SynthesisError> {
+    use byteorder::{ByteOrder, LittleEndian};
+
+    assert!((1..=64).contains(&output_len));
+    assert!(input.len() % 8 == 0);
+
+    let mut h = vec![
+        UInt64::constant(IV[0] ^ 0x0101_0000 ^ output_len as u64),
+        UInt64::constant(IV[1]),
+        UInt64::constant(IV[2]),
+        UInt64::constant(IV[3]),
+        UInt64::constant(IV[4]),
+        UInt64::constant(IV[5]),
+        // Personalization is stored here
+        UInt64::constant(IV[6] ^ LittleEndian::read_u64(&personalization[0..8])),
+        UInt64::constant(IV[7] ^ LittleEndian::read_u64(&personalization[8..16])),
+    ];
+
+    let mut blocks: Vec<Vec<UInt64>> = vec![];
+
+    for block in input.chunks(1024) {
+        let mut this_block = Vec::with_capacity(16);
+        for word in block.chunks(64) {
+            let mut tmp = word.to_vec();
+            while tmp.len() < 64 {
+                tmp.push(Boolean::constant(false));
+            }
+            this_block.push(UInt64::from_bits_le(&tmp));
+        }
+        while this_block.len() < 16 {
+            this_block.push(UInt64::constant(0));
+        }
+        blocks.push(this_block);
+    }
+
+    if blocks.is_empty() {
+        blocks.push((0..16).map(|_| UInt64::constant(0)).collect());
+    }
+
+    for (i, block) in blocks[0..blocks.len() - 1].iter().enumerate() {
+        let cs = cs.namespace(|| format!("block {}", i));
+
+        blake2b_compression(cs, &mut h, block, ((i as u128) + 1) * 128, false)?;
+    }
+
+    {
+        let cs = cs.namespace(|| "final block");
+
+        blake2b_compression(
+            cs,
+            &mut h,
+            &blocks[blocks.len() - 1],
+            (input.len() / 8) as u128,
+            true,
+        )?;
+    }
+
+    Ok(h.into_iter()
+        .flat_map(|b| b.into_bits_le())
+        .take(output_len * 8)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use blake2b_simd::Params as Blake2bParams;
+    use blstrs::Bls12;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{blake2b, blake2b_with_length};
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::test::TestConstraintSystem;
+    use crate::ConstraintSystem;
+
+    const PERSONALIZATION: &[u8; 16] = b"1234567890abcdef";
+
+    fn check_against_blake2b_simd(data: &[u8], output_len: usize) {
+        let expected = Blake2bParams::new()
+            .hash_length(output_len)
+            .personal(PERSONALIZATION)
+            .hash(data);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut input_bits = vec![];
+        for (byte_i, input_byte) in data.iter().enumerate() {
+            for bit_i in 0..8 {
+                let cs = cs.namespace(|| format!("input bit {} {}", byte_i, bit_i));
+
+                input_bits.push(
+                    AllocatedBit::alloc(cs, Some((input_byte >> bit_i) & 1u8 == 1u8))
+                        .unwrap()
+                        .into(),
+                );
+            }
+        }
+
+        let r = if output_len == 64 {
+            blake2b(&mut cs, &input_bits, PERSONALIZATION).unwrap()
+        } else {
+            blake2b_with_length(&mut cs, &input_bits, PERSONALIZATION, output_len).unwrap()
+        };
+
+        assert!(cs.is_satisfied());
+        assert_eq!(r.len(), output_len * 8);
+
+        let s = expected
+            .as_bytes()
+            .iter()
+            .flat_map(|&byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8));
+        for (b, expected) in r.iter().zip(s) {
+            assert_eq!(b.get_value().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_blank_hash() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let out = blake2b(&mut cs, &[], PERSONALIZATION).unwrap();
+        assert!(cs.is_satisfied());
+        assert!(out.iter().all(|b| b.is_constant()));
+        assert_eq!(cs.num_constraints(), 0);
+
+        check_against_blake2b_simd(&[], 64);
+    }
+
+    #[test]
+    fn test_blake2b_constant_constraints() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let input_bits: Vec<_> = (0..1024)
+            .map(|_| Boolean::constant(rng.next_u32() % 2 != 0))
+            .collect();
+        blake2b(&mut cs, &input_bits, PERSONALIZATION).unwrap();
+        assert_eq!(cs.num_constraints(), 0);
+    }
+
+    #[test]
+    fn test_blake2b() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        // A partial block, exactly one block, and more than one block.
+        for &input_len in &[3, 128, 200] {
+            let data: Vec<u8> = (0..input_len).map(|_| rng.next_u32() as u8).collect();
+            check_against_blake2b_simd(&data, 64);
+        }
+    }
+
+    #[test]
+    fn test_blake2b_output_length() {
+        for &output_len in &[1, 20, 32, 63] {
+            check_against_blake2b_simd(b"abc", output_len);
+        }
+    }
+}
@@ -510,6 +510,52 @@ impl Boolean {
         }
     }
 
+    /// Computes the AND of all of `bits`, which is `true` if `bits` is empty.
+    ///
+    /// Constants are folded first: if any bit is constant `false` the result
+    /// is `false`, and constant `true` bits are skipped, so this adds one
+    /// constraint per non-constant bit after the first, and none if there is at
+    /// most one.
+    pub fn and_many<E, CS>(mut cs: CS, bits: &[Self]) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        if bits
+            .iter()
+            .any(|bit| matches!(bit, Boolean::Constant(false)))
+        {
+            return Ok(Boolean::Constant(false));
+        }
+
+        let mut variables = bits.iter().filter(|bit| !bit.is_constant());
+        let mut acc = match variables.next() {
+            Some(first) => first.clone(),
+            None => return Ok(Boolean::Constant(true)),
+        };
+        for (i, bit) in variables.enumerate() {
+            acc = Boolean::and(cs.namespace(|| format!("and {}", i)), &acc, bit)?;
+        }
+
+        Ok(acc)
+    }
+
+    /// Computes the OR of all of `bits`, which is `false` if `bits` is empty.
+    ///
+    /// Like `and_many`, if any bit is constant `true` the result is `true`,
+    /// constant `false` bits are skipped, and this adds one constraint per
+    /// non-constant bit after the first.
+    pub fn or_many<E, CS>(cs: CS, bits: &[Self]) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        // a OR b = NOT((NOT a) AND (NOT b))
+        let negated = bits.iter().map(Boolean::not).collect::<Vec<_>>();
+
+        Ok(Boolean::and_many(cs, &negated)?.not())
+    }
+
     /// Computes (a and b) xor ((not a) and c)
     pub fn sha256_ch<'a, E, CS>(
         mut cs: CS,
@@ -741,9 +787,75 @@ impl From<AllocatedBit> for Boolean {
     }
 }
 
+/// Returns the negation of `b`. This adds no constraints.
+pub fn not(b: &Boolean) -> Boolean {
+    b.not()
+}
+
+/// Negates every boolean in `bits`. This adds no constraints.
+pub fn not_many(bits: &[Boolean]) -> Vec<Boolean> {
+    bits.iter().map(Boolean::not).collect()
+}
+
+/// Computes the majority of `a`, `b` and `c`, i.e.
+/// `(a and b) xor (a and c) xor (b and c)`. This adds at most two constraints,
+/// and none if enough of the inputs are constant.
+pub fn maj<E, CS>(cs: CS, a: &Boolean, b: &Boolean, c: &Boolean) -> Result<Boolean, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    Boolean::sha256_maj(cs, a, b, c)
+}
+
+/// Computes the SHA-256 choice function `(a and b) xor ((not a) and c)`, i.e.
+/// `b` if `a` is set and `c` otherwise. This adds at most one constraint, and
+/// none if enough of the inputs are constant.
+pub fn sha256_ch<E, CS>(
+    cs: CS,
+    a: &Boolean,
+    b: &Boolean,
+    c: &Boolean,
+) -> Result<Boolean, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    Boolean::sha256_ch(cs, a, b, c)
+}
+
+/// Enforces that `a` implies `b`, i.e. that `b` is set whenever `a` is. This
+/// adds at most one constraint, and none if `a` is constant false or `b` is
+/// constant true. Fails with `Unsatisfiable` if `a` is constant true and `b`
+/// constant false.
+pub fn enforce_implies<E, CS>(mut cs: CS, a: &Boolean, b: &Boolean) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    match (a, b) {
+        (&Boolean::Constant(false), _) | (_, &Boolean::Constant(true)) => Ok(()),
+        (&Boolean::Constant(true), b) => Boolean::enforce_equal(cs, b, &Boolean::constant(true)),
+        (a, b) => {
+            // a * (1 - b) = 0
+            cs.enforce(
+                || "implies",
+                |_| a.lc(CS::one(), E::Fr::one()),
+                |lc| lc + CS::one() - &b.lc(CS::one(), E::Fr::one()),
+                |lc| lc,
+            );
+
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{field_into_allocated_bits_le, u64_into_boolean_vec_le, AllocatedBit, Boolean};
+    use super::{
+        enforce_implies, field_into_allocated_bits_le, maj, not, not_many, sha256_ch,
+        u64_into_boolean_vec_le, AllocatedBit, Boolean,
+    };
     use crate::gadgets::test::*;
     use crate::ConstraintSystem;
     use blstrs::{Bls12, Scalar as Fr};
@@ -1047,6 +1159,32 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_not_many() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+
+        let bits = vec![
+            Boolean::constant(true),
+            Boolean::constant(false),
+            Boolean::from(AllocatedBit::alloc(cs.namespace(|| "a"), Some(true)).unwrap()),
+            Boolean::from(AllocatedBit::alloc(cs.namespace(|| "b"), Some(false)).unwrap()).not(),
+        ];
+        let num_constraints = cs.num_constraints();
+
+        let negated = not_many(&bits);
+        assert_eq!(negated.len(), bits.len());
+        for (b, n) in bits.iter().zip(negated.iter()) {
+            assert_eq!(n.get_value().unwrap(), !b.get_value().unwrap());
+            assert_eq!(not(b).get_value(), n.get_value());
+        }
+
+        // Negation only flips the representation.
+        assert_eq!(cs.num_constraints(), num_constraints);
+        assert!(matches!(negated[2], Boolean::Not(_)));
+        assert!(matches!(negated[3], Boolean::Is(_)));
+        assert!(cs.is_satisfied());
+    }
+
     #[derive(Copy, Clone, Debug)]
     enum OperandType {
         True,
@@ -1661,6 +1799,211 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_enforce_implies() {
+        // Constant, allocated and negated allocated booleans.
+        fn make(cs: &mut TestConstraintSystem<Bls12>, name: &str, kind: u8, v: bool) -> Boolean {
+            match kind {
+                0 => Boolean::constant(v),
+                1 => Boolean::from(AllocatedBit::alloc(cs.namespace(|| name), Some(v)).unwrap()),
+                _ => Boolean::from(AllocatedBit::alloc(cs.namespace(|| name), Some(!v)).unwrap())
+                    .not(),
+            }
+        }
+
+        for a_kind in 0..3 {
+            for b_kind in 0..3 {
+                for &a_val in &[false, true] {
+                    for &b_val in &[false, true] {
+                        let mut cs = TestConstraintSystem::<Bls12>::new();
+                        let a = make(&mut cs, "a", a_kind, a_val);
+                        let b = make(&mut cs, "b", b_kind, b_val);
+                        let before = cs.num_constraints();
+
+                        let res = enforce_implies(cs.namespace(|| "implies"), &a, &b);
+                        let expected = !a_val || b_val;
+                        if a_kind == 0 && b_kind == 0 {
+                            assert_eq!(res.is_ok(), expected);
+                            assert_eq!(cs.num_constraints(), before);
+                        } else {
+                            res.unwrap();
+                            assert_eq!(cs.is_satisfied(), expected);
+                        }
+                        if (a_kind == 0 && !a_val) || (b_kind == 0 && b_val) {
+                            assert_eq!(cs.num_constraints(), before);
+                        } else if a_kind != 0 || b_kind != 0 {
+                            assert_eq!(cs.num_constraints(), before + 1);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_and_many_or_many() {
+        // Two variables, a constant which leaves the result alone, two more
+        // variables, and the constant which decides the result.
+        fn bits(cs: &mut TestConstraintSystem<Bls12>, values: u8, absorbing: bool) -> Vec<Boolean> {
+            let mut alloc = |i: usize| {
+                Boolean::from(
+                    AllocatedBit::alloc(
+                        cs.namespace(|| format!("bit {}", i)),
+                        Some(values >> i & 1 == 1),
+                    )
+                    .unwrap(),
+                )
+            };
+            vec![
+                alloc(0),
+                alloc(1).not(),
+                Boolean::constant(!absorbing),
+                alloc(2),
+                Boolean::constant(absorbing),
+                alloc(3),
+            ]
+        }
+
+        fn naive<F>(cs: &mut TestConstraintSystem<Bls12>, bits: &[Boolean], op: F) -> Boolean
+        where
+            F: Fn(&mut TestConstraintSystem<Bls12>, usize, &Boolean, &Boolean) -> Boolean,
+        {
+            let mut acc = bits[0].clone();
+            for (i, bit) in bits[1..].iter().enumerate() {
+                acc = op(cs, i, &acc, bit);
+            }
+            acc
+        }
+
+        for values in 0..16u8 {
+            let value = |i: u8, negated: bool| (values >> i & 1 == 1) ^ negated;
+
+            // AND, absorbed by a constant false.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let and_bits = bits(&mut cs, values, false);
+            let before = cs.num_constraints();
+            let result = Boolean::and_many(cs.namespace(|| "and_many"), &and_bits).unwrap();
+            assert_eq!(result.get_value(), Some(false));
+            assert_eq!(cs.num_constraints(), before);
+            naive(&mut cs, &and_bits, |cs, i, a, b| {
+                Boolean::and(cs.namespace(|| format!("naive and {}", i)), a, b).unwrap()
+            });
+            assert!(cs.num_constraints() > before);
+            assert!(cs.is_satisfied());
+
+            // AND without the absorbing constant.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let and_bits = bits(&mut cs, values, false);
+            let and_bits = [&and_bits[..4], &and_bits[5..]].concat();
+            let before = cs.num_constraints();
+            let result = Boolean::and_many(cs.namespace(|| "and_many"), &and_bits).unwrap();
+            let expected = value(0, false) && value(1, true) && value(2, false) && value(3, false);
+            assert_eq!(result.get_value(), Some(expected));
+            assert_eq!(cs.num_constraints(), before + 3);
+            assert!(cs.is_satisfied());
+
+            // OR, absorbed by a constant true.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let or_bits = bits(&mut cs, values, true);
+            let before = cs.num_constraints();
+            let result = Boolean::or_many(cs.namespace(|| "or_many"), &or_bits).unwrap();
+            assert_eq!(result.get_value(), Some(true));
+            assert_eq!(cs.num_constraints(), before);
+
+            // OR without the absorbing constant.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let or_bits = bits(&mut cs, values, true);
+            let or_bits = [&or_bits[..4], &or_bits[5..]].concat();
+            let before = cs.num_constraints();
+            let result = Boolean::or_many(cs.namespace(|| "or_many"), &or_bits).unwrap();
+            let expected = value(0, false) || value(1, true) || value(2, false) || value(3, false);
+            assert_eq!(result.get_value(), Some(expected));
+            assert_eq!(cs.num_constraints(), before + 3);
+            assert!(cs.is_satisfied());
+        }
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        assert_eq!(
+            Boolean::and_many(&mut cs, &[]).unwrap().get_value(),
+            Some(true)
+        );
+        assert_eq!(
+            Boolean::or_many(&mut cs, &[]).unwrap().get_value(),
+            Some(false)
+        );
+        assert_eq!(cs.num_constraints(), 0);
+    }
+
+    #[test]
+    fn test_maj() {
+        for i in 0..8u8 {
+            let values = [i & 1 == 1, i & 2 == 2, i & 4 == 4];
+            let expected = values.iter().filter(|v| **v).count() >= 2;
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let bits = values
+                .iter()
+                .enumerate()
+                .map(|(j, v)| {
+                    Boolean::from(
+                        AllocatedBit::alloc(cs.namespace(|| format!("bit {}", j)), Some(*v))
+                            .unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let result = maj(cs.namespace(|| "maj"), &bits[0], &bits[1], &bits[2]).unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(result.get_value(), Some(expected), "{:?}", values);
+            assert_eq!(cs.num_constraints(), 3 + 2);
+
+            // Constant inputs are folded.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let constants = values
+                .iter()
+                .map(|v| Boolean::constant(*v))
+                .collect::<Vec<_>>();
+            let result = maj(&mut cs, &constants[0], &constants[1], &constants[2]).unwrap();
+            assert_eq!(result.get_value(), Some(expected));
+            assert_eq!(cs.num_constraints(), 0);
+        }
+    }
+
+    #[test]
+    fn test_sha256_ch() {
+        for i in 0..8u8 {
+            let values = [i & 1 == 1, i & 2 == 2, i & 4 == 4];
+            let expected = if values[0] { values[1] } else { values[2] };
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let bits = values
+                .iter()
+                .enumerate()
+                .map(|(j, v)| {
+                    Boolean::from(
+                        AllocatedBit::alloc(cs.namespace(|| format!("bit {}", j)), Some(*v))
+                            .unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let result = sha256_ch(cs.namespace(|| "ch"), &bits[0], &bits[1], &bits[2]).unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(result.get_value(), Some(expected), "{:?}", values);
+            assert_eq!(cs.num_constraints(), 3 + 1);
+
+            // Constant inputs are folded.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let constants = values
+                .iter()
+                .map(|v| Boolean::constant(*v))
+                .collect::<Vec<_>>();
+            let result = sha256_ch(&mut cs, &constants[0], &constants[1], &constants[2]).unwrap();
+            assert_eq!(result.get_value(), Some(expected));
+            assert_eq!(cs.num_constraints(), 0);
+        }
+    }
+
     #[test]
     fn test_boolean_sha256_maj() {
         let variants = [
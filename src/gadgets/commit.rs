@@ -0,0 +1,91 @@
+//! Gadgets for opening hash commitments.
+
+use super::boolean::Boolean;
+use super::sha256::sha256;
+use crate::{ConstraintSystem, SynthesisError};
+use pairing::Engine;
+
+/// Enforces that `expected_bits` is the SHA-256 hash of `preimage_bits`. Both
+/// are in the bit order of the `sha256` gadget, i.e. big-endian within each
+/// byte.
+///
+/// Fails with `IncompatibleLengthVector` if `expected_bits` is not 256 bits
+/// long.
+pub fn enforce_sha256_commitment<E, CS>(
+    mut cs: CS,
+    preimage_bits: &[Boolean],
+    expected_bits: &[Boolean],
+) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    if expected_bits.len() != 256 {
+        return Err(SynthesisError::IncompatibleLengthVector(format!(
+            "expected a 256 bit hash, got {} bits",
+            expected_bits.len()
+        )));
+    }
+
+    let hash = sha256(cs.namespace(|| "sha256"), preimage_bits)?;
+    for (i, (actual, expected)) in hash.iter().zip(expected_bits).enumerate() {
+        Boolean::enforce_equal(cs.namespace(|| format!("hash bit {}", i)), actual, expected)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::enforce_sha256_commitment;
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::test::*;
+    use crate::{ConstraintSystem, SynthesisError};
+    use blstrs::Bls12;
+    use sha2::{Digest, Sha256};
+
+    fn alloc_bytes<CS: ConstraintSystem<Bls12>>(mut cs: CS, bytes: &[u8]) -> Vec<Boolean> {
+        bytes
+            .iter()
+            .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1u8 == 1u8))
+            .enumerate()
+            .map(|(i, b)| {
+                Boolean::from(
+                    AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), Some(b)).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_enforce_sha256_commitment() {
+        let preimage = b"bellperson commitment";
+        let hash = Sha256::digest(preimage);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let preimage_bits = alloc_bytes(cs.namespace(|| "preimage"), preimage);
+        let hash_bits = alloc_bytes(cs.namespace(|| "hash"), &hash);
+        enforce_sha256_commitment(cs.namespace(|| "commitment"), &preimage_bits, &hash_bits)
+            .unwrap();
+        assert!(cs.is_satisfied());
+
+        let mut wrong_hash = hash;
+        wrong_hash[7] ^= 0x10;
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let preimage_bits = alloc_bytes(cs.namespace(|| "preimage"), preimage);
+        let hash_bits = alloc_bytes(cs.namespace(|| "hash"), &wrong_hash);
+        enforce_sha256_commitment(cs.namespace(|| "commitment"), &preimage_bits, &hash_bits)
+            .unwrap();
+        assert!(!cs.is_satisfied());
+        assert_eq!(
+            cs.which_is_unsatisfied(),
+            Some("commitment/hash bit 59/enforce equal")
+        );
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        assert!(matches!(
+            enforce_sha256_commitment(&mut cs, &preimage_bits, &hash_bits[..255]),
+            Err(SynthesisError::IncompatibleLengthVector(_))
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+//! The [Keccak-256] hash function, as used by Ethereum.
+//!
+//! This is the original Keccak submission with its `0x01` padding, not the
+//! standardized SHA3-256, which differs only in the padding.
+//!
+//! [Keccak-256]: https://keccak.team/files/Keccak-reference-3.0.pdf
+
+use super::boolean::Boolean;
+use crate::{ConstraintSystem, SynthesisError};
+use pairing::Engine;
+
+/// Width of a lane of the state in bits.
+const LANE: usize = 64;
+
+/// Rate of the sponge in bits, for a capacity of twice the output length.
+const RATE: usize = 1088;
+
+#[allow(clippy::unreadable_literal)]
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of the lane at `x + 5 * y`.
+const ROTATIONS: [usize; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Keccak-256 of `input`, whose length must be a whole number of bytes.
+///
+/// As with `blake2s`, the bits of each input and output byte are in
+/// little-endian order.
+///
+/// Each round of the permutation costs at most 6400 constraints: 3200 XORs
+/// for theta and 1600 ANDs and 1600 XORs for chi, while rho, pi and iota
+/// only move bits around or XOR them with constants. A block of 136 bytes
+/// thus costs at most 153600 constraints, a little less for the first block
+/// of a constant-free input as the XORs with the zero state are free.
+pub fn keccak256<E, CS>(mut cs: CS, input: &[Boolean]) -> Result<Vec<Boolean>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    assert!(input.len() % 8 == 0);
+
+    // pad10*1 with the Keccak domain: a one bit, zeros, and a final one bit
+    // at the end of the block.
+    let mut padded = input.to_vec();
+    padded.push(Boolean::constant(true));
+    while padded.len() % RATE != RATE - 1 {
+        padded.push(Boolean::constant(false));
+    }
+    padded.push(Boolean::constant(true));
+
+    let mut state = vec![vec![Boolean::constant(false); LANE]; 25];
+    for (i, block) in padded.chunks(RATE).enumerate() {
+        let mut cs = cs.namespace(|| format!("block {}", i));
+
+        // Absorb the block into the first lanes of the state.
+        for (j, (lane, block)) in state.iter_mut().zip(block.chunks(LANE)).enumerate() {
+            *lane = xor_lane(cs.namespace(|| format!("absorb lane {}", j)), lane, block)?;
+        }
+
+        state = keccak_f(cs.namespace(|| "keccak-f"), state)?;
+    }
+
+    Ok(state.into_iter().take(4).flatten().collect())
+}
+
+/// The Keccak-f[1600] permutation, over 25 lanes of 64 little-endian bits
+/// with the lane at `(x, y)` at index `x + 5 * y`.
+fn keccak_f<E, CS>(
+    mut cs: CS,
+    mut a: Vec<Vec<Boolean>>,
+) -> Result<Vec<Vec<Boolean>>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    assert_eq!(a.len(), 25);
+
+    for (round, rc) in ROUND_CONSTANTS.iter().enumerate() {
+        let mut cs = cs.namespace(|| format!("round {}", round));
+
+        // theta: c[x] = a[x, 0] ^ ... ^ a[x, 4], d[x] = c[x - 1] ^ rotl(c[x + 1], 1)
+        let mut c = Vec::with_capacity(5);
+        for x in 0..5 {
+            let mut cs = cs.namespace(|| format!("theta c {}", x));
+            let mut lane = a[x].clone();
+            for y in 1..5 {
+                lane = xor_lane(cs.namespace(|| format!("y {}", y)), &lane, &a[x + 5 * y])?;
+            }
+            c.push(lane);
+        }
+        let d = (0..5)
+            .map(|x| {
+                xor_lane(
+                    cs.namespace(|| format!("theta d {}", x)),
+                    &c[(x + 4) % 5],
+                    &rotl(&c[(x + 1) % 5], 1),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, lane) in a.iter_mut().enumerate() {
+            *lane = xor_lane(cs.namespace(|| format!("theta {}", i)), lane, &d[i % 5])?;
+        }
+
+        // rho and pi: b[y, 2x + 3y] = rotl(a[x, y], r[x, y])
+        let mut b = vec![vec![]; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = rotl(&a[x + 5 * y], ROTATIONS[x + 5 * y]);
+            }
+        }
+
+        // chi: a[x, y] = b[x, y] ^ (!b[x + 1, y] & b[x + 2, y])
+        for x in 0..5 {
+            for y in 0..5 {
+                let mut cs = cs.namespace(|| format!("chi {}", x + 5 * y));
+                let next = &b[(x + 1) % 5 + 5 * y];
+                let after = &b[(x + 2) % 5 + 5 * y];
+                let and = next
+                    .iter()
+                    .zip(after)
+                    .enumerate()
+                    .map(|(z, (next, after))| {
+                        Boolean::and(cs.namespace(|| format!("and {}", z)), &next.not(), after)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                a[x + 5 * y] = xor_lane(cs.namespace(|| "xor"), &b[x + 5 * y], &and)?;
+            }
+        }
+
+        // iota: the round constant only flips bits of the first lane.
+        for (z, bit) in a[0].iter_mut().enumerate() {
+            if (rc >> z) & 1 == 1 {
+                *bit = bit.not();
+            }
+        }
+    }
+
+    Ok(a)
+}
+
+/// Rotates a lane left by `by` bits.
+fn rotl(lane: &[Boolean], by: usize) -> Vec<Boolean> {
+    (0..LANE)
+        .map(|z| lane[(z + LANE - by) % LANE].clone())
+        .collect()
+}
+
+fn xor_lane<E, CS>(mut cs: CS, a: &[Boolean], b: &[Boolean]) -> Result<Vec<Boolean>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    a.iter()
+        .zip(b)
+        .enumerate()
+        .map(|(z, (a, b))| Boolean::xor(cs.namespace(|| format!("xor {}", z)), a, b))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::boolean::AllocatedBit;
+    use crate::gadgets::test::TestConstraintSystem;
+    use blstrs::Bls12;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+    use tiny_keccak::{Hasher, Keccak};
+
+    fn check_against_tiny_keccak(data: &[u8]) {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let input_bits = data
+            .iter()
+            .enumerate()
+            .flat_map(|(byte_i, &byte)| (0..8).map(move |bit_i| (byte_i, byte, bit_i)))
+            .map(|(byte_i, byte, bit_i)| {
+                AllocatedBit::alloc(
+                    cs.namespace(|| format!("input bit {} {}", byte_i, bit_i)),
+                    Some((byte >> bit_i) & 1u8 == 1u8),
+                )
+                .unwrap()
+                .into()
+            })
+            .collect::<Vec<Boolean>>();
+
+        let out = keccak256(cs.namespace(|| "keccak"), &input_bits).unwrap();
+        assert!(cs.is_satisfied());
+
+        let mut expected = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(data);
+        keccak.finalize(&mut expected);
+
+        assert_eq!(out.len(), 256);
+        let mut out = out.into_iter();
+        for b in expected.iter() {
+            for i in 0..8 {
+                let c = out.next().unwrap().get_value().unwrap();
+
+                assert_eq!(c, (b >> i) & 1u8 == 1u8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_blank_hash() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let out = keccak256(&mut cs, &[]).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 0);
+
+        let expected = hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+
+        let mut out = out.into_iter();
+        for b in expected.iter() {
+            for i in 0..8 {
+                let c = out.next().unwrap().get_value().unwrap();
+
+                assert_eq!(c, (b >> i) & 1u8 == 1u8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_short_input() {
+        check_against_tiny_keccak(b"abc");
+    }
+
+    #[test]
+    fn test_multi_block_input() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        // One byte short of a block, where both padding bits share a byte,
+        // and inputs spanning two and three blocks.
+        for &len in &[135, 136, 300] {
+            let data = (0..len).map(|_| rng.next_u32() as u8).collect::<Vec<_>>();
+            check_against_tiny_keccak(&data);
+        }
+    }
+}
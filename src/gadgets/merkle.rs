@@ -0,0 +1,250 @@
+//! Merkle tree inclusion proofs over a two-to-one hash of field elements.
+
+use ff::{Field, PrimeField, PrimeFieldBits};
+use pairing::Engine;
+use sha2::{Digest, Sha256};
+
+use super::boolean::Boolean;
+use super::multipack;
+use super::num::AllocatedNum;
+use super::poseidon::{poseidon, PoseidonParams};
+use super::sha256::sha256;
+use crate::{ConstraintSystem, SynthesisError};
+
+/// A two-to-one hash of field elements, in-circuit and natively.
+pub trait HashGadget<E: Engine> {
+    /// Hashes `left` and `right` in-circuit.
+    fn hash2<CS: ConstraintSystem<E>>(
+        &self,
+        cs: CS,
+        left: &AllocatedNum<E>,
+        right: &AllocatedNum<E>,
+    ) -> Result<AllocatedNum<E>, SynthesisError>;
+
+    /// Hashes `left` and `right` natively, with the same result as `hash2`.
+    fn hash2_native(&self, left: E::Fr, right: E::Fr) -> E::Fr;
+}
+
+impl<E: Engine> HashGadget<E> for PoseidonParams<E> {
+    fn hash2<CS: ConstraintSystem<E>>(
+        &self,
+        cs: CS,
+        left: &AllocatedNum<E>,
+        right: &AllocatedNum<E>,
+    ) -> Result<AllocatedNum<E>, SynthesisError> {
+        poseidon(cs, &[left.clone(), right.clone()], self)
+    }
+
+    fn hash2_native(&self, left: E::Fr, right: E::Fr) -> E::Fr {
+        self.hash(&[left, right])
+    }
+}
+
+/// SHA-256 as a two-to-one hash. Each element is encoded as 32 bytes of its
+/// little-endian bits, in the bit order of the `sha256` gadget, and the hash
+/// is the number packed from the first `CAPACITY` bits of the digest.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha256Hash;
+
+impl<E> HashGadget<E> for Sha256Hash
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+{
+    fn hash2<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        left: &AllocatedNum<E>,
+        right: &AllocatedNum<E>,
+    ) -> Result<AllocatedNum<E>, SynthesisError> {
+        let mut input = Vec::with_capacity(512);
+        for (name, num) in [("left", left), ("right", right)].iter() {
+            let mut bits = num.to_bits_le_strict(cs.namespace(|| *name))?;
+            bits.resize(256, Boolean::constant(false));
+            input.extend(bits);
+        }
+
+        let digest = sha256(cs.namespace(|| "sha256"), &input)?;
+
+        multipack::pack_bits(cs.namespace(|| "pack"), &digest[..E::Fr::CAPACITY as usize])
+    }
+
+    fn hash2_native(&self, left: E::Fr, right: E::Fr) -> E::Fr {
+        let mut hasher = Sha256::new();
+        for num in [left, right].iter() {
+            let mut bytes = [0u8; 32];
+            for (i, bit) in num.to_le_bits().into_iter().take(256).enumerate() {
+                if bit {
+                    bytes[i / 8] |= 0x80 >> (i % 8);
+                }
+            }
+            hasher.update(&bytes);
+        }
+
+        hasher
+            .finalize()
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte << i) & 0x80 != 0))
+            .take(E::Fr::CAPACITY as usize)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .fold(E::Fr::zero(), |acc, bit| {
+                let acc = acc.double();
+                if bit {
+                    acc + E::Fr::one()
+                } else {
+                    acc
+                }
+            })
+    }
+}
+
+/// Enforces that `leaf` is included in the Merkle tree with root `root`.
+///
+/// `path` holds, from the leaf up, the sibling of the node at each level, and
+/// whether that node is the right child, so that the parent is
+/// `hash2(sibling, node)` if it is and `hash2(node, sibling)` otherwise.
+pub fn verify_inclusion<E, CS, H>(
+    mut cs: CS,
+    hasher: &H,
+    leaf: &AllocatedNum<E>,
+    path: &[(AllocatedNum<E>, Boolean)],
+    root: &AllocatedNum<E>,
+) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+    H: HashGadget<E>,
+{
+    let mut node = leaf.clone();
+    for (i, (sibling, is_right)) in path.iter().enumerate() {
+        let mut cs = cs.namespace(|| format!("level {}", i));
+
+        let (left, right) = AllocatedNum::conditionally_reverse(
+            cs.namespace(|| "order"),
+            &node,
+            sibling,
+            is_right,
+        )?;
+        node = hasher.hash2(cs.namespace(|| "hash"), &left, &right)?;
+    }
+
+    cs.enforce(
+        || "root equality",
+        |lc| lc + node.get_variable(),
+        |lc| lc + CS::one(),
+        |lc| lc + root.get_variable(),
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::boolean::AllocatedBit;
+    use crate::gadgets::test::*;
+    use blstrs::{Bls12, Scalar as Fr};
+
+    const DEPTH: usize = 4;
+
+    /// Builds a tree of depth `DEPTH`, returning its levels from the leaves up.
+    fn tree<H: HashGadget<Bls12>>(hasher: &H) -> Vec<Vec<Fr>> {
+        let mut levels = vec![(0..1u64 << DEPTH)
+            .map(|i| Fr::from(i * 7 + 3))
+            .collect::<Vec<_>>()];
+        for _ in 0..DEPTH {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| hasher.hash2_native(pair[0], pair[1]))
+                .collect();
+            levels.push(level);
+        }
+
+        levels
+    }
+
+    /// Synthesizes the inclusion proof of the leaf at `index`, where `path` is
+    /// the siblings and directions, returning whether it is satisfied.
+    fn verify<H: HashGadget<Bls12>>(hasher: &H, leaf: Fr, path: &[(Fr, bool)], root: Fr) -> bool {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let leaf = AllocatedNum::alloc(cs.namespace(|| "leaf"), || Ok(leaf)).unwrap();
+        let path = path
+            .iter()
+            .enumerate()
+            .map(|(i, (sibling, is_right))| {
+                let sibling =
+                    AllocatedNum::alloc(cs.namespace(|| format!("sibling {}", i)), || Ok(*sibling))
+                        .unwrap();
+                let is_right = AllocatedBit::alloc(
+                    cs.namespace(|| format!("is right {}", i)),
+                    Some(*is_right),
+                )
+                .unwrap();
+                (sibling, Boolean::from(is_right))
+            })
+            .collect::<Vec<_>>();
+        let root = AllocatedNum::alloc(cs.namespace(|| "root"), || Ok(root)).unwrap();
+
+        verify_inclusion(cs.namespace(|| "inclusion"), hasher, &leaf, &path, &root).unwrap();
+
+        cs.is_satisfied()
+    }
+
+    fn test_inclusion<H: HashGadget<Bls12>>(hasher: &H, indices: &[usize]) {
+        let levels = tree(hasher);
+        let root = levels[DEPTH][0];
+
+        for &index in indices {
+            let leaf = levels[0][index];
+            let path = (0..DEPTH)
+                .map(|level| {
+                    let node = index >> level;
+                    (levels[level][node ^ 1], node & 1 == 1)
+                })
+                .collect::<Vec<_>>();
+
+            assert!(verify(hasher, leaf, &path, root));
+
+            // Tampering with the leaf, a sibling, a direction or the root.
+            assert!(!verify(hasher, leaf + Fr::one(), &path, root));
+            let mut tampered = path.clone();
+            tampered[1].0 += Fr::one();
+            assert!(!verify(hasher, leaf, &tampered, root));
+            let mut tampered = path.clone();
+            tampered[2].1 = !tampered[2].1;
+            assert!(!verify(hasher, leaf, &tampered, root));
+            assert!(!verify(hasher, leaf, &path, levels[DEPTH - 1][0]));
+        }
+    }
+
+    #[test]
+    fn test_verify_inclusion_poseidon() {
+        test_inclusion(&PoseidonParams::<Bls12>::bls12_t3(), &[0, 5, 10, 15]);
+    }
+
+    #[test]
+    fn test_verify_inclusion_sha256() {
+        test_inclusion(&Sha256Hash, &[6]);
+    }
+
+    #[test]
+    fn test_sha256_hash_native() {
+        let left = Fr::from(1u64);
+        let right = -Fr::one();
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let l = AllocatedNum::alloc(cs.namespace(|| "left"), || Ok(left)).unwrap();
+        let r = AllocatedNum::alloc(cs.namespace(|| "right"), || Ok(right)).unwrap();
+        let hash = Sha256Hash.hash2(cs.namespace(|| "hash"), &l, &r).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(
+            hash.get_value(),
+            Some(HashGadget::<Bls12>::hash2_native(&Sha256Hash, left, right))
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use ff::{Field, PrimeField};
 use pairing::Engine;
 
-use crate::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use crate::{ConstraintSystem, LinearCombination, SynthesisError, SynthesisMode, Variable};
 
 pub struct MultiEq<E: Engine, CS: ConstraintSystem<E>> {
     cs: CS,
@@ -117,4 +117,8 @@ impl<E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for MultiEq<E, CS>
     fn get_root(&mut self) -> &mut Self::Root {
         self
     }
+
+    fn mode(&self) -> SynthesisMode {
+        self.cs.mode()
+    }
 }
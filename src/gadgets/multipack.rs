@@ -3,10 +3,11 @@
 use std::ops::AddAssign;
 
 use super::boolean::Boolean;
-use super::num::{AllocatedNum, Num};
+use super::num::{enforce_fits_in_bits, AllocatedNum, Num};
 use super::Assignment;
-use crate::{ConstraintSystem, SynthesisError};
-use ff::{Field, PrimeField};
+use crate::multicore::Worker;
+use crate::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use ff::{Field, PrimeField, PrimeFieldBits};
 use pairing::Engine;
 
 /// Takes a sequence of booleans and exposes them as compact
@@ -53,36 +54,98 @@ pub fn bytes_to_bits_le(bytes: &[u8]) -> Vec<bool> {
         .collect()
 }
 
+/// Packs `bits` into field elements of `E::Fr::CAPACITY` bits each, the same way
+/// `pack_into_inputs` does. The field elements are computed in parallel.
 pub fn compute_multipacking<E: Engine>(bits: &[bool]) -> Vec<E::Fr> {
-    let mut result = vec![];
+    let chunks = bits.chunks(E::Fr::CAPACITY as usize).collect::<Vec<_>>();
+    let mut result = vec![E::Fr::zero(); chunks.len()];
 
-    for bits in bits.chunks(E::Fr::CAPACITY as usize) {
-        let mut cur = E::Fr::zero();
-        let mut coeff = E::Fr::one();
+    Worker::new().scope(chunks.len(), |scope, chunk_size| {
+        for (result, chunks) in result.chunks_mut(chunk_size).zip(chunks.chunks(chunk_size)) {
+            scope.execute(move || {
+                for (cur, bits) in result.iter_mut().zip(chunks.iter()) {
+                    *cur = compute_packing::<E>(bits);
+                }
+            });
+        }
+    });
 
-        for bit in bits {
-            if *bit {
-                cur.add_assign(&coeff);
-            }
+    result
+}
 
-            coeff = coeff.double();
+fn compute_packing<E: Engine>(bits: &[bool]) -> E::Fr {
+    let mut cur = E::Fr::zero();
+    let mut coeff = E::Fr::one();
+
+    for bit in bits {
+        if *bit {
+            cur.add_assign(&coeff);
         }
 
-        result.push(cur);
+        coeff = coeff.double();
     }
 
-    result
+    cur
+}
+
+/// Reverses `compute_multipacking`: reads `E::Fr::CAPACITY` bits from each of
+/// `inputs`, in the order `pack_into_inputs` packed them, and returns the first
+/// `total_bits` of them.
+///
+/// # Panics
+///
+/// Panics if `inputs` holds fewer than `total_bits` bits.
+pub fn unpack_inputs<E>(inputs: &[E::Fr], total_bits: usize) -> Vec<bool>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+{
+    let capacity = E::Fr::CAPACITY as usize;
+    assert!(
+        total_bits <= inputs.len() * capacity,
+        "{} inputs cannot hold {} bits",
+        inputs.len(),
+        total_bits
+    );
+
+    let mut bits = inputs
+        .iter()
+        .flat_map(|input| {
+            input
+                .to_le_bits()
+                .into_iter()
+                .take(capacity)
+                .collect::<Vec<bool>>()
+        })
+        .collect::<Vec<_>>();
+    bits.truncate(total_bits);
+
+    bits
+}
+
+/// Checks that a value of `num_bits` bits can be packed into a single field
+/// element without wrapping around the modulus, i.e. that `num_bits` is at most
+/// `E::Fr::CAPACITY`.
+pub fn assert_bits_fit_field<E: Engine>(num_bits: usize) -> Result<(), SynthesisError> {
+    if num_bits > E::Fr::CAPACITY as usize {
+        return Err(SynthesisError::ExceedsFieldCapacity(num_bits));
+    }
+
+    Ok(())
 }
 
 /// Takes a sequence of booleans and exposes them as a single compact Num.
+/// Fails if there are more bits than fit into the field.
 pub fn pack_bits<E, CS>(mut cs: CS, bits: &[Boolean]) -> Result<AllocatedNum<E>, SynthesisError>
 where
     E: Engine,
     CS: ConstraintSystem<E>,
 {
+    assert_bits_fit_field::<E>(bits.len())?;
+
     let mut num = Num::<E>::zero();
     let mut coeff = E::Fr::one();
-    for bit in bits.iter().take(E::Fr::CAPACITY as usize) {
+    for bit in bits {
         num = num.add_bool_with_coeff(CS::one(), &bit, coeff);
 
         coeff = coeff.double();
@@ -103,6 +166,61 @@ where
     Ok(alloc_num)
 }
 
+/// Enforces that `bits` are the little-endian encoding of the value of `input`.
+pub fn enforce_bits_equal_input<E, CS>(
+    mut cs: CS,
+    bits: &[Boolean],
+    input: Variable,
+) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    assert_bits_fit_field::<E>(bits.len())?;
+
+    let mut num = Num::<E>::zero();
+    let mut coeff = E::Fr::one();
+    for bit in bits {
+        num = num.add_bool_with_coeff(CS::one(), bit, coeff);
+
+        coeff = coeff.double();
+    }
+
+    // num * 1 = input
+    cs.enforce(
+        || "bits equal input constraint",
+        |_| num.lc(E::Fr::one()),
+        |lc| lc + CS::one(),
+        |lc| lc + input,
+    );
+
+    Ok(())
+}
+
+/// Exposes `num` as a single public input, after enforcing that it fits into
+/// `max_bits` bits.
+pub fn expose_as_input<E, CS>(
+    mut cs: CS,
+    num: &AllocatedNum<E>,
+    max_bits: usize,
+) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    CS: ConstraintSystem<E>,
+{
+    assert_bits_fit_field::<E>(max_bits)?;
+
+    enforce_fits_in_bits(
+        cs.namespace(|| "range check"),
+        num.get_value(),
+        LinearCombination::zero() + num.get_variable(),
+        max_bits,
+    )?;
+
+    num.inputize(cs.namespace(|| "input"))
+}
+
 #[test]
 fn test_multipacking() {
     use crate::ConstraintSystem;
@@ -141,3 +259,147 @@ fn test_multipacking() {
         assert!(cs.verify(&expected_inputs));
     }
 }
+
+#[test]
+fn test_compute_multipacking_large() {
+    use blstrs::{Bls12, Scalar as Fr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let bits: Vec<bool> = (0..1_000_000).map(|_| rng.next_u32() % 2 != 0).collect();
+
+    let mut expected = vec![];
+    for bits in bits.chunks(Fr::CAPACITY as usize) {
+        let mut cur = Fr::zero();
+        let mut coeff = Fr::one();
+        for bit in bits {
+            if *bit {
+                cur.add_assign(&coeff);
+            }
+            coeff = coeff.double();
+        }
+        expected.push(cur);
+    }
+
+    assert_eq!(compute_multipacking::<Bls12>(&bits), expected);
+    assert!(compute_multipacking::<Bls12>(&[]).is_empty());
+}
+
+#[test]
+fn test_unpack_inputs() {
+    use blstrs::{Bls12, Scalar as Fr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    // Empty, within a single element, exactly filling one, and spanning several.
+    let capacity = Fr::CAPACITY as usize;
+    for &num_bits in &[0, 1, 100, capacity, capacity + 1, 3 * capacity - 7] {
+        let bits: Vec<bool> = (0..num_bits).map(|_| rng.next_u32() % 2 != 0).collect();
+        let inputs = compute_multipacking::<Bls12>(&bits);
+
+        assert_eq!(unpack_inputs::<Bls12>(&inputs, num_bits), bits);
+    }
+
+    // A known vector: 0b1101 packs to 11.
+    let bits = vec![true, true, false, true];
+    assert_eq!(compute_multipacking::<Bls12>(&bits), vec![Fr::from(11u64)]);
+    assert_eq!(unpack_inputs::<Bls12>(&[Fr::from(11u64)], 4), bits);
+}
+
+#[test]
+#[should_panic]
+fn test_unpack_inputs_too_few() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    unpack_inputs::<Bls12>(&[Fr::one()], Fr::CAPACITY as usize + 1);
+}
+
+#[test]
+fn test_assert_bits_fit_field() {
+    use crate::SynthesisError;
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let capacity = Fr::CAPACITY as usize;
+
+    assert!(assert_bits_fit_field::<Bls12>(0).is_ok());
+    assert!(assert_bits_fit_field::<Bls12>(capacity).is_ok());
+    assert!(matches!(
+        assert_bits_fit_field::<Bls12>(capacity + 1),
+        Err(SynthesisError::ExceedsFieldCapacity(n)) if n == capacity + 1
+    ));
+}
+
+#[test]
+fn test_pack_bits_too_long() {
+    use crate::ConstraintSystem;
+    use blstrs::{Bls12, Scalar as Fr};
+
+    use crate::gadgets::test::*;
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let bits = vec![Boolean::constant(true); Fr::CAPACITY as usize + 1];
+
+    assert!(pack_bits(cs.namespace(|| "too long"), &bits).is_err());
+    assert!(pack_bits(cs.namespace(|| "fits"), &bits[1..]).is_ok());
+    assert!(cs.is_satisfied());
+}
+
+#[test]
+fn test_enforce_bits_equal_input() {
+    use crate::ConstraintSystem;
+    use blstrs::{Bls12, Scalar as Fr};
+
+    use super::boolean::u64_into_boolean_vec_le;
+    use crate::gadgets::test::*;
+
+    for &(value, input, satisfied) in &[(11u64, 11u64, true), (11, 12, false)] {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+
+        let input = cs.alloc_input(|| "input", || Ok(Fr::from(input))).unwrap();
+        let bits = u64_into_boolean_vec_le(cs.namespace(|| "bits"), Some(value)).unwrap();
+
+        enforce_bits_equal_input(cs.namespace(|| "enforce"), &bits, input).unwrap();
+
+        assert_eq!(cs.is_satisfied(), satisfied);
+    }
+}
+
+#[test]
+fn test_expose_as_input() {
+    use crate::ConstraintSystem;
+    use blstrs::{Bls12, Scalar as Fr};
+
+    use crate::gadgets::test::*;
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let num = AllocatedNum::alloc(cs.namespace(|| "num"), || Ok(Fr::from(255u64))).unwrap();
+    let inputs = cs.num_inputs();
+
+    expose_as_input(cs.namespace(|| "expose"), &num, 8).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_inputs(), inputs + 1);
+    assert!(cs.verify(&[Fr::from(255u64)]));
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let num = AllocatedNum::alloc(cs.namespace(|| "num"), || Ok(Fr::from(256u64))).unwrap();
+
+    expose_as_input(cs.namespace(|| "expose"), &num, 8).unwrap();
+
+    assert!(!cs.is_satisfied());
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let num = AllocatedNum::alloc(cs.namespace(|| "num"), || Ok(Fr::one())).unwrap();
+
+    assert!(expose_as_input(cs.namespace(|| "expose"), &num, Fr::CAPACITY as usize + 1).is_err());
+}
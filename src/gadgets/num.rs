@@ -1,8 +1,8 @@
 //! Gadgets representing numbers in the scalar field of the underlying curve.
 
-use std::ops::{AddAssign, MulAssign};
+use std::ops::{AddAssign, MulAssign, SubAssign};
 
-use ff::{Field, PrimeFieldBits};
+use ff::{Field, PrimeField, PrimeFieldBits};
 use pairing::Engine;
 
 use crate::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
@@ -10,6 +10,7 @@ use crate::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
 use super::Assignment;
 
 use super::boolean::{self, AllocatedBit, Boolean};
+use super::multipack::{self, assert_bits_fit_field};
 
 pub struct AllocatedNum<E: Engine> {
     value: Option<E::Fr>,
@@ -221,6 +222,21 @@ impl<E: Engine> AllocatedNum<E> {
         Ok(bits.into_iter().map(Boolean::from).collect())
     }
 
+    /// Allocates the number whose little-endian bits are `bits`, the inverse of
+    /// `to_bits_le`. This costs one constraint.
+    ///
+    /// Fails with `Unsatisfiable` if there are more bits than fit into the field.
+    pub fn from_bits_le<CS>(cs: CS, bits: &[Boolean]) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        if bits.len() > E::Fr::CAPACITY as usize {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        multipack::pack_bits(cs, bits)
+    }
+
     pub fn mul<CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
     where
         CS: ConstraintSystem<E>,
@@ -285,6 +301,75 @@ impl<E: Engine> AllocatedNum<E> {
         })
     }
 
+    /// Returns `base^exp` for a constant `exp`, by square-and-multiply over the
+    /// bits of `exp`. Each squaring and multiplication costs one constraint.
+    ///
+    /// For `exp == 0` this is the constant one, and for `exp == 1` a clone of
+    /// `base`, neither of which costs any constraints.
+    pub fn pow_constant<CS>(mut cs: CS, base: &Self, exp: u64) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        if exp == 0 {
+            return Ok(AllocatedNum {
+                value: Some(E::Fr::one()),
+                variable: CS::one(),
+            });
+        }
+
+        let mut acc = base.clone();
+        let num_bits = 64 - exp.leading_zeros();
+        for i in (0..num_bits - 1).rev() {
+            acc = acc.square(cs.namespace(|| format!("square {}", i)))?;
+            if (exp >> i) & 1 == 1 {
+                acc = acc.mul(cs.namespace(|| format!("multiply {}", i)), base)?;
+            }
+        }
+
+        Ok(acc)
+    }
+
+    /// Returns whether `x` is zero, by witnessing the bit `is_zero` and `inv`,
+    /// the inverse of `x` or zero, and enforcing `x * inv = 1 - is_zero` and
+    /// `x * is_zero = 0`. This costs three constraints, including the one
+    /// constraining `is_zero` to be boolean.
+    pub fn is_zero<CS>(mut cs: CS, x: &Self) -> Result<Boolean, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let is_zero = AllocatedBit::alloc(
+            cs.namespace(|| "is zero"),
+            x.value.map(|x| x.is_zero().into()),
+        )?;
+
+        let inv = cs.alloc(
+            || "inverse",
+            || {
+                let tmp = *x.value.get()?;
+
+                Ok(tmp.invert().unwrap_or_else(E::Fr::zero))
+            },
+        )?;
+
+        // x * inv = 1 - is_zero. If x is zero, is_zero must be set.
+        cs.enforce(
+            || "inverse constraint",
+            |lc| lc + x.variable,
+            |lc| lc + inv,
+            |lc| lc + CS::one() - is_zero.get_variable(),
+        );
+
+        // x * is_zero = 0. If x is not zero, is_zero must be unset.
+        cs.enforce(
+            || "zero constraint",
+            |lc| lc + x.variable,
+            |lc| lc + is_zero.get_variable(),
+            |lc| lc,
+        );
+
+        Ok(Boolean::from(is_zero))
+    }
+
     pub fn assert_nonzero<CS>(&self, mut cs: CS) -> Result<(), SynthesisError>
     where
         CS: ConstraintSystem<E>,
@@ -360,6 +445,77 @@ impl<E: Engine> AllocatedNum<E> {
         Ok((c, d))
     }
 
+    /// Enforces that `value` fits into `n` bits, returning its `n` allocated
+    /// bits in little-endian order. `n` must be at most the field's capacity.
+    ///
+    /// Fails with `Unsatisfiable` if the witnessed value is `2^n` or larger.
+    pub fn enforce_in_range<CS>(
+        cs: CS,
+        value: &Self,
+        n: usize,
+    ) -> Result<Vec<Boolean>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+        E::Fr: PrimeFieldBits,
+    {
+        assert_bits_fit_field::<E>(n)?;
+
+        if let Some(value) = value.value {
+            if value.to_le_bits().iter().skip(n).any(|bit| *bit) {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        }
+
+        let bits = decompose_le(
+            cs,
+            value.value,
+            LinearCombination::zero() + value.variable,
+            n,
+        )?;
+
+        Ok(bits.into_iter().map(Boolean::from).collect())
+    }
+
+    /// Enforces `self < bound` for a constant `bound`, by showing that both `self`
+    /// and `bound - self - 1` fit into `num_bits` bits. `bound` must be at most
+    /// `2^num_bits`, and `num_bits` must be less than the field's capacity so
+    /// that neither difference can wrap around the modulus.
+    pub fn enforce_less_than_constant<CS>(
+        &self,
+        mut cs: CS,
+        bound: E::Fr,
+        num_bits: usize,
+    ) -> Result<(), SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+        E::Fr: PrimeFieldBits,
+    {
+        assert_bits_fit_field::<E>(num_bits + 1)?;
+
+        enforce_fits_in_bits(
+            cs.namespace(|| "value"),
+            self.value,
+            LinearCombination::zero() + self.variable,
+            num_bits,
+        )?;
+
+        let diff = self.value.map(|value| {
+            let mut tmp = bound;
+            tmp.sub_assign(&value);
+            tmp.sub_assign(&E::Fr::one());
+            tmp
+        });
+        let mut bound_minus_one = bound;
+        bound_minus_one.sub_assign(&E::Fr::one());
+
+        enforce_fits_in_bits(
+            cs.namespace(|| "bound - value - 1"),
+            diff,
+            LinearCombination::zero() + (bound_minus_one, CS::one()) - self.variable,
+            num_bits,
+        )
+    }
+
     pub fn get_value(&self) -> Option<E::Fr> {
         self.value
     }
@@ -369,6 +525,390 @@ impl<E: Engine> AllocatedNum<E> {
     }
 }
 
+/// Returns `(b, a)` if `condition` is true, and `(a, b)` otherwise.
+///
+/// This costs two constraints: one selecting the first output, and one tying the
+/// second output to it so the pair is a permutation of the inputs. Selecting
+/// each output independently would not need fewer.
+pub fn conditional_swap<E, CS>(
+    cs: CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+    condition: &Boolean,
+) -> Result<(AllocatedNum<E>, AllocatedNum<E>), SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    AllocatedNum::conditionally_reverse(cs, a, b, condition)
+}
+
+/// Returns `a` if `condition` is true, and `b` otherwise.
+///
+/// This costs one constraint, `(a - b) * condition = result - b`, and none if
+/// `condition` is constant, in which case `a` or `b` is returned as is.
+pub fn conditionally_select<E, CS>(
+    mut cs: CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+    condition: &Boolean,
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    match *condition {
+        Boolean::Constant(true) => return Ok(a.clone()),
+        Boolean::Constant(false) => return Ok(b.clone()),
+        _ => {}
+    }
+
+    let result = AllocatedNum::alloc(cs.namespace(|| "selected"), || {
+        if *condition.get_value().get()? {
+            Ok(*a.value.get()?)
+        } else {
+            Ok(*b.value.get()?)
+        }
+    })?;
+
+    cs.enforce(
+        || "conditional selection",
+        |lc| lc + a.variable - b.variable,
+        |_| condition.lc(CS::one(), E::Fr::one()),
+        |lc| lc + result.variable - b.variable,
+    );
+
+    Ok(result)
+}
+
+/// Enforces that `index` is a valid index into a table of `len` entries, i.e.
+/// that it lies in `[0, len)`, using `enforce_less_than_constant`.
+///
+/// Fails with `Unsatisfiable` if `len` is zero, as no index is valid then.
+pub fn enforce_valid_index<E, CS>(
+    cs: CS,
+    index: &AllocatedNum<E>,
+    len: usize,
+) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    CS: ConstraintSystem<E>,
+{
+    if len == 0 {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    // The smallest number of bits with `len <= 2^num_bits`.
+    let mut num_bits = 0;
+    while (1u128 << num_bits) < len as u128 {
+        num_bits += 1;
+    }
+    index.enforce_less_than_constant(cs, E::Fr::from(len as u64), num_bits)
+}
+
+/// Returns the inverses of `values`, enforcing `value * inverse = 1` for each
+/// of them, which also proves that none of them is zero.
+///
+/// The inverses are witnessed with Montgomery's trick, which needs a single
+/// field inversion for the whole slice. In-circuit, one constraint per value
+/// is already the cost of witnessing an inverse, so the constraints are not
+/// batched. Fails with `DivisionByZero` if any of the values is zero.
+pub fn batch_invert<E, CS>(
+    mut cs: CS,
+    values: &[AllocatedNum<E>],
+) -> Result<Vec<AllocatedNum<E>>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    let inverses = match values.iter().map(|v| v.value).collect::<Option<Vec<_>>>() {
+        Some(values) => Some(batch_invert_values::<E>(&values)?),
+        None => None,
+    };
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let mut cs = cs.namespace(|| format!("value {}", i));
+            let inverse = AllocatedNum::alloc(cs.namespace(|| "inverse"), || {
+                Ok(inverses.as_ref().get()?[i])
+            })?;
+
+            cs.enforce(
+                || "inverse constraint",
+                |lc| lc + value.variable,
+                |lc| lc + inverse.variable,
+                |lc| lc + CS::one(),
+            );
+
+            Ok(inverse)
+        })
+        .collect()
+}
+
+/// Inverts all of `values` with a single field inversion, by inverting their
+/// product and back-substituting the prefix products.
+fn batch_invert_values<E: Engine>(values: &[E::Fr]) -> Result<Vec<E::Fr>, SynthesisError> {
+    // prefix[i] is the product of values[..i].
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = E::Fr::one();
+    for value in values {
+        prefix.push(acc);
+        acc.mul_assign(value);
+    }
+
+    let mut acc: E::Fr = Option::from(acc.invert()).ok_or(SynthesisError::DivisionByZero)?;
+    let mut inverses = vec![E::Fr::zero(); values.len()];
+    for (i, value) in values.iter().enumerate().rev() {
+        let mut inverse = acc;
+        inverse.mul_assign(&prefix[i]);
+        inverses[i] = inverse;
+        acc.mul_assign(value);
+    }
+
+    Ok(inverses)
+}
+
+/// Returns whether `a < b`, as the top bit of the `n + 1` bit decomposition of
+/// `b - a - 1 + 2^n`. `n` must be less than the field's capacity.
+///
+/// This does not range-check its inputs: the caller must ensure that both `a`
+/// and `b` fit into `n` bits, e.g. with `AllocatedNum::enforce_in_range`.
+/// Otherwise the difference can wrap around and the result is meaningless,
+/// which makes the circuit unsound.
+pub fn less_than<E, CS>(
+    cs: CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+    n: usize,
+) -> Result<Boolean, SynthesisError>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    CS: ConstraintSystem<E>,
+{
+    assert_bits_fit_field::<E>(n + 1)?;
+
+    let mut offset = E::Fr::from(2u64).pow_vartime(&[n as u64]);
+    offset.sub_assign(&E::Fr::one());
+    let value = a.value.and_then(|a| {
+        b.value.map(|b| {
+            let mut tmp = b;
+            tmp.sub_assign(&a);
+            tmp.add_assign(&offset);
+            tmp
+        })
+    });
+
+    let mut bits = decompose_le(
+        cs,
+        value,
+        LinearCombination::zero() + b.variable - a.variable + (offset, CS::one()),
+        n + 1,
+    )?;
+
+    Ok(Boolean::from(bits.pop().unwrap()))
+}
+
+/// Returns the maximum of `nums`, enforcing that all of them fit into
+/// `num_bits` bits, by folding `nums` with `less_than` comparisons and
+/// `conditionally_select`. `num_bits` must be less than the field's capacity.
+///
+/// Fails with `IncompatibleLengthVector` if `nums` is empty.
+pub fn max<E, CS>(
+    mut cs: CS,
+    nums: &[AllocatedNum<E>],
+    num_bits: usize,
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    CS: ConstraintSystem<E>,
+{
+    let (first, rest) = nums.split_first().ok_or_else(|| {
+        SynthesisError::IncompatibleLengthVector("no numbers to take the maximum of".to_string())
+    })?;
+    assert_bits_fit_field::<E>(num_bits + 1)?;
+
+    for (i, num) in nums.iter().enumerate() {
+        enforce_fits_in_bits(
+            cs.namespace(|| format!("num {}", i)),
+            num.value,
+            LinearCombination::zero() + num.variable,
+            num_bits,
+        )?;
+    }
+
+    // Every intermediate maximum is one of `nums`, so it is in range too.
+    let mut max = first.clone();
+    for (i, num) in rest.iter().enumerate() {
+        let mut cs = cs.namespace(|| format!("step {}", i));
+        let is_less = less_than(cs.namespace(|| "less than"), &max, num, num_bits)?;
+        max = conditionally_select(cs.namespace(|| "select"), num, &max, &is_less)?;
+    }
+
+    Ok(max)
+}
+
+/// Allocates a number and immediately runs `check` on it, so that the invariants
+/// the number must satisfy are constrained right where it is allocated.
+pub fn alloc_with_check<E, CS, F, C>(
+    mut cs: CS,
+    value: F,
+    check: C,
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+    F: FnOnce() -> Result<E::Fr, SynthesisError>,
+    C: FnOnce(&mut CS, &AllocatedNum<E>) -> Result<(), SynthesisError>,
+{
+    let num = AllocatedNum::alloc(&mut cs, value)?;
+    check(&mut cs, &num)?;
+
+    Ok(num)
+}
+
+/// Allocates the number of `bits` which are set, enforcing it with one
+/// constraint on the sum of the bits.
+pub fn popcount<E, CS>(mut cs: CS, bits: &[Boolean]) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    let count = AllocatedNum::alloc(cs.namespace(|| "count"), || {
+        let mut count = E::Fr::zero();
+        for bit in bits {
+            if *bit.get_value().get()? {
+                count.add_assign(&E::Fr::one());
+            }
+        }
+
+        Ok(count)
+    })?;
+
+    let mut sum = LinearCombination::zero();
+    for bit in bits {
+        sum = sum + &bit.lc(CS::one(), E::Fr::one());
+    }
+
+    cs.enforce(
+        || "popcount",
+        |_| sum,
+        |lc| lc + CS::one(),
+        |lc| lc + count.variable,
+    );
+
+    Ok(count)
+}
+
+/// Enforces `nums[i] <= nums[i + 1]` for every adjacent pair, by showing that
+/// every number and every difference `nums[i + 1] - nums[i]` fits into
+/// `num_bits` bits. `num_bits` must be less than the field's capacity so that
+/// the differences cannot wrap around the modulus.
+pub fn enforce_sorted<E, CS>(
+    mut cs: CS,
+    nums: &[AllocatedNum<E>],
+    num_bits: usize,
+) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    CS: ConstraintSystem<E>,
+{
+    assert_bits_fit_field::<E>(num_bits + 1)?;
+
+    for (i, num) in nums.iter().enumerate() {
+        enforce_fits_in_bits(
+            cs.namespace(|| format!("num {}", i)),
+            num.value,
+            LinearCombination::zero() + num.variable,
+            num_bits,
+        )?;
+    }
+
+    for (i, pair) in nums.windows(2).enumerate() {
+        let diff = pair[1].value.and_then(|b| {
+            pair[0].value.map(|a| {
+                let mut tmp = b;
+                tmp.sub_assign(&a);
+                tmp
+            })
+        });
+
+        enforce_fits_in_bits(
+            cs.namespace(|| format!("num {} - num {}", i + 1, i)),
+            diff,
+            LinearCombination::zero() + pair[1].variable - pair[0].variable,
+            num_bits,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Allocates the `num_bits` lowest bits of `value` and enforces that they
+/// recompose to `lc`, which is only satisfiable if `lc` is below `2^num_bits`.
+pub(crate) fn enforce_fits_in_bits<E, CS>(
+    cs: CS,
+    value: Option<E::Fr>,
+    lc: LinearCombination<E>,
+    num_bits: usize,
+) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    CS: ConstraintSystem<E>,
+{
+    decompose_le(cs, value, lc, num_bits).map(|_| ())
+}
+
+/// Like `enforce_fits_in_bits`, but returns the allocated bits, least
+/// significant first.
+fn decompose_le<E, CS>(
+    mut cs: CS,
+    value: Option<E::Fr>,
+    lc: LinearCombination<E>,
+    num_bits: usize,
+) -> Result<Vec<AllocatedBit>, SynthesisError>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    CS: ConstraintSystem<E>,
+{
+    let values: Vec<Option<bool>> = match value {
+        Some(value) => value
+            .to_le_bits()
+            .into_iter()
+            .take(num_bits)
+            .map(Some)
+            .collect(),
+        None => vec![None; num_bits],
+    };
+
+    let mut bits = Vec::with_capacity(num_bits);
+    let mut packed = LinearCombination::zero();
+    let mut coeff = E::Fr::one();
+    for (i, b) in values.into_iter().enumerate() {
+        let bit = AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), b)?;
+        packed = packed + (coeff, bit.get_variable());
+        bits.push(bit);
+
+        coeff = coeff.double();
+    }
+
+    cs.enforce(
+        || "unpacking constraint",
+        |_| packed,
+        |lc| lc + CS::one(),
+        |_| lc,
+    );
+
+    Ok(bits)
+}
+
 #[derive(Clone)]
 pub struct Num<E: Engine> {
     value: Option<E::Fr>,
@@ -451,13 +991,17 @@ impl<E: Engine> Num<E> {
 mod test {
     use std::ops::{AddAssign, MulAssign, SubAssign};
 
-    use crate::ConstraintSystem;
+    use crate::{ConstraintSystem, SynthesisError};
     use blstrs::{Bls12, Scalar as Fr};
     use ff::{Field, PrimeField, PrimeFieldBits};
-    use rand_core::SeedableRng;
+    use rand_core::{RngCore, SeedableRng};
     use rand_xorshift::XorShiftRng;
 
-    use super::{AllocatedNum, Boolean, Num};
+    use super::{
+        alloc_with_check, batch_invert, conditional_swap, conditionally_select, enforce_sorted,
+        enforce_valid_index, less_than, max, popcount, AllocatedNum, Boolean, Num,
+    };
+    use crate::gadgets::boolean::AllocatedBit;
     use crate::gadgets::test::*;
 
     #[test]
@@ -469,6 +1013,68 @@ mod test {
         assert!(cs.get("num") == Fr::one());
     }
 
+    #[test]
+    fn test_popcount() {
+        let pattern = [true, false, true, true, false, false, true, false, true];
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut bits = pattern
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                Boolean::from(
+                    AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), Some(*b)).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        // Negated and constant bits count too.
+        bits[1] = bits[1].not();
+        bits.push(Boolean::constant(true));
+        bits.push(Boolean::constant(false));
+
+        let before = cs.num_constraints();
+        let count = popcount(cs.namespace(|| "popcount"), &bits).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(count.get_value(), Some(Fr::from(7u64)));
+        assert_eq!(cs.num_constraints(), before + 1);
+
+        cs.set("popcount/count/num", Fr::from(6u64));
+        assert_eq!(cs.which_is_unsatisfied(), Some("popcount/popcount"));
+    }
+
+    #[test]
+    fn test_alloc_with_check() {
+        let boolean = |cs: &mut &mut TestConstraintSystem<Bls12>, num: &AllocatedNum<Bls12>| {
+            cs.enforce(
+                || "boolean",
+                |lc| lc + num.get_variable(),
+                |lc| lc + TestConstraintSystem::<Bls12>::one() - num.get_variable(),
+                |lc| lc,
+            );
+            Ok(())
+        };
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let num = alloc_with_check(&mut cs, || Ok(Fr::one()), boolean).unwrap();
+        assert_eq!(num.get_value(), Some(Fr::one()));
+        assert_eq!(cs.num_constraints(), 1);
+        assert!(cs.is_satisfied());
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        alloc_with_check(&mut cs, || Ok(Fr::from(2u64)), boolean).unwrap();
+        assert_eq!(cs.which_is_unsatisfied(), Some("boolean"));
+
+        // Errors from the check are returned.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        assert!(matches!(
+            alloc_with_check(
+                &mut cs,
+                || Ok(Fr::one()),
+                |_, _| Err(SynthesisError::Unsatisfiable)
+            ),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+    }
+
     #[test]
     fn test_num_squaring() {
         let mut cs = TestConstraintSystem::<Bls12>::new();
@@ -533,6 +1139,375 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_conditional_swap() {
+        for &swap in &[false, true] {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::from(5u64))).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::from(9u64))).unwrap();
+            let condition = Boolean::from(
+                AllocatedBit::alloc(cs.namespace(|| "condition"), Some(swap)).unwrap(),
+            );
+            let before = cs.num_constraints();
+
+            let (c, d) = conditional_swap(cs.namespace(|| "swap"), &a, &b, &condition).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints() - before, 2);
+            if swap {
+                assert_eq!(c.get_value(), b.get_value());
+                assert_eq!(d.get_value(), a.get_value());
+            } else {
+                assert_eq!(c.get_value(), a.get_value());
+                assert_eq!(d.get_value(), b.get_value());
+            }
+        }
+    }
+
+    #[test]
+    fn test_conditionally_select() {
+        for &select_a in &[false, true] {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::from(5u64))).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::from(9u64))).unwrap();
+            let condition = Boolean::from(
+                AllocatedBit::alloc(cs.namespace(|| "condition"), Some(select_a)).unwrap(),
+            );
+            let before = cs.num_constraints();
+
+            let selected =
+                conditionally_select(cs.namespace(|| "select"), &a, &b, &condition).unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints() - before, 1);
+            let expected = if select_a { &a } else { &b };
+            assert_eq!(selected.get_value(), expected.get_value());
+
+            // The other number does not satisfy the constraint.
+            let other = if select_a { &b } else { &a };
+            cs.set("select/selected/num", other.get_value().unwrap());
+            assert_eq!(
+                cs.which_is_unsatisfied(),
+                Some("select/conditional selection")
+            );
+
+            // Negated conditions select the other number.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::from(5u64))).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::from(9u64))).unwrap();
+            let condition = Boolean::from(
+                AllocatedBit::alloc(cs.namespace(|| "condition"), Some(select_a)).unwrap(),
+            )
+            .not();
+            let selected =
+                conditionally_select(cs.namespace(|| "select"), &a, &b, &condition).unwrap();
+            assert!(cs.is_satisfied());
+            let expected = if select_a { &b } else { &a };
+            assert_eq!(selected.get_value(), expected.get_value());
+
+            // Constant conditions add no constraints.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::from(5u64))).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::from(9u64))).unwrap();
+            let selected = conditionally_select(
+                cs.namespace(|| "select"),
+                &a,
+                &b,
+                &Boolean::constant(select_a),
+            )
+            .unwrap();
+            assert_eq!(cs.num_constraints(), 0);
+            let expected = if select_a { &a } else { &b };
+            assert_eq!(selected.get_variable(), expected.get_variable());
+        }
+    }
+
+    #[test]
+    fn test_num_is_zero() {
+        for &value in &[0u64, 1, 42] {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let x = AllocatedNum::alloc(cs.namespace(|| "x"), || Ok(Fr::from(value))).unwrap();
+            let is_zero = AllocatedNum::is_zero(cs.namespace(|| "is_zero"), &x).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 3);
+            assert_eq!(is_zero.get_value(), Some(value == 0));
+
+            // Lying about the result is unsatisfiable, whatever the inverse.
+            let lie = if value == 0 { Fr::zero() } else { Fr::one() };
+            cs.set("is_zero/is zero/boolean", lie);
+            for inv in &[
+                Fr::zero(),
+                Fr::one(),
+                Fr::from(value).invert().unwrap_or(Fr::one()),
+            ] {
+                cs.set("is_zero/inverse", *inv);
+                assert!(!cs.is_satisfied());
+            }
+        }
+    }
+
+    #[test]
+    fn test_enforce_in_range() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(cs.namespace(|| "n"), || Ok(Fr::from(255u64))).unwrap();
+        let bits = AllocatedNum::enforce_in_range(cs.namespace(|| "range"), &n, 8).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 9);
+        assert_eq!(bits.len(), 8);
+        assert!(bits.iter().all(|bit| bit.get_value() == Some(true)));
+
+        // The packing constraint rejects a forged decomposition.
+        cs.set("range/bit 0/boolean", Fr::zero());
+        assert_eq!(
+            cs.which_is_unsatisfied(),
+            Some("range/unpacking constraint")
+        );
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(cs.namespace(|| "n"), || Ok(Fr::from(256u64))).unwrap();
+        assert!(matches!(
+            AllocatedNum::enforce_in_range(cs.namespace(|| "range"), &n, 8),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+    }
+
+    #[test]
+    fn test_less_than() {
+        // a < b, a == b, a > b, including the ends of the range.
+        let pairs = [(3u64, 9u64), (0, 15), (7, 7), (15, 15), (9, 3), (15, 0)];
+        for &(a_value, b_value) in &pairs {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::from(a_value))).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::from(b_value))).unwrap();
+
+            let lt = less_than(cs.namespace(|| "lt"), &a, &b, 4).unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(lt.get_value(), Some(a_value < b_value));
+
+            // The result is an allocated bit bound by the decomposition.
+            match lt {
+                Boolean::Is(ref bit) => assert_eq!(
+                    cs.get("lt/bit 4/boolean"),
+                    if bit.get_value().unwrap() {
+                        Fr::one()
+                    } else {
+                        Fr::zero()
+                    }
+                ),
+                _ => panic!("expected an allocated bit"),
+            }
+            let forged = if a_value < b_value {
+                Fr::zero()
+            } else {
+                Fr::one()
+            };
+            cs.set("lt/bit 4/boolean", forged);
+            assert_eq!(cs.which_is_unsatisfied(), Some("lt/unpacking constraint"));
+        }
+    }
+
+    #[test]
+    fn test_max() {
+        let values = [13u64, 200, 7, 200, 55, 199];
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let nums = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("num {}", i)), || Ok(Fr::from(*v)))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let result = max(cs.namespace(|| "max"), &nums, 8).unwrap();
+        assert!(cs.is_satisfied());
+        let expected = *values.iter().max().unwrap();
+        assert_eq!(result.get_value(), Some(Fr::from(expected)));
+
+        // A forged maximum is unsatisfiable.
+        cs.set("max/step 4/select/selected/num", Fr::from(201u64));
+        assert_eq!(
+            cs.which_is_unsatisfied(),
+            Some("max/step 4/select/conditional selection")
+        );
+
+        // Numbers out of range are rejected.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let nums = [3u64, 256]
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("num {}", i)), || Ok(Fr::from(*v)))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        max(cs.namespace(|| "max"), &nums, 8).unwrap();
+        assert_eq!(
+            cs.which_is_unsatisfied(),
+            Some("max/num 1/unpacking constraint")
+        );
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        assert!(matches!(
+            max::<Bls12, _>(&mut cs, &[], 8),
+            Err(SynthesisError::IncompatibleLengthVector(_))
+        ));
+    }
+
+    #[test]
+    fn test_pow_constant() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &exp in &[0u64, 1, 2, 3, 5, 8, 13, 255, (1 << 20) + 7, u64::MAX] {
+            let base_value = Fr::random(&mut rng);
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let base = AllocatedNum::alloc(cs.namespace(|| "base"), || Ok(base_value)).unwrap();
+            let result = AllocatedNum::pow_constant(cs.namespace(|| "pow"), &base, exp).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(result.get_value(), Some(base_value.pow_vartime(&[exp])));
+
+            let expected_constraints = if exp == 0 {
+                0
+            } else {
+                // One squaring per bit after the first, one multiplication per
+                // set bit after the first.
+                (63 - exp.leading_zeros() + exp.count_ones() - 1) as usize
+            };
+            assert_eq!(cs.num_constraints(), expected_constraints);
+        }
+
+        // The result is bound to the base.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let base = AllocatedNum::alloc(cs.namespace(|| "base"), || Ok(Fr::from(3u64))).unwrap();
+        let result = AllocatedNum::pow_constant(cs.namespace(|| "pow"), &base, 5).unwrap();
+        assert_eq!(result.get_value(), Some(Fr::from(243u64)));
+        cs.set("base/num", Fr::from(2u64));
+        assert_eq!(
+            cs.which_is_unsatisfied(),
+            Some("pow/square 1/squaring constraint")
+        );
+    }
+
+    #[test]
+    fn test_batch_invert() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &n in &[0, 1, 4, 9] {
+            let values = (0..n).map(|_| Fr::random(&mut rng)).collect::<Vec<_>>();
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let nums = values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    AllocatedNum::alloc(cs.namespace(|| format!("num {}", i)), || Ok(*v)).unwrap()
+                })
+                .collect::<Vec<_>>();
+            let inverses = batch_invert(cs.namespace(|| "invert"), &nums).unwrap();
+            assert!(cs.is_satisfied());
+            for (value, inverse) in values.iter().zip(inverses.iter()) {
+                assert_eq!(inverse.get_value(), Some(value.invert().unwrap()));
+            }
+
+            assert_eq!(cs.num_constraints(), n);
+
+            if n > 0 {
+                cs.set("invert/value 0/inverse/num", Fr::one());
+                assert_eq!(
+                    cs.which_is_unsatisfied(),
+                    Some("invert/value 0/inverse constraint")
+                );
+            }
+        }
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let nums = [Fr::from(2u64), Fr::zero(), Fr::from(3u64)]
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("num {}", i)), || Ok(*v)).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            batch_invert(cs.namespace(|| "invert"), &nums),
+            Err(SynthesisError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn test_from_bits_le() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..10 {
+            let value = Fr::from(rng.next_u64());
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let n = AllocatedNum::alloc(cs.namespace(|| "n"), || Ok(value)).unwrap();
+            let bits = n.to_bits_le(cs.namespace(|| "to bits")).unwrap();
+            let packed =
+                AllocatedNum::from_bits_le(cs.namespace(|| "from bits"), &bits[..64]).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(packed.get_value(), Some(value));
+
+            cs.set("from bits/input/num", value + Fr::one());
+            assert_eq!(
+                cs.which_is_unsatisfied(),
+                Some("from bits/packing constraint")
+            );
+        }
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let bits = vec![Boolean::constant(true); Fr::CAPACITY as usize + 1];
+        assert!(matches!(
+            AllocatedNum::from_bits_le(cs.namespace(|| "from bits"), &bits),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+        AllocatedNum::from_bits_le(cs.namespace(|| "from capacity bits"), &bits[1..]).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_valid_index() {
+        for &len in &[1usize, 2, 5, 8, 9] {
+            for index in 0..len + 3 {
+                let mut cs = TestConstraintSystem::<Bls12>::new();
+                let n =
+                    AllocatedNum::alloc(cs.namespace(|| "index"), || Ok(Fr::from(index as u64)))
+                        .unwrap();
+                enforce_valid_index(cs.namespace(|| "valid index"), &n, len).unwrap();
+                assert_eq!(cs.is_satisfied(), index < len, "{} of {}", index, len);
+            }
+        }
+
+        // Indices which wrap around the modulus are rejected too.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(cs.namespace(|| "index"), || Ok(-Fr::one())).unwrap();
+        enforce_valid_index(cs.namespace(|| "valid index"), &n, 8).unwrap();
+        assert!(!cs.is_satisfied());
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(cs.namespace(|| "index"), || Ok(Fr::zero())).unwrap();
+        assert!(matches!(
+            enforce_valid_index(cs.namespace(|| "valid index"), &n, 0),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+    }
+
     #[test]
     fn test_num_nonzero() {
         {
@@ -553,6 +1528,81 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_enforce_less_than_constant() {
+        let cases = [
+            // (value, bound, satisfied)
+            (0u64, 10u64, true),
+            (9, 10, true),
+            (10, 10, false),
+            (11, 10, false),
+            (255, 256, true),
+            (256, 256, false),
+            // Larger than 2^num_bits.
+            (300, 256, false),
+            (1 << 20, 256, false),
+        ];
+
+        for &(value, bound, satisfied) in cases.iter() {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let n = AllocatedNum::alloc(&mut cs, || Ok(Fr::from(value))).unwrap();
+            n.enforce_less_than_constant(&mut cs, Fr::from(bound), 8)
+                .unwrap();
+
+            assert_eq!(cs.is_satisfied(), satisfied, "{} < {}", value, bound);
+        }
+
+        // Values close to the modulus must not wrap around.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(&mut cs, || Ok(-Fr::one())).unwrap();
+        n.enforce_less_than_constant(&mut cs, Fr::from(10u64), 8)
+            .unwrap();
+        assert!(!cs.is_satisfied());
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(&mut cs, || Ok(Fr::one())).unwrap();
+        assert!(n
+            .enforce_less_than_constant(&mut cs, Fr::from(10u64), Fr::CAPACITY as usize)
+            .is_err());
+    }
+
+    #[test]
+    fn test_enforce_sorted() {
+        let cases: [(&[u64], bool); 6] = [
+            (&[], true),
+            (&[7], true),
+            (&[0, 1, 1, 5, 255], true),
+            (&[0, 2, 1, 5], false),
+            (&[5, 4], false),
+            // Larger than 2^num_bits.
+            (&[1, 256], false),
+        ];
+
+        for &(values, satisfied) in cases.iter() {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let nums = values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    AllocatedNum::alloc(cs.namespace(|| format!("num {}", i)), || Ok(Fr::from(*v)))
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            enforce_sorted(cs.namespace(|| "sorted"), &nums, 8).unwrap();
+
+            assert_eq!(cs.is_satisfied(), satisfied, "{:?}", values);
+        }
+
+        // Differences close to the modulus must not wrap around.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::one())).unwrap();
+        let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::zero())).unwrap();
+        enforce_sorted(cs.namespace(|| "sorted"), &[a, b], 8).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
     #[test]
     fn test_into_bits_strict() {
         let negone = -Fr::one();
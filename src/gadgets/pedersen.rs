@@ -0,0 +1,934 @@
+//! Pedersen hash gadget using windowed fixed-base multiplication over an
+//! embedded twisted Edwards curve.
+//!
+//! This is the construction used by Sapling: the input is split into 3-bit
+//! chunks, each chunk selects a signed multiple of a window generator, and the
+//! windows of each segment are accumulated in Montgomery form before the
+//! segments are added together on the Edwards curve.
+//!
+//! The Edwards point gadgets, and fixed-base multiplication built on them, are
+//! exposed for other uses of the curve. See [`pedersen_hash`] for the hash with
+//! Sapling's generators over Jubjub.
+//!
+//! [`pedersen_hash`]: super::pedersen_hash
+
+use std::ops::{AddAssign, MulAssign, SubAssign};
+
+use blstrs::Scalar;
+use ff::Field;
+use pairing::Engine;
+
+use super::boolean::Boolean;
+use super::lookup::{lookup3_xy, lookup3_xy_with_conditional_negation};
+use super::num::{AllocatedNum, Num};
+use super::Assignment;
+use crate::{ConstraintSystem, SynthesisError};
+
+/// Parameters of an embedded twisted Edwards curve `-u^2 + v^2 = 1 + d u^2 v^2`
+/// defined over `E::Fr`, along with its birationally equivalent Montgomery
+/// curve `y^2 = x^3 + A x^2 + x`.
+#[derive(Clone, Debug)]
+pub struct EdwardsCurve<E: Engine> {
+    edwards_d: E::Fr,
+    montgomery_a: E::Fr,
+    montgomery_scale: E::Fr,
+}
+
+impl<E: Engine> EdwardsCurve<E> {
+    /// Derives the Montgomery form of the curve with Edwards parameter `d`.
+    /// Returns `None` if the curve has no Montgomery form with `B = 1` over
+    /// `E::Fr`.
+    ///
+    /// For Jubjub over the BLS12-381 scalar field, `d = -(10240/10241)`.
+    pub fn new(edwards_d: E::Fr) -> Option<Self> {
+        // A = 2(a + d) / (a - d) and B = 4 / (a - d), with a = -1.
+        let a_minus_d = -E::Fr::one() - edwards_d;
+        let a_minus_d_inv: Option<E::Fr> = a_minus_d.invert().into();
+        let a_minus_d_inv = a_minus_d_inv?;
+
+        let mut montgomery_a = edwards_d - E::Fr::one();
+        montgomery_a = montgomery_a.double();
+        montgomery_a.mul_assign(&a_minus_d_inv);
+
+        let mut montgomery_b = E::Fr::one().double().double();
+        montgomery_b.mul_assign(&a_minus_d_inv);
+        let montgomery_scale: Option<E::Fr> = montgomery_b.sqrt().into();
+
+        Some(EdwardsCurve {
+            edwards_d,
+            montgomery_a,
+            montgomery_scale: montgomery_scale?,
+        })
+    }
+
+    pub fn edwards_d(&self) -> &E::Fr {
+        &self.edwards_d
+    }
+
+    pub fn montgomery_a(&self) -> &E::Fr {
+        &self.montgomery_a
+    }
+
+    /// Scaling factor of the map from the Montgomery curve to the Edwards
+    /// curve, `u = scale * x / y`.
+    pub fn montgomery_scale(&self) -> &E::Fr {
+        &self.montgomery_scale
+    }
+}
+
+impl<E: Engine<Fr = Scalar>> EdwardsCurve<E> {
+    /// Jubjub, the curve embedded in the BLS12-381 scalar field.
+    pub fn jubjub() -> Self {
+        let d = -(Scalar::from(10240u64) * Scalar::from(10241u64).invert().unwrap());
+        Self::new(d).expect("Jubjub has a Montgomery form")
+    }
+}
+
+/// Computes the Pedersen hash of `personalization || bits`, returning the
+/// Edwards coordinates `(u, v)` of the resulting point.
+///
+/// `generators[i][j]` holds the Montgomery coordinates of `[1, 2, 3, 4] * G_ij`,
+/// where `G_ij = 16^j * G_i` is the generator of window `j` of segment `i`.
+/// Within a segment, the window generators must be such that no two partial
+/// sums share an x-coordinate, which holds when each segment's scalar stays
+/// below half the order of the generator.
+///
+/// # Panics
+///
+/// Panics if the input is empty, if a segment of `generators` is empty, or if
+/// there are not enough generators for the input.
+#[allow(clippy::type_complexity)]
+pub fn pedersen_hash<E, CS>(
+    cs: CS,
+    personalization: &[bool],
+    bits: &[Boolean],
+    generators: &[Vec<Vec<(E::Fr, E::Fr)>>],
+    curve: &EdwardsCurve<E>,
+) -> Result<(AllocatedNum<E>, AllocatedNum<E>), SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    let result = pedersen_hash_edwards(cs, personalization, bits, generators, curve)?;
+
+    Ok((result.u, result.v))
+}
+
+/// Computes the Pedersen hash of `personalization || bits` like
+/// `pedersen_hash`, returning the resulting point.
+#[allow(clippy::type_complexity)]
+pub(crate) fn pedersen_hash_edwards<E, CS>(
+    mut cs: CS,
+    personalization: &[bool],
+    bits: &[Boolean],
+    generators: &[Vec<Vec<(E::Fr, E::Fr)>>],
+    curve: &EdwardsCurve<E>,
+) -> Result<EdwardsPoint<E>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    assert!(
+        generators.iter().all(|segment| !segment.is_empty()),
+        "generator segments must not be empty"
+    );
+
+    let personalization = personalization
+        .iter()
+        .map(|&b| Boolean::constant(b))
+        .collect::<Vec<_>>();
+
+    let mut edwards_result: Option<EdwardsPoint<E>> = None;
+    let mut bits = personalization.iter().chain(bits.iter()).peekable();
+    let mut segment_generators = generators.iter();
+    let boolean_false = Boolean::constant(false);
+
+    let mut segment_i = 0;
+    while bits.peek().is_some() {
+        let mut segment_result: Option<MontgomeryPoint<E>> = None;
+        let mut segment_windows = &segment_generators
+            .next()
+            .expect("not enough generators for the input")[..];
+
+        let mut window_i = 0;
+        while let Some(a) = bits.next() {
+            let b = bits.next().unwrap_or(&boolean_false);
+            let c = bits.next().unwrap_or(&boolean_false);
+
+            let (x, y) = lookup3_xy_with_conditional_negation(
+                cs.namespace(|| format!("segment {}, window {}", segment_i, window_i)),
+                &[a.clone(), b.clone(), c.clone()],
+                &segment_windows[0],
+            )?;
+            let tmp = MontgomeryPoint { x, y };
+
+            segment_result = Some(match segment_result {
+                None => tmp,
+                Some(segment_result) => tmp.add(
+                    cs.namespace(|| {
+                        format!("addition of segment {}, window {}", segment_i, window_i)
+                    }),
+                    &segment_result,
+                    curve,
+                )?,
+            });
+
+            segment_windows = &segment_windows[1..];
+            if segment_windows.is_empty() {
+                break;
+            }
+
+            window_i += 1;
+        }
+
+        let segment_result = segment_result
+            .expect("segment has at least one window")
+            .into_edwards(
+                cs.namespace(|| format!("conversion of segment {} into edwards", segment_i)),
+                curve,
+            )?;
+
+        edwards_result = Some(match edwards_result {
+            None => segment_result,
+            Some(edwards_result) => segment_result.add(
+                cs.namespace(|| format!("addition of segment {} to accumulator", segment_i)),
+                &edwards_result,
+                curve,
+            )?,
+        });
+
+        segment_i += 1;
+    }
+
+    Ok(edwards_result.expect("pedersen hash input must not be empty"))
+}
+
+/// Multiplies a fixed base point by the scalar with little-endian bits `by`,
+/// using windows of 3 bits.
+///
+/// `windows[i]` holds the Edwards coordinates of `[0, 1, ..., 7] * B_i`,
+/// where `B_i = 8^i * B` is the base of window `i`, so that each window costs
+/// a table lookup and a complete addition.
+///
+/// # Panics
+///
+/// Panics if `by` is empty, or if there are not enough windows for it.
+pub fn fixed_base_multiplication<E, CS>(
+    mut cs: CS,
+    by: &[Boolean],
+    windows: &[Vec<(E::Fr, E::Fr)>],
+    curve: &EdwardsCurve<E>,
+) -> Result<EdwardsPoint<E>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    assert!(!by.is_empty(), "scalar must not be empty");
+    assert!(
+        windows.len() * 3 >= by.len(),
+        "not enough windows for the scalar"
+    );
+
+    let boolean_false = Boolean::constant(false);
+    let mut result: Option<EdwardsPoint<E>> = None;
+    for (i, (chunk, window)) in by.chunks(3).zip(windows).enumerate() {
+        let bit = |j: usize| chunk.get(j).unwrap_or(&boolean_false).clone();
+
+        let (u, v) = lookup3_xy(
+            cs.namespace(|| format!("window table lookup {}", i)),
+            &[bit(0), bit(1), bit(2)],
+            window,
+        )?;
+        let p = EdwardsPoint { u, v };
+
+        result = Some(match result {
+            None => p,
+            Some(result) => result.add(cs.namespace(|| format!("addition {}", i)), &p, curve)?,
+        });
+    }
+
+    Ok(result.expect("scalar has at least one window"))
+}
+
+fn div<E: Engine>(mut num: E::Fr, den: E::Fr) -> Result<E::Fr, SynthesisError> {
+    let den: Option<E::Fr> = den.invert().into();
+    num.mul_assign(&den.ok_or(SynthesisError::DivisionByZero)?);
+
+    Ok(num)
+}
+
+/// A point on the Edwards curve, with both coordinates allocated.
+#[derive(Clone)]
+pub struct EdwardsPoint<E: Engine> {
+    u: AllocatedNum<E>,
+    v: AllocatedNum<E>,
+}
+
+impl<E: Engine> EdwardsPoint<E> {
+    pub fn get_u(&self) -> &AllocatedNum<E> {
+        &self.u
+    }
+
+    pub fn get_v(&self) -> &AllocatedNum<E> {
+        &self.v
+    }
+
+    /// Doubles `self`, which is cheaper than adding it to itself.
+    pub fn double<CS>(&self, mut cs: CS, curve: &EdwardsCurve<E>) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        // T = (u + v)^2
+        let t = AllocatedNum::alloc(cs.namespace(|| "T"), || {
+            let mut t0 = *self.u.get_value().get()?;
+            t0.add_assign(self.v.get_value().get()?);
+            Ok(t0.square())
+        })?;
+
+        cs.enforce(
+            || "T computation",
+            |lc| lc + self.u.get_variable() + self.v.get_variable(),
+            |lc| lc + self.u.get_variable() + self.v.get_variable(),
+            |lc| lc + t.get_variable(),
+        );
+
+        // A = u * v
+        let a = self.u.mul(cs.namespace(|| "A computation"), &self.v)?;
+
+        // C = d * A^2
+        let c = AllocatedNum::alloc(cs.namespace(|| "C"), || {
+            let mut t0 = a.get_value().get()?.square();
+            t0.mul_assign(curve.edwards_d());
+            Ok(t0)
+        })?;
+
+        cs.enforce(
+            || "C computation",
+            |lc| lc + (*curve.edwards_d(), a.get_variable()),
+            |lc| lc + a.get_variable(),
+            |lc| lc + c.get_variable(),
+        );
+
+        // u3 = 2A / (1 + C)
+        let u3 = AllocatedNum::alloc(cs.namespace(|| "u3"), || {
+            let t0 = a.get_value().get()?.double();
+
+            let mut t1 = E::Fr::one();
+            t1.add_assign(c.get_value().get()?);
+
+            div::<E>(t0, t1)
+        })?;
+
+        cs.enforce(
+            || "u3 computation",
+            |lc| lc + CS::one() + c.get_variable(),
+            |lc| lc + u3.get_variable(),
+            |lc| lc + a.get_variable() + a.get_variable(),
+        );
+
+        // v3 = (T - 2A) / (1 - C)
+        let v3 = AllocatedNum::alloc(cs.namespace(|| "v3"), || {
+            let mut t0 = *t.get_value().get()?;
+            t0.sub_assign(&a.get_value().get()?.double());
+
+            let mut t1 = E::Fr::one();
+            t1.sub_assign(c.get_value().get()?);
+
+            div::<E>(t0, t1)
+        })?;
+
+        cs.enforce(
+            || "v3 computation",
+            |lc| lc + CS::one() - c.get_variable(),
+            |lc| lc + v3.get_variable(),
+            |lc| lc + t.get_variable() - a.get_variable() - a.get_variable(),
+        );
+
+        Ok(EdwardsPoint { u: u3, v: v3 })
+    }
+
+    /// Performs a complete addition of `self` and `other`.
+    pub fn add<CS>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+        curve: &EdwardsCurve<E>,
+    ) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        // U = (u1 + v1) * (u2 + v2)
+        let uppercase_u = AllocatedNum::alloc(cs.namespace(|| "U"), || {
+            let mut t0 = *self.u.get_value().get()?;
+            t0.add_assign(self.v.get_value().get()?);
+
+            let mut t1 = *other.u.get_value().get()?;
+            t1.add_assign(other.v.get_value().get()?);
+
+            t0.mul_assign(&t1);
+            Ok(t0)
+        })?;
+
+        cs.enforce(
+            || "U computation",
+            |lc| lc + self.u.get_variable() + self.v.get_variable(),
+            |lc| lc + other.u.get_variable() + other.v.get_variable(),
+            |lc| lc + uppercase_u.get_variable(),
+        );
+
+        // A = v2 * u1
+        let a = other.v.mul(cs.namespace(|| "A computation"), &self.u)?;
+
+        // B = u2 * v1
+        let b = other.u.mul(cs.namespace(|| "B computation"), &self.v)?;
+
+        // C = d * A * B
+        let c = AllocatedNum::alloc(cs.namespace(|| "C"), || {
+            let mut t0 = *a.get_value().get()?;
+            t0.mul_assign(b.get_value().get()?);
+            t0.mul_assign(curve.edwards_d());
+            Ok(t0)
+        })?;
+
+        cs.enforce(
+            || "C computation",
+            |lc| lc + (*curve.edwards_d(), a.get_variable()),
+            |lc| lc + b.get_variable(),
+            |lc| lc + c.get_variable(),
+        );
+
+        // u3 = (A + B) / (1 + C)
+        let u3 = AllocatedNum::alloc(cs.namespace(|| "u3"), || {
+            let mut t0 = *a.get_value().get()?;
+            t0.add_assign(b.get_value().get()?);
+
+            let mut t1 = E::Fr::one();
+            t1.add_assign(c.get_value().get()?);
+
+            div::<E>(t0, t1)
+        })?;
+
+        cs.enforce(
+            || "u3 computation",
+            |lc| lc + CS::one() + c.get_variable(),
+            |lc| lc + u3.get_variable(),
+            |lc| lc + a.get_variable() + b.get_variable(),
+        );
+
+        // v3 = (U - A - B) / (1 - C)
+        let v3 = AllocatedNum::alloc(cs.namespace(|| "v3"), || {
+            let mut t0 = *uppercase_u.get_value().get()?;
+            t0.sub_assign(a.get_value().get()?);
+            t0.sub_assign(b.get_value().get()?);
+
+            let mut t1 = E::Fr::one();
+            t1.sub_assign(c.get_value().get()?);
+
+            div::<E>(t0, t1)
+        })?;
+
+        cs.enforce(
+            || "v3 computation",
+            |lc| lc + CS::one() - c.get_variable(),
+            |lc| lc + v3.get_variable(),
+            |lc| lc + uppercase_u.get_variable() - a.get_variable() - b.get_variable(),
+        );
+
+        Ok(EdwardsPoint { u: u3, v: v3 })
+    }
+}
+
+/// A point on the Montgomery curve, which is never the point at infinity.
+struct MontgomeryPoint<E: Engine> {
+    x: Num<E>,
+    y: Num<E>,
+}
+
+impl<E: Engine> MontgomeryPoint<E> {
+    /// Converts the point into Edwards form.
+    fn into_edwards<CS>(
+        self,
+        mut cs: CS,
+        curve: &EdwardsCurve<E>,
+    ) -> Result<EdwardsPoint<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        // u = (scale * x) / y
+        let u = AllocatedNum::alloc(cs.namespace(|| "u"), || {
+            let mut t0 = *self.x.get_value().get()?;
+            t0.mul_assign(curve.montgomery_scale());
+
+            div::<E>(t0, *self.y.get_value().get()?)
+        })?;
+
+        cs.enforce(
+            || "u computation",
+            |lc| lc + &self.y.lc(E::Fr::one()),
+            |lc| lc + u.get_variable(),
+            |lc| lc + &self.x.lc(*curve.montgomery_scale()),
+        );
+
+        // v = (x - 1) / (x + 1)
+        let v = AllocatedNum::alloc(cs.namespace(|| "v"), || {
+            let x = *self.x.get_value().get()?;
+
+            div::<E>(x - E::Fr::one(), x + E::Fr::one())
+        })?;
+
+        let one = CS::one();
+        cs.enforce(
+            || "v computation",
+            |lc| lc + &self.x.lc(E::Fr::one()) + one,
+            |lc| lc + v.get_variable(),
+            |lc| lc + &self.x.lc(E::Fr::one()) - one,
+        );
+
+        Ok(EdwardsPoint { u, v })
+    }
+
+    /// Adds `self` and `other`, which must have distinct x-coordinates.
+    fn add<CS>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+        curve: &EdwardsCurve<E>,
+    ) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        // lambda = (y' - y) / (x' - x)
+        let lambda = AllocatedNum::alloc(cs.namespace(|| "lambda"), || {
+            let mut n = *other.y.get_value().get()?;
+            n.sub_assign(self.y.get_value().get()?);
+
+            let mut d = *other.x.get_value().get()?;
+            d.sub_assign(self.x.get_value().get()?);
+
+            div::<E>(n, d)
+        })?;
+
+        cs.enforce(
+            || "evaluate lambda",
+            |lc| lc + &other.x.lc(E::Fr::one()) - &self.x.lc(E::Fr::one()),
+            |lc| lc + lambda.get_variable(),
+            |lc| lc + &other.y.lc(E::Fr::one()) - &self.y.lc(E::Fr::one()),
+        );
+
+        // x'' = lambda^2 - A - x - x'
+        let xprime = AllocatedNum::alloc(cs.namespace(|| "xprime"), || {
+            let mut t0 = lambda.get_value().get()?.square();
+            t0.sub_assign(curve.montgomery_a());
+            t0.sub_assign(self.x.get_value().get()?);
+            t0.sub_assign(other.x.get_value().get()?);
+            Ok(t0)
+        })?;
+
+        // (lambda) * (lambda) = (A + x + x' + x'')
+        let one = CS::one();
+        cs.enforce(
+            || "evaluate xprime",
+            |lc| lc + lambda.get_variable(),
+            |lc| lc + lambda.get_variable(),
+            |lc| {
+                lc + (*curve.montgomery_a(), one)
+                    + &self.x.lc(E::Fr::one())
+                    + &other.x.lc(E::Fr::one())
+                    + xprime.get_variable()
+            },
+        );
+
+        // y'' = -(y + lambda(x'' - x))
+        let yprime = AllocatedNum::alloc(cs.namespace(|| "yprime"), || {
+            let mut t0 = *xprime.get_value().get()?;
+            t0.sub_assign(self.x.get_value().get()?);
+            t0.mul_assign(lambda.get_value().get()?);
+            t0.add_assign(self.y.get_value().get()?);
+            Ok(-t0)
+        })?;
+
+        // y'' + y = lambda(x - x'')
+        cs.enforce(
+            || "evaluate yprime",
+            |lc| lc + &self.x.lc(E::Fr::one()) - xprime.get_variable(),
+            |lc| lc + lambda.get_variable(),
+            |lc| lc + yprime.get_variable() + &self.y.lc(E::Fr::one()),
+        );
+
+        Ok(MontgomeryPoint {
+            x: xprime.into(),
+            y: yprime.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::test::*;
+    use blstrs::{Bls12, Scalar as Fr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    const WINDOWS_PER_SEGMENT: usize = 4;
+    const SEGMENTS: usize = 3;
+
+    /// Reference implementation of the Edwards curve arithmetic.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Point {
+        u: Fr,
+        v: Fr,
+    }
+
+    impl Point {
+        fn identity() -> Self {
+            Point {
+                u: Fr::zero(),
+                v: Fr::one(),
+            }
+        }
+
+        fn add(&self, other: &Self, curve: &EdwardsCurve<Bls12>) -> Self {
+            let uu = self.u * other.u;
+            let vv = self.v * other.v;
+            let c = *curve.edwards_d() * uu * vv;
+
+            Point {
+                u: (self.u * other.v + self.v * other.u) * (Fr::one() + c).invert().unwrap(),
+                v: (vv + uu) * (Fr::one() - c).invert().unwrap(),
+            }
+        }
+
+        fn negate(&self) -> Self {
+            Point {
+                u: -self.u,
+                v: self.v,
+            }
+        }
+
+        fn to_montgomery(self, curve: &EdwardsCurve<Bls12>) -> (Fr, Fr) {
+            let x = (Fr::one() + self.v) * (Fr::one() - self.v).invert().unwrap();
+            let y = *curve.montgomery_scale() * x * self.u.invert().unwrap();
+            (x, y)
+        }
+
+        /// Finds a point in the prime order subgroup, starting the search at `v`.
+        fn find(mut v: Fr, curve: &EdwardsCurve<Bls12>) -> Self {
+            loop {
+                // u^2 = (v^2 - 1) / (d v^2 + 1)
+                let v2 = v.square();
+                let u2 = (v2 - Fr::one()) * (*curve.edwards_d() * v2 + Fr::one()).invert().unwrap();
+                let u: Option<Fr> = u2.sqrt().into();
+                if let Some(u) = u {
+                    let mut p = Point { u, v };
+                    for _ in 0..3 {
+                        p = p.add(&p, curve);
+                    }
+                    if p != Point::identity() {
+                        return p;
+                    }
+                }
+                v += Fr::one();
+            }
+        }
+    }
+
+    struct Generators {
+        /// `[1, 2, 3, 4] * G_ij` in Edwards form.
+        edwards: Vec<Vec<Vec<Point>>>,
+        /// `[1, 2, 3, 4] * G_ij` in Montgomery form.
+        montgomery: Vec<Vec<Vec<(Fr, Fr)>>>,
+    }
+
+    fn generators(curve: &EdwardsCurve<Bls12>) -> Generators {
+        let mut edwards = vec![];
+        for i in 0..SEGMENTS {
+            let mut g = Point::find(Fr::from(2 + 10 * i as u64), curve);
+            let mut windows = vec![];
+            for _ in 0..WINDOWS_PER_SEGMENT {
+                let mut table = vec![g];
+                for _ in 1..4 {
+                    table.push(table.last().unwrap().add(&g, curve));
+                }
+                windows.push(table);
+
+                for _ in 0..4 {
+                    g = g.add(&g, curve);
+                }
+            }
+            edwards.push(windows);
+        }
+
+        let montgomery = edwards
+            .iter()
+            .map(|windows| {
+                windows
+                    .iter()
+                    .map(|table| table.iter().map(|p| p.to_montgomery(curve)).collect())
+                    .collect()
+            })
+            .collect();
+
+        Generators {
+            edwards,
+            montgomery,
+        }
+    }
+
+    fn reference_hash(
+        personalization: &[bool],
+        bits: &[bool],
+        generators: &Generators,
+        curve: &EdwardsCurve<Bls12>,
+    ) -> Point {
+        let input = personalization
+            .iter()
+            .chain(bits.iter())
+            .collect::<Vec<_>>();
+
+        let mut acc = Point::identity();
+        for (i, chunk) in input.chunks(3).enumerate() {
+            let bit = |j: usize| chunk.get(j).map_or(false, |b| **b);
+            let table = &generators.edwards[i / WINDOWS_PER_SEGMENT][i % WINDOWS_PER_SEGMENT];
+
+            let mut term = table[bit(0) as usize + 2 * bit(1) as usize];
+            if bit(2) {
+                term = term.negate();
+            }
+            acc = acc.add(&term, curve);
+        }
+
+        acc
+    }
+
+    #[test]
+    fn test_edwards_curve() {
+        let curve = EdwardsCurve::<Bls12>::jubjub();
+        assert_eq!(curve.montgomery_a(), &Fr::from(40962u64));
+        assert_eq!(curve.montgomery_scale().square(), -Fr::from(40964u64));
+    }
+
+    #[test]
+    fn test_pedersen_hash() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let curve = EdwardsCurve::<Bls12>::jubjub();
+        let generators = generators(&curve);
+        let personalization = [true, true, false, false, true, false];
+        let capacity = SEGMENTS * WINDOWS_PER_SEGMENT * 3 - personalization.len();
+
+        for length in 0..=capacity {
+            let input: Vec<bool> = (0..length).map(|_| rng.next_u32() % 2 != 0).collect();
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let input_bools = input
+                .iter()
+                .enumerate()
+                .map(|(i, b)| {
+                    Boolean::from(
+                        AllocatedBit::alloc(cs.namespace(|| format!("input {}", i)), Some(*b))
+                            .unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let (u, v) = pedersen_hash(
+                cs.namespace(|| "pedersen hash"),
+                &personalization,
+                &input_bools,
+                &generators.montgomery,
+                &curve,
+            )
+            .unwrap();
+
+            assert!(cs.is_satisfied());
+
+            let expected = reference_hash(&personalization, &input, &generators, &curve);
+            assert_eq!(u.get_value().unwrap(), expected.u);
+            assert_eq!(v.get_value().unwrap(), expected.v);
+        }
+    }
+
+    /// Allocates the coordinates of `p`.
+    fn alloc_point<CS: ConstraintSystem<Bls12>>(mut cs: CS, p: &Point) -> EdwardsPoint<Bls12> {
+        EdwardsPoint {
+            u: AllocatedNum::alloc(cs.namespace(|| "u"), || Ok(p.u)).unwrap(),
+            v: AllocatedNum::alloc(cs.namespace(|| "v"), || Ok(p.v)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_edwards_add_and_double() {
+        let curve = EdwardsCurve::<Bls12>::jubjub();
+
+        for i in 0..10 {
+            let p = Point::find(Fr::from(2 + 10 * i as u64), &curve);
+            let q = Point::find(Fr::from(7 + 10 * i as u64), &curve);
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let p_var = alloc_point(cs.namespace(|| "p"), &p);
+            let q_var = alloc_point(cs.namespace(|| "q"), &q);
+
+            let sum = p_var.add(cs.namespace(|| "p + q"), &q_var, &curve).unwrap();
+            let double = p_var.double(cs.namespace(|| "2p"), &curve).unwrap();
+            let num_constraints = cs.num_constraints();
+            let neg_p_var = alloc_point(cs.namespace(|| "-p"), &p.negate());
+            let identity = p_var
+                .add(cs.namespace(|| "p - p"), &neg_p_var, &curve)
+                .unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(num_constraints, 6 + 5);
+
+            let expected = p.add(&q, &curve);
+            assert_eq!(sum.get_u().get_value(), Some(expected.u));
+            assert_eq!(sum.get_v().get_value(), Some(expected.v));
+            let expected = p.add(&p, &curve);
+            assert_eq!(double.get_u().get_value(), Some(expected.u));
+            assert_eq!(double.get_v().get_value(), Some(expected.v));
+            assert_eq!(identity.get_u().get_value(), Some(Fr::zero()));
+            assert_eq!(identity.get_v().get_value(), Some(Fr::one()));
+        }
+    }
+
+    #[test]
+    fn test_fixed_base_multiplication() {
+        const WINDOWS: usize = 5;
+
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let curve = EdwardsCurve::<Bls12>::jubjub();
+        let base = Point::find(Fr::from(5u64), &curve);
+
+        let mut windows = vec![];
+        let mut window_base = base;
+        for _ in 0..WINDOWS {
+            let mut table = vec![Point::identity()];
+            for _ in 1..8 {
+                table.push(table.last().unwrap().add(&window_base, &curve));
+            }
+            windows.push(table.iter().map(|p| (p.u, p.v)).collect::<Vec<_>>());
+
+            for _ in 0..3 {
+                window_base = window_base.add(&window_base, &curve);
+            }
+        }
+
+        for length in 1..=WINDOWS * 3 {
+            let scalar: Vec<bool> = (0..length).map(|_| rng.next_u32() % 2 != 0).collect();
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let by = scalar
+                .iter()
+                .enumerate()
+                .map(|(i, b)| {
+                    Boolean::from(
+                        AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), Some(*b))
+                            .unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let result =
+                fixed_base_multiplication(cs.namespace(|| "multiplication"), &by, &windows, &curve)
+                    .unwrap();
+
+            assert!(cs.is_satisfied());
+
+            let mut expected = Point::identity();
+            let mut power = base;
+            for bit in &scalar {
+                if *bit {
+                    expected = expected.add(&power, &curve);
+                }
+                power = power.add(&power, &curve);
+            }
+            assert_eq!(result.get_u().get_value(), Some(expected.u));
+            assert_eq!(result.get_v().get_value(), Some(expected.v));
+        }
+    }
+
+    #[test]
+    fn test_pedersen_hash_sapling() {
+        use crate::gadgets::pedersen_hash::generator_tables;
+        use ff::{PrimeField, PrimeFieldBits};
+
+        // With Sapling's generators, the Merkle hash at level 0 of two empty
+        // leaves, which are 1, is the root of the empty Sapling note commitment
+        // tree of depth 1, as listed in librustzcash.
+        let curve = EdwardsCurve::<Bls12>::jubjub();
+        let leaf = Fr::one()
+            .to_le_bits()
+            .into_iter()
+            .take(Fr::NUM_BITS as usize)
+            .collect::<Vec<_>>();
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let input = leaf
+            .iter()
+            .chain(leaf.iter())
+            .enumerate()
+            .map(|(i, b)| {
+                Boolean::from(
+                    AllocatedBit::alloc(cs.namespace(|| format!("input {}", i)), Some(*b)).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let (u, _) = pedersen_hash(
+            cs.namespace(|| "pedersen hash"),
+            &[false; 6],
+            &input,
+            &generator_tables(),
+            &curve,
+        )
+        .unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(
+            hex::encode(u.get_value().unwrap().to_repr()),
+            "817de36ab2d57feb077634bca77819c8e0bd298c04f6fed0e6a83cc1356ca155"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "generator segments must not be empty")]
+    fn test_pedersen_hash_empty_segment() {
+        let curve = EdwardsCurve::<Bls12>::jubjub();
+        let mut generators = generators(&curve).montgomery;
+        generators[1].clear();
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let _ = pedersen_hash(
+            cs.namespace(|| "pedersen hash"),
+            &[],
+            &[Boolean::constant(true)],
+            &generators,
+            &curve,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough generators")]
+    fn test_pedersen_hash_too_long() {
+        let curve = EdwardsCurve::<Bls12>::jubjub();
+        let generators = generators(&curve);
+        let input = vec![Boolean::constant(true); SEGMENTS * WINDOWS_PER_SEGMENT * 3 + 1];
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let _ = pedersen_hash(
+            cs.namespace(|| "pedersen hash"),
+            &[],
+            &input,
+            &generators.montgomery,
+            &curve,
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! The Sapling Pedersen hash over Jubjub, with the generators of the Zcash
+//! protocol specification.
+//!
+//! See [`pedersen`] for the generic gadget and the Edwards point gadgets this
+//! is built on.
+//!
+//! [`pedersen`]: super::pedersen
+
+use blake2s_simd::Params as Blake2sParams;
+use blstrs::{Bls12, Scalar};
+use ff::{Field, PrimeField};
+use lazy_static::lazy_static;
+use pairing::Engine;
+
+use super::boolean::Boolean;
+use super::pedersen::{pedersen_hash_edwards, EdwardsCurve, EdwardsPoint};
+use crate::{ConstraintSystem, SynthesisError};
+
+/// Number of generators, which bounds the input to
+/// `GENERATORS * CHUNKS_PER_GENERATOR * 3` bits, personalization included.
+const GENERATORS: u32 = 6;
+
+/// Number of 3-bit chunks of the input each generator is used for.
+const CHUNKS_PER_GENERATOR: usize = 63;
+
+/// First block of the BLAKE2s input of the group hash, a public random string.
+const GH_FIRST_BLOCK: &[u8; 64] =
+    b"096b36a5804bfacef1691e173c366a47ff5ba84a44f26ddd7e8d9f79d5b42df0";
+
+/// BLAKE2s personalization of the group hash deriving the generators.
+const GENERATORS_PERSONALIZATION: &[u8; 8] = b"Zcash_PH";
+
+lazy_static! {
+    /// The Montgomery coordinates of `[1, 2, 3, 4] * 16^j * G_i` for every
+    /// window `j` of every generator `G_i`, as taken by `pedersen_hash`.
+    static ref GENERATOR_TABLES: Vec<Vec<Vec<(Scalar, Scalar)>>> = generator_tables();
+}
+
+/// The domain of a hash, whose bits are prepended to the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Personalization {
+    NoteCommitment,
+    MerkleTree(usize),
+}
+
+impl Personalization {
+    /// Returns the 6 bits prepended to the input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the Merkle tree level is 63 or more, since it would not fit
+    /// in 6 bits or would collide with `NoteCommitment`.
+    pub fn get_bits(&self) -> Vec<bool> {
+        match *self {
+            Personalization::NoteCommitment => vec![true; 6],
+            Personalization::MerkleTree(level) => {
+                assert!(level < 63, "Merkle tree level {} is too large", level);
+                (0..6).map(|i| (level >> i) & 1 == 1).collect()
+            }
+        }
+    }
+}
+
+/// Computes the Sapling Pedersen hash of `personalization || bits`.
+///
+/// # Panics
+///
+/// Panics if `bits` is longer than 1128 bits, the capacity of the generators
+/// once the personalization is prepended.
+pub fn pedersen_hash<E, CS>(
+    cs: CS,
+    personalization: Personalization,
+    bits: &[Boolean],
+) -> Result<EdwardsPoint<E>, SynthesisError>
+where
+    E: Engine<Fr = Scalar>,
+    CS: ConstraintSystem<E>,
+{
+    pedersen_hash_edwards(
+        cs,
+        &personalization.get_bits(),
+        bits,
+        &GENERATOR_TABLES,
+        &EdwardsCurve::jubjub(),
+    )
+}
+
+/// Returns the window tables of the generators, in the format of
+/// `GENERATOR_TABLES`.
+pub(crate) fn generator_tables() -> Vec<Vec<Vec<(Scalar, Scalar)>>> {
+    let curve = EdwardsCurve::<Bls12>::jubjub();
+
+    (0..GENERATORS)
+        .map(|i| {
+            let mut g = find_group_hash(&i.to_le_bytes(), &curve);
+            (0..CHUNKS_PER_GENERATOR)
+                .map(|_| {
+                    let mut multiple = g;
+                    let mut table = vec![g.to_montgomery(&curve)];
+                    for _ in 1..4 {
+                        multiple = multiple.add(&g, &curve);
+                        table.push(multiple.to_montgomery(&curve));
+                    }
+
+                    for _ in 0..4 {
+                        g = g.add(&g, &curve);
+                    }
+
+                    table
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the first point of the prime order subgroup obtained by hashing
+/// `m` followed by a counter byte.
+fn find_group_hash(m: &[u8], curve: &EdwardsCurve<Bls12>) -> Point {
+    let mut tag = m.to_vec();
+    tag.push(0);
+
+    loop {
+        if let Some(p) = group_hash(&tag, curve) {
+            return p;
+        }
+
+        let counter = tag.last_mut().unwrap();
+        *counter = counter.checked_add(1).expect("group hash found no point");
+    }
+}
+
+/// Maps the BLAKE2s hash of `tag` to a point of the prime order subgroup, if
+/// it encodes a curve point whose cofactor multiple is not the identity.
+fn group_hash(tag: &[u8], curve: &EdwardsCurve<Bls12>) -> Option<Point> {
+    let hash = Blake2sParams::new()
+        .hash_length(32)
+        .personal(GENERATORS_PERSONALIZATION)
+        .to_state()
+        .update(GH_FIRST_BLOCK)
+        .update(tag)
+        .finalize();
+
+    let mut p = Point::from_bytes(hash.as_array(), curve)?;
+    // Clears the cofactor of 8.
+    for _ in 0..3 {
+        p = p.add(&p, curve);
+    }
+
+    if p == Point::identity() {
+        None
+    } else {
+        Some(p)
+    }
+}
+
+/// A point of Jubjub in Edwards coordinates, used to derive the generators.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Point {
+    u: Scalar,
+    v: Scalar,
+}
+
+impl Point {
+    fn identity() -> Self {
+        Point {
+            u: Scalar::zero(),
+            v: Scalar::one(),
+        }
+    }
+
+    /// Decodes a point from the little-endian encoding of `v`, whose top bit
+    /// holds the parity of `u`.
+    fn from_bytes(bytes: &[u8; 32], curve: &EdwardsCurve<Bls12>) -> Option<Self> {
+        let mut repr = <Scalar as PrimeField>::Repr::default();
+        repr.as_mut().copy_from_slice(bytes);
+        let sign = repr.as_ref()[31] >> 7 == 1;
+        repr.as_mut()[31] &= 0x7f;
+        let v: Scalar = Option::from(Scalar::from_repr(repr))?;
+
+        // u^2 = (v^2 - 1) / (d v^2 + 1)
+        let v2 = v.square();
+        let den: Scalar = Option::from((*curve.edwards_d() * v2 + Scalar::one()).invert())?;
+        let mut u: Scalar = Option::from(((v2 - Scalar::one()) * den).sqrt())?;
+        if bool::from(u.is_odd()) != sign {
+            // Zero has no negative to carry the sign.
+            if bool::from(u.is_zero()) {
+                return None;
+            }
+            u = -u;
+        }
+
+        Some(Point { u, v })
+    }
+
+    fn add(&self, other: &Self, curve: &EdwardsCurve<Bls12>) -> Self {
+        let uu = self.u * other.u;
+        let vv = self.v * other.v;
+        let c = *curve.edwards_d() * uu * vv;
+
+        Point {
+            u: (self.u * other.v + self.v * other.u) * (Scalar::one() + c).invert().unwrap(),
+            v: (vv + uu) * (Scalar::one() - c).invert().unwrap(),
+        }
+    }
+
+    /// Maps the point, which must not have order 1 or 2, to the Montgomery
+    /// curve.
+    fn to_montgomery(self, curve: &EdwardsCurve<Bls12>) -> (Scalar, Scalar) {
+        let x = (Scalar::one() + self.v) * (Scalar::one() - self.v).invert().unwrap();
+        let y = *curve.montgomery_scale() * x * self.u.invert().unwrap();
+        (x, y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::boolean::AllocatedBit;
+    use crate::gadgets::test::*;
+    use ff::PrimeFieldBits;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    /// Allocates `bits` as inputs of `cs`.
+    fn alloc_bits<CS: ConstraintSystem<Bls12>>(mut cs: CS, bits: &[bool]) -> Vec<Boolean> {
+        bits.iter()
+            .enumerate()
+            .map(|(i, b)| {
+                Boolean::from(
+                    AllocatedBit::alloc(cs.namespace(|| format!("input {}", i)), Some(*b)).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pedersen_hash_sapling_empty_roots() {
+        // The roots of the empty Sapling note commitment trees of depth 1 to 4,
+        // as listed in librustzcash. The root of depth `i + 1` is the Merkle
+        // hash at level `i` of two roots of depth `i`, and the empty leaf is 1.
+        let expected = [
+            "817de36ab2d57feb077634bca77819c8e0bd298c04f6fed0e6a83cc1356ca155",
+            "ffe9fc03f18b176c998806439ff0bb8ad193afdb27b2ccbc88856916dd804e34",
+            "d8283386ef2ef07ebdbb4383c12a739a953a4d6e0d6fb1139a4036d693bfbb6c",
+            "e110de65c907b9dea4ae0bd83a4b0a51bea175646a64c12b4c9f931b2cb31b49",
+        ];
+
+        let mut root = Scalar::one();
+        for (level, expected) in expected.iter().enumerate() {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let child = root
+                .to_le_bits()
+                .into_iter()
+                .take(Scalar::NUM_BITS as usize)
+                .collect::<Vec<_>>();
+            let input = alloc_bits(cs.namespace(|| "input"), &[&child[..], &child[..]].concat());
+
+            let hash = pedersen_hash(
+                cs.namespace(|| "pedersen hash"),
+                Personalization::MerkleTree(level),
+                &input,
+            )
+            .unwrap();
+
+            assert!(cs.is_satisfied());
+
+            root = hash.get_u().get_value().unwrap();
+            assert_eq!(hex::encode(root.to_repr()), *expected, "level {}", level);
+        }
+    }
+
+    #[test]
+    fn test_pedersen_hash_bit_flip() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let hash = |personalization: Personalization, input: &[bool]| {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let input = alloc_bits(cs.namespace(|| "input"), input);
+            let hash =
+                pedersen_hash(cs.namespace(|| "pedersen hash"), personalization, &input).unwrap();
+            assert!(cs.is_satisfied());
+
+            (
+                hash.get_u().get_value().unwrap(),
+                hash.get_v().get_value().unwrap(),
+            )
+        };
+
+        // Long enough to span two generators.
+        let input: Vec<bool> = (0..200).map(|_| rng.next_u32() % 2 != 0).collect();
+        let expected = hash(Personalization::NoteCommitment, &input);
+
+        for i in 0..input.len() {
+            let mut flipped = input.clone();
+            flipped[i] = !flipped[i];
+            assert_ne!(
+                hash(Personalization::NoteCommitment, &flipped),
+                expected,
+                "bit {}",
+                i
+            );
+        }
+
+        assert_ne!(hash(Personalization::MerkleTree(0), &input), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough generators")]
+    fn test_pedersen_hash_too_long() {
+        let input = vec![Boolean::constant(true); 1128 + 1];
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let _ = pedersen_hash(
+            cs.namespace(|| "pedersen hash"),
+            Personalization::NoteCommitment,
+            &input,
+        );
+    }
+}
@@ -0,0 +1,588 @@
+//! Poseidon hash gadget with the `x^5` S-box.
+//!
+//! The permutation runs `full_rounds / 2` full rounds, then `partial_rounds`
+//! partial rounds, in which only the first state element goes through the
+//! S-box, and then `full_rounds / 2` more full rounds. Each round adds the
+//! round constants, applies the S-box and multiplies the state with the MDS
+//! matrix.
+//!
+//! Hashing uses a sponge with a capacity of one element: the first state
+//! element is initialized to `len * 2^64`, where `len` is the number of inputs,
+//! and the inputs are added into the other `width - 1` elements, permuting
+//! after each chunk. The last chunk is padded with zeros, which the length in
+//! the capacity element makes unambiguous. The hash is the second state
+//! element after the last permutation.
+
+use std::cmp;
+use std::ops::AddAssign;
+
+use blstrs::Bls12;
+use ff::{Field, PrimeField};
+use pairing::Engine;
+
+use super::num::AllocatedNum;
+use super::Assignment;
+use crate::{ConstraintSystem, LinearCombination, SynthesisError};
+
+/// Round constants and MDS matrix of a Poseidon permutation.
+#[derive(Clone, Debug)]
+pub struct PoseidonParams<E: Engine> {
+    full_rounds: usize,
+    partial_rounds: usize,
+    round_constants: Vec<E::Fr>,
+    mds: Vec<Vec<E::Fr>>,
+}
+
+impl<E: Engine> PoseidonParams<E> {
+    /// Builds the parameters of a permutation over `mds.len()` elements, with
+    /// `mds.len()` round constants per round, in round order.
+    ///
+    /// Panics unless `mds` is square with at least two rows, `full_rounds` is
+    /// even and the number of round constants matches the number of rounds.
+    pub fn new(
+        full_rounds: usize,
+        partial_rounds: usize,
+        round_constants: Vec<E::Fr>,
+        mds: Vec<Vec<E::Fr>>,
+    ) -> Self {
+        let width = mds.len();
+        assert!(width >= 2, "the state must hold at least two elements");
+        assert!(
+            mds.iter().all(|row| row.len() == width),
+            "the MDS matrix must be square"
+        );
+        assert_eq!(full_rounds % 2, 0, "the number of full rounds must be even");
+        assert_eq!(
+            round_constants.len(),
+            (full_rounds + partial_rounds) * width,
+            "there must be one round constant per state element and round"
+        );
+
+        PoseidonParams {
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Number of elements in the state.
+    pub fn width(&self) -> usize {
+        self.mds.len()
+    }
+
+    /// Number of inputs absorbed per permutation when hashing.
+    pub fn rate(&self) -> usize {
+        self.width() - 1
+    }
+
+    /// Applies the permutation to `state`, which must hold `width` elements.
+    pub fn permute(&self, state: &mut [E::Fr]) {
+        assert_eq!(state.len(), self.width());
+
+        for round in 0..self.num_rounds() {
+            for (x, c) in state.iter_mut().zip(self.round_constants(round)) {
+                x.add_assign(c);
+            }
+
+            if self.is_full_round(round) {
+                state.iter_mut().for_each(|x| *x = pow5(*x));
+            } else {
+                state[0] = pow5(state[0]);
+            }
+
+            let mixed = self
+                .mds
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(state.iter())
+                        .fold(E::Fr::zero(), |acc, (m, x)| acc + *m * x)
+                })
+                .collect::<Vec<_>>();
+            state.copy_from_slice(&mixed);
+        }
+    }
+
+    /// Hashes `inputs` with the sponge, natively.
+    pub fn hash(&self, inputs: &[E::Fr]) -> E::Fr {
+        let mut state = vec![E::Fr::zero(); self.width()];
+        state[0] = self.domain_tag(inputs.len());
+
+        for chunk in chunks(inputs, self.rate()) {
+            for (x, input) in state[1..].iter_mut().zip(chunk) {
+                x.add_assign(input);
+            }
+            self.permute(&mut state);
+        }
+
+        state[1]
+    }
+
+    fn num_rounds(&self) -> usize {
+        self.full_rounds + self.partial_rounds
+    }
+
+    fn is_full_round(&self, round: usize) -> bool {
+        round < self.full_rounds / 2 || round >= self.full_rounds / 2 + self.partial_rounds
+    }
+
+    fn round_constants(&self, round: usize) -> &[E::Fr] {
+        &self.round_constants[round * self.width()..(round + 1) * self.width()]
+    }
+
+    /// The initial value of the capacity element when hashing `len` inputs.
+    fn domain_tag(&self, len: usize) -> E::Fr {
+        E::Fr::from(len as u64) * E::Fr::from(2u64).pow_vartime(&[64u64])
+    }
+
+    /// Derives the parameters with the Grain LFSR, the same way the reference
+    /// implementation does.
+    ///
+    /// The reference also checks the MDS matrix against subspace trail attacks
+    /// and draws a new one if it fails. That is not done here, so only use this
+    /// for parameter sets whose first matrix is known to pass.
+    fn generate(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let mut grain = Grain::new(E::Fr::NUM_BITS as usize, width, full_rounds, partial_rounds);
+
+        let round_constants = (0..(full_rounds + partial_rounds) * width)
+            .map(|_| grain.next_field_element())
+            .collect();
+
+        // A Cauchy matrix, `mds[i][j] = 1 / (x_i + y_j)`, for distinct `x_i`
+        // and `y_j`.
+        let mds = loop {
+            let elements = (0..2 * width)
+                .map(|_| grain.next_field_element_reduced::<E::Fr>())
+                .collect::<Vec<_>>();
+            let distinct = elements
+                .iter()
+                .enumerate()
+                .all(|(i, x)| elements[i + 1..].iter().all(|y| x != y));
+            if !distinct {
+                continue;
+            }
+
+            let (xs, ys) = elements.split_at(width);
+            let mds = xs
+                .iter()
+                .map(|x| {
+                    ys.iter()
+                        .map(|y| Option::from((*x + y).invert()))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .collect::<Option<Vec<_>>>();
+            if let Some(mds) = mds {
+                break mds;
+            }
+        };
+
+        PoseidonParams::new(full_rounds, partial_rounds, round_constants, mds)
+    }
+}
+
+impl PoseidonParams<Bls12> {
+    /// The width 3 parameter set of the reference implementation over the
+    /// BLS12-381 scalar field, `poseidonperm_x5_255_3`, which hashes two
+    /// elements per permutation with 8 full and 57 partial rounds.
+    pub fn bls12_t3() -> Self {
+        PoseidonParams::generate(3, 8, 57)
+    }
+}
+
+/// Splits `inputs` into the chunks of `rate` elements absorbed by each
+/// permutation. There is always at least one, possibly empty, chunk.
+fn chunks<T>(inputs: &[T], rate: usize) -> impl Iterator<Item = &[T]> {
+    let len = inputs.len();
+    let num_chunks = cmp::max(1, (len + rate - 1) / rate);
+
+    (0..num_chunks).map(move |i| &inputs[i * rate..cmp::min((i + 1) * rate, len)])
+}
+
+fn pow5<F: Field>(x: F) -> F {
+    x.square().square() * x
+}
+
+/// The Grain LFSR in the self-shrinking mode the reference implementation uses
+/// to derive the round constants and the MDS matrix.
+struct Grain {
+    state: [bool; 80],
+    head: usize,
+}
+
+impl Grain {
+    fn new(field_size: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let mut bits = Vec::with_capacity(80);
+        let mut push = |value: usize, len: usize| {
+            bits.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        // A prime field, and the `x^alpha` S-box.
+        push(1, 2);
+        push(0, 4);
+        push(field_size, 12);
+        push(width, 12);
+        push(full_rounds, 10);
+        push(partial_rounds, 10);
+        push((1 << 30) - 1, 30);
+
+        let mut state = [false; 80];
+        state.copy_from_slice(&bits);
+        let mut grain = Grain { state, head: 0 };
+        for _ in 0..160 {
+            grain.next_bit();
+        }
+
+        grain
+    }
+
+    fn next_bit(&mut self) -> bool {
+        let s = |i: usize| self.state[(self.head + i) % 80];
+        let bit = s(62) ^ s(51) ^ s(38) ^ s(23) ^ s(13) ^ s(0);
+        self.state[self.head] = bit;
+        self.head = (self.head + 1) % 80;
+
+        bit
+    }
+
+    /// Returns the next `num_bits` output bits, most significant first. Each of
+    /// them is the second bit of the first pair of bits whose first bit is set.
+    fn next_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        (0..num_bits)
+            .map(|_| loop {
+                let first = self.next_bit();
+                let second = self.next_bit();
+                if first {
+                    break second;
+                }
+            })
+            .collect()
+    }
+
+    /// Samples a field element, rejecting integers not below the modulus.
+    fn next_field_element<F: PrimeField>(&mut self) -> F {
+        loop {
+            let mut repr = F::Repr::default();
+            let bytes = repr.as_mut();
+            for (i, bit) in self
+                .next_bits(F::NUM_BITS as usize)
+                .iter()
+                .rev()
+                .enumerate()
+            {
+                if *bit {
+                    bytes[i / 8] |= 1 << (i % 8);
+                }
+            }
+
+            if let Some(f) = Option::from(F::from_repr(repr)) {
+                return f;
+            }
+        }
+    }
+
+    /// Samples a field element, reducing the integer modulo the field order.
+    fn next_field_element_reduced<F: PrimeField>(&mut self) -> F {
+        self.next_bits(F::NUM_BITS as usize)
+            .into_iter()
+            .fold(F::zero(), |acc, bit| {
+                let acc = acc.double();
+                if bit {
+                    acc + F::one()
+                } else {
+                    acc
+                }
+            })
+    }
+}
+
+/// A state element: a linear combination of variables along with its value.
+struct Element<E: Engine> {
+    value: Option<E::Fr>,
+    lc: LinearCombination<E>,
+}
+
+impl<E: Engine> Element<E> {
+    fn constant<CS: ConstraintSystem<E>>(value: E::Fr) -> Self {
+        Element {
+            value: Some(value),
+            lc: LinearCombination::zero() + (value, CS::one()),
+        }
+    }
+
+    fn add_constant<CS: ConstraintSystem<E>>(&mut self, c: E::Fr) {
+        self.lc = self.lc.clone() + (c, CS::one());
+        self.value = self.value.map(|value| value + c);
+    }
+
+    fn add_num(&mut self, num: &AllocatedNum<E>) {
+        self.lc = self.lc.clone() + num.get_variable();
+        self.value = self
+            .value
+            .and_then(|value| num.get_value().map(|num| value + num));
+    }
+
+    /// Allocates a number equal to this element, at a cost of one constraint.
+    fn allocate<CS>(self, mut cs: CS) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let num = AllocatedNum::alloc(cs.namespace(|| "value"), || Ok(*self.value.get()?))?;
+
+        cs.enforce(
+            || "value constraint",
+            |_| self.lc,
+            |lc| lc + CS::one(),
+            |lc| lc + num.get_variable(),
+        );
+
+        Ok(num)
+    }
+}
+
+impl<'a, E: Engine> From<&'a AllocatedNum<E>> for Element<E> {
+    fn from(num: &'a AllocatedNum<E>) -> Self {
+        Element {
+            value: num.get_value(),
+            lc: LinearCombination::zero() + num.get_variable(),
+        }
+    }
+}
+
+/// Returns `x^5`, at a cost of three constraints.
+fn sbox<E, CS>(mut cs: CS, x: &Element<E>) -> Result<Element<E>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    let x2 = AllocatedNum::alloc(cs.namespace(|| "x^2"), || Ok(x.value.get()?.square()))?;
+    cs.enforce(
+        || "x^2 constraint",
+        |_| x.lc.clone(),
+        |_| x.lc.clone(),
+        |lc| lc + x2.get_variable(),
+    );
+
+    let x4 = x2.square(cs.namespace(|| "x^4"))?;
+
+    let x5 = AllocatedNum::alloc(cs.namespace(|| "x^5"), || {
+        Ok(*x4.get_value().get()? * x.value.get()?)
+    })?;
+    cs.enforce(
+        || "x^5 constraint",
+        |lc| lc + x4.get_variable(),
+        |_| x.lc.clone(),
+        |lc| lc + x5.get_variable(),
+    );
+
+    Ok(Element::from(&x5))
+}
+
+/// Multiplies `state` with the MDS matrix, which costs no constraints.
+fn mix<E: Engine>(params: &PoseidonParams<E>, state: &[Element<E>]) -> Vec<Element<E>> {
+    params
+        .mds
+        .iter()
+        .map(|row| {
+            let mut lc = LinearCombination::zero();
+            let mut value = Some(E::Fr::zero());
+            for (m, x) in row.iter().zip(state) {
+                lc = lc + (*m, &x.lc);
+                value = value.and_then(|value| x.value.map(|x| value + *m * x));
+            }
+
+            Element { value, lc }
+        })
+        .collect()
+}
+
+fn permute<E, CS>(
+    mut cs: CS,
+    mut state: Vec<Element<E>>,
+    params: &PoseidonParams<E>,
+) -> Result<Vec<Element<E>>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    for round in 0..params.num_rounds() {
+        let mut cs = cs.namespace(|| format!("round {}", round));
+
+        for (x, c) in state.iter_mut().zip(params.round_constants(round)) {
+            x.add_constant::<CS>(*c);
+        }
+
+        if params.is_full_round(round) {
+            state = state
+                .iter()
+                .enumerate()
+                .map(|(i, x)| sbox(cs.namespace(|| format!("s-box {}", i)), x))
+                .collect::<Result<_, _>>()?;
+        } else {
+            state[0] = sbox(cs.namespace(|| "s-box 0"), &state[0])?;
+        }
+
+        state = mix(params, &state);
+    }
+
+    Ok(state)
+}
+
+/// Applies the Poseidon permutation to `state`, which must hold
+/// `params.width()` numbers.
+///
+/// Each S-box costs three constraints, and allocating the outputs costs one
+/// constraint per state element.
+pub fn poseidon_permutation<E, CS>(
+    mut cs: CS,
+    state: &[AllocatedNum<E>],
+    params: &PoseidonParams<E>,
+) -> Result<Vec<AllocatedNum<E>>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    if state.len() != params.width() {
+        return Err(SynthesisError::IncompatibleLengthVector(format!(
+            "expected a state of {} numbers, got {}",
+            params.width(),
+            state.len()
+        )));
+    }
+
+    let state = permute(
+        cs.namespace(|| "permutation"),
+        state.iter().map(Element::from).collect(),
+        params,
+    )?;
+
+    state
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| x.allocate(cs.namespace(|| format!("output {}", i))))
+        .collect()
+}
+
+/// Hashes `inputs` with the Poseidon sponge, see the module documentation.
+pub fn poseidon<E, CS>(
+    mut cs: CS,
+    inputs: &[AllocatedNum<E>],
+    params: &PoseidonParams<E>,
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    let mut state = (0..params.width())
+        .map(|_| Element::constant::<CS>(E::Fr::zero()))
+        .collect::<Vec<_>>();
+    state[0] = Element::constant::<CS>(params.domain_tag(inputs.len()));
+
+    for (i, chunk) in chunks(inputs, params.rate()).enumerate() {
+        for (x, input) in state[1..].iter_mut().zip(chunk) {
+            x.add_num(input);
+        }
+        state = permute(cs.namespace(|| format!("permutation {}", i)), state, params)?;
+    }
+
+    state.swap_remove(1).allocate(cs.namespace(|| "hash"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::test::*;
+    use blstrs::Scalar as Fr;
+
+    /// Parses a big-endian hex encoded field element.
+    fn fr(s: &str) -> Fr {
+        let mut bytes = hex::decode(s).unwrap();
+        bytes.reverse();
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        repr.as_mut().copy_from_slice(&bytes);
+        Fr::from_repr(repr).unwrap()
+    }
+
+    #[test]
+    fn test_poseidon_permutation() {
+        let params = PoseidonParams::<Bls12>::bls12_t3();
+
+        // Test vector of the reference implementation's `poseidonperm_x5_255_3`.
+        let input = [Fr::zero(), Fr::one(), Fr::from(2u64)];
+        let expected = [
+            fr("28ce19420fc246a05553ad1e8c98f5c9d67166be2c18e9e4cb4b4e317dd2a78a"),
+            fr("51f3e312c95343a896cfd8945ea82ba956c1118ce9b9859b6ea56637b4b1ddc4"),
+            fr("3b2b69139b235626a0bfb56c9527ae66a7bf486ad8c11c14d1da0c69bbe0f79a"),
+        ];
+
+        let mut state = input;
+        params.permute(&mut state);
+        assert_eq!(state, expected);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let state = input
+            .iter()
+            .enumerate()
+            .map(|(i, x)| AllocatedNum::alloc(cs.namespace(|| format!("x {}", i)), || Ok(*x)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let output = poseidon_permutation(cs.namespace(|| "poseidon"), &state, &params).unwrap();
+
+        assert!(cs.is_satisfied());
+        let output = output.iter().map(|x| x.get_value()).collect::<Vec<_>>();
+        assert_eq!(
+            output,
+            expected.iter().cloned().map(Some).collect::<Vec<_>>()
+        );
+        // 8 full rounds of 3 S-boxes, 57 partial rounds of one, and 3 outputs.
+        assert_eq!(cs.num_constraints(), (8 * 3 + 57) * 3 + 3);
+
+        cs.set(
+            "poseidon/permutation/round 10/s-box 0/x^5/num",
+            Fr::from(5u64),
+        );
+        assert_eq!(
+            cs.which_is_unsatisfied(),
+            Some("poseidon/permutation/round 10/s-box 0/x^5 constraint")
+        );
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        assert!(matches!(
+            poseidon_permutation(&mut cs, &state[..2], &params),
+            Err(SynthesisError::IncompatibleLengthVector(_))
+        ));
+    }
+
+    #[test]
+    fn test_poseidon_hash() {
+        let params = PoseidonParams::<Bls12>::bls12_t3();
+
+        for len in 0..6 {
+            let inputs = (0..len).map(|i| Fr::from(i as u64 + 1)).collect::<Vec<_>>();
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let nums = inputs
+                .iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    AllocatedNum::alloc(cs.namespace(|| format!("input {}", i)), || Ok(*x))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let hash = poseidon(cs.namespace(|| "poseidon"), &nums, &params).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(hash.get_value(), Some(params.hash(&inputs)));
+            let permutations = cmp::max(1, (len + 1) / 2);
+            assert_eq!(cs.num_constraints(), permutations * (8 * 3 + 57) * 3 + 1);
+        }
+
+        // The length is part of the hash, so zero padding is unambiguous.
+        let one = Fr::one();
+        assert_ne!(params.hash(&[one]), params.hash(&[one, Fr::zero()]));
+        assert_ne!(params.hash(&[]), params.hash(&[Fr::zero()]));
+        assert_ne!(
+            params.hash(&[one, Fr::from(2u64)]),
+            params.hash(&[Fr::from(2u64), one])
+        );
+    }
+}
@@ -356,6 +356,11 @@ mod test {
 
         assert!(cs.is_satisfied());
         assert_eq!(cs.num_constraints() - 512, 44874);
+        // Pins the constraint system down, not just its size.
+        assert_eq!(
+            cs.hash(),
+            "39b920088193af138e8d3494179b1d9a20442bb23e28e65028b068c6a9e48771"
+        );
     }
 
     #[test]
@@ -3,15 +3,18 @@
 use ff::{Field, PrimeField};
 use pairing::Engine;
 
+use crate::util_cs::test_cs::{
+    constraint_satisfied, unsatisfied_paths, unsatisfied_paths_parallel,
+};
 use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Write;
-use std::ops::{AddAssign, MulAssign};
+use std::ops::{AddAssign, MulAssign, SubAssign};
 
 use byteorder::{BigEndian, ByteOrder};
-use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use blake2s_simd::{Params as Blake2sParams, State as Blake2sState};
 
@@ -35,41 +38,14 @@ pub struct TestConstraintSystem<E: Engine> {
     )>,
     inputs: Vec<(E::Fr, String)>,
     aux: Vec<(E::Fr, String)>,
+    // Computed on the first call to `pretty_print`.
+    powers_of_two: RefCell<Option<Vec<E::Fr>>>,
 }
 
-#[derive(Clone, Copy)]
-struct OrderedVariable(Variable);
-
-impl Eq for OrderedVariable {}
-impl PartialEq for OrderedVariable {
-    fn eq(&self, other: &OrderedVariable) -> bool {
-        match (self.0.get_unchecked(), other.0.get_unchecked()) {
-            (Index::Input(ref a), Index::Input(ref b)) => a == b,
-            (Index::Aux(ref a), Index::Aux(ref b)) => a == b,
-            _ => false,
-        }
-    }
-}
-impl PartialOrd for OrderedVariable {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-impl Ord for OrderedVariable {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self.0.get_unchecked(), other.0.get_unchecked()) {
-            (Index::Input(ref a), Index::Input(ref b)) => a.cmp(b),
-            (Index::Aux(ref a), Index::Aux(ref b)) => a.cmp(b),
-            (Index::Input(_), Index::Aux(_)) => Ordering::Less,
-            (Index::Aux(_), Index::Input(_)) => Ordering::Greater,
-        }
-    }
-}
-
-fn proc_lc<E: Engine>(terms: &LinearCombination<E>) -> BTreeMap<OrderedVariable, E::Fr> {
+fn proc_lc<E: Engine>(terms: &LinearCombination<E>) -> BTreeMap<Variable, E::Fr> {
     let mut map = BTreeMap::new();
     for (var, &coeff) in terms.iter() {
-        map.entry(OrderedVariable(var))
+        map.entry(var)
             .or_insert_with(E::Fr::zero)
             .add_assign(&coeff);
     }
@@ -97,7 +73,7 @@ fn hash_lc<E: Engine>(terms: &LinearCombination<E>, h: &mut Blake2sState) {
     h.update(&buf[0..8]);
 
     for (var, coeff) in map {
-        match var.0.get_unchecked() {
+        match var.get_unchecked() {
             Index::Input(i) => {
                 buf[0] = b'I';
                 BigEndian::write_u64(&mut buf[1..9], i as u64);
@@ -141,11 +117,34 @@ impl<E: Engine> TestConstraintSystem<E> {
     pub fn pretty_print(&self) -> String {
         let mut s = String::new();
 
+        for &(ref a, ref b, ref c, ref name) in &self.constraints {
+            writeln!(&mut s).unwrap();
+
+            write!(&mut s, "{}: ", name).unwrap();
+            self.write_constraint(&mut s, a, b, c);
+        }
+
+        writeln!(&mut s).unwrap();
+
+        s
+    }
+
+    /// Writes `a * b = c`, naming the variables by their paths.
+    fn write_constraint(
+        &self,
+        s: &mut String,
+        a: &LinearCombination<E>,
+        b: &LinearCombination<E>,
+        c: &LinearCombination<E>,
+    ) {
         let negone = -E::Fr::one();
 
-        let powers_of_two = (0..E::Fr::NUM_BITS)
-            .map(|i| E::Fr::from(2u64).pow_vartime(&[u64::from(i)]))
-            .collect::<Vec<_>>();
+        let mut powers_of_two = self.powers_of_two.borrow_mut();
+        let powers_of_two = powers_of_two.get_or_insert_with(|| {
+            (0..E::Fr::NUM_BITS)
+                .map(|i| E::Fr::from(2u64).pow_vartime(&[u64::from(i)]))
+                .collect()
+        });
 
         let pp = |s: &mut String, lc: &LinearCombination<E>| {
             write!(s, "(").unwrap();
@@ -169,7 +168,7 @@ impl<E: Engine> TestConstraintSystem<E> {
                     write!(s, "{:?} . ", coeff).unwrap();
                 }
 
-                match var.0.get_unchecked() {
+                match var.get_unchecked() {
                     Index::Input(i) => {
                         write!(s, "`{}`", &self.inputs[i].1).unwrap();
                     }
@@ -185,18 +184,127 @@ impl<E: Engine> TestConstraintSystem<E> {
             write!(s, ")").unwrap();
         };
 
-        for &(ref a, ref b, ref c, ref name) in &self.constraints {
+        pp(s, a);
+        write!(s, " * ").unwrap();
+        pp(s, b);
+        write!(s, " = ").unwrap();
+        pp(s, c);
+    }
+
+    /// Returns the values of A, B and C of the constraint at `path`, or `None`
+    /// if there is no constraint there.
+    pub fn eval_constraint(&self, path: &str) -> Option<(E::Fr, E::Fr, E::Fr)> {
+        match self.named_objects.get(path) {
+            Some(&NamedObject::Constraint(index)) => Some(self.eval_constraint_at(index)),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the constraint at `path` is satisfied, without
+    /// evaluating any other constraint, or `None` if there is no constraint
+    /// there.
+    pub fn is_constraint_satisfied(&self, path: &str) -> Option<bool> {
+        self.eval_constraint(path).map(|(a, b, c)| a * b == c)
+    }
+
+    fn eval_constraint_at(&self, index: usize) -> (E::Fr, E::Fr, E::Fr) {
+        let (ref a, ref b, ref c, _) = self.constraints[index];
+
+        (
+            eval_lc::<E>(a, &self.inputs, &self.aux),
+            eval_lc::<E>(b, &self.inputs, &self.aux),
+            eval_lc::<E>(c, &self.inputs, &self.aux),
+        )
+    }
+
+    /// Describes every unsatisfied constraint: its path, its linear
+    /// combinations as in `pretty_print`, the values of A, B and C, and the
+    /// delta `A * B - C`.
+    pub fn explain_unsatisfied(&self) -> String {
+        let mut s = String::new();
+
+        for (index, &(ref a, ref b, ref c, ref path)) in self.constraints.iter().enumerate() {
+            let (a_value, b_value, c_value) = self.eval_constraint_at(index);
+            let mut delta = a_value;
+            delta.mul_assign(&b_value);
+            delta.sub_assign(&c_value);
+            if bool::from(delta.is_zero()) {
+                continue;
+            }
+
+            write!(&mut s, "{}: ", path).unwrap();
+            self.write_constraint(&mut s, a, b, c);
             writeln!(&mut s).unwrap();
+            writeln!(&mut s, "    A = {:?}", a_value).unwrap();
+            writeln!(&mut s, "    B = {:?}", b_value).unwrap();
+            writeln!(&mut s, "    C = {:?}", c_value).unwrap();
+            writeln!(&mut s, "    A * B - C = {:?}", delta).unwrap();
+        }
 
-            write!(&mut s, "{}: ", name).unwrap();
-            pp(&mut s, a);
-            write!(&mut s, " * ").unwrap();
-            pp(&mut s, b);
-            write!(&mut s, " = ").unwrap();
-            pp(&mut s, c);
+        s
+    }
+
+    /// Renders the first `max_constraints` constraints and the variables they
+    /// use as a bipartite Graphviz graph. Each variable is a node labelled with
+    /// its path, inputs as boxes and auxiliary variables as ellipses. Each
+    /// constraint is a diamond labelled with its path, linked by one edge to
+    /// every variable it uses, so the graph grows linearly with the size of the
+    /// constraints.
+    pub fn to_dot(&self, max_constraints: usize) -> String {
+        fn escape(s: &str) -> String {
+            s.replace('\\', "\\\\").replace('"', "\\\"")
+        }
+        fn node(var: Variable) -> String {
+            match var.get_unchecked() {
+                Index::Input(i) => format!("i{}", i),
+                Index::Aux(i) => format!("a{}", i),
+            }
         }
 
-        writeln!(&mut s).unwrap();
+        let mut nodes = BTreeSet::new();
+        let mut constraints = String::new();
+        for (i, &(ref a, ref b, ref c, ref path)) in
+            self.constraints.iter().take(max_constraints).enumerate()
+        {
+            let mut vars = a
+                .iter()
+                .chain(b.iter())
+                .chain(c.iter())
+                .map(|(var, _)| var)
+                .collect::<Vec<_>>();
+            vars.sort();
+            vars.dedup();
+
+            writeln!(
+                &mut constraints,
+                "  c{} [label=\"{}\", shape=diamond];",
+                i,
+                escape(path)
+            )
+            .unwrap();
+            for var in vars {
+                nodes.insert(var);
+                writeln!(&mut constraints, "  c{} -- {};", i, node(var)).unwrap();
+            }
+        }
+
+        let mut s = String::from("graph circuit {\n");
+        for var in nodes {
+            let (name, shape) = match var.get_unchecked() {
+                Index::Input(i) => (&self.inputs[i].1, "box"),
+                Index::Aux(i) => (&self.aux[i].1, "ellipse"),
+            };
+            writeln!(
+                &mut s,
+                "  {} [label=\"{}\", shape={}];",
+                node(var),
+                escape(name),
+                shape
+            )
+            .unwrap();
+        }
+        s += &constraints;
+        s += "}\n";
 
         s
     }
@@ -226,20 +334,89 @@ impl<E: Engine> TestConstraintSystem<E> {
         s
     }
 
-    pub fn which_is_unsatisfied(&self) -> Option<&str> {
-        for &(ref a, ref b, ref c, ref path) in &self.constraints {
-            let mut a = eval_lc::<E>(a, &self.inputs, &self.aux);
-            let b = eval_lc::<E>(b, &self.inputs, &self.aux);
-            let c = eval_lc::<E>(c, &self.inputs, &self.aux);
+    /// Returns whether both systems have the same number of inputs and
+    /// auxiliary variables, and the same constraints in the same order. Names
+    /// and assignments are ignored, and linear combinations are compared after
+    /// merging repeated terms and dropping zero terms. See `diff`.
+    pub fn structurally_eq(&self, other: &Self) -> bool {
+        self.diff(other).is_empty()
+    }
 
-            a.mul_assign(&b);
+    /// Describes the differences `structurally_eq` looks for: mismatched
+    /// numbers of variables or constraints, and every constraint which
+    /// differs, with its path in both systems and the normalized linear
+    /// combinations which differ.
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        let mut diff = vec![];
+
+        if self.inputs.len() != other.inputs.len() {
+            diff.push(format!(
+                "{} inputs != {} inputs",
+                self.inputs.len(),
+                other.inputs.len()
+            ));
+        }
+        if self.aux.len() != other.aux.len() {
+            diff.push(format!(
+                "{} aux variables != {} aux variables",
+                self.aux.len(),
+                other.aux.len()
+            ));
+        }
+        if self.constraints.len() != other.constraints.len() {
+            diff.push(format!(
+                "{} constraints != {} constraints",
+                self.constraints.len(),
+                other.constraints.len()
+            ));
+        }
 
-            if a != c {
-                return Some(&*path);
+        for (i, (ours, theirs)) in self.constraints.iter().zip(&other.constraints).enumerate() {
+            let lcs = [
+                ("A", &ours.0, &theirs.0),
+                ("B", &ours.1, &theirs.1),
+                ("C", &ours.2, &theirs.2),
+            ];
+            for (name, ours_lc, theirs_lc) in lcs.iter() {
+                let ours_lc = proc_lc::<E>(ours_lc);
+                let theirs_lc = proc_lc::<E>(theirs_lc);
+                if ours_lc != theirs_lc {
+                    diff.push(format!(
+                        "constraint {} (`{}` vs `{}`): {} is {:?} vs {:?}",
+                        i, ours.3, theirs.3, name, ours_lc, theirs_lc
+                    ));
+                }
             }
         }
 
-        None
+        diff
+    }
+
+    /// Returns the path of the first unsatisfied constraint, if any.
+    pub fn which_is_unsatisfied(&self) -> Option<&str> {
+        self.unsatisfied().next()
+    }
+
+    /// Returns the paths of all unsatisfied constraints, in the order they were
+    /// enforced.
+    pub fn which_are_unsatisfied(&self) -> Vec<String> {
+        unsatisfied_paths(&self.constraints, &self.inputs, &self.aux)
+    }
+
+    /// Like `which_are_unsatisfied`, but evaluates the constraints in parallel.
+    pub fn which_are_unsatisfied_parallel(&self) -> Vec<String> {
+        unsatisfied_paths_parallel(&self.constraints, &self.inputs, &self.aux)
+    }
+
+    pub fn unsatisfied_count(&self) -> usize {
+        self.unsatisfied().count()
+    }
+
+    fn unsatisfied(&self) -> impl Iterator<Item = &str> + '_ {
+        self.constraints
+            .iter()
+            .filter(move |constraint| !constraint_satisfied(constraint, &self.inputs, &self.aux))
+            .map(|(_, _, _, path)| &**path)
     }
 
     pub fn is_satisfied(&self) -> bool {
@@ -250,6 +427,40 @@ impl<E: Engine> TestConstraintSystem<E> {
         self.constraints.len()
     }
 
+    /// Counts the constraints by the top-level namespace they were enforced
+    /// in. Constraints enforced outside of any namespace are counted under the
+    /// empty string.
+    pub fn namespace_stats(&self) -> BTreeMap<String, usize> {
+        let mut stats = BTreeMap::new();
+        for (_, _, _, path) in &self.constraints {
+            let namespace = match path.find('/') {
+                Some(end) => &path[..end],
+                None => "",
+            };
+            *stats.entry(namespace.to_string()).or_insert(0) += 1;
+        }
+
+        stats
+    }
+
+    /// Returns how many of the A, B and C linear combinations of all
+    /// constraints have each number of terms: entry `i` is the number of
+    /// linear combinations with `i` terms.
+    pub fn density_histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![];
+        for (a, b, c, _) in &self.constraints {
+            for lc in &[a, b, c] {
+                let terms = lc.iter().count();
+                if histogram.len() <= terms {
+                    histogram.resize(terms + 1, 0);
+                }
+                histogram[terms] += 1;
+            }
+        }
+
+        histogram
+    }
+
     pub fn set(&mut self, path: &str, to: E::Fr) {
         match self.named_objects.get(path) {
             Some(&NamedObject::Var(ref v)) => match v.get_unchecked() {
@@ -302,6 +513,30 @@ impl<E: Engine> TestConstraintSystem<E> {
         }
     }
 
+    /// Panics if the constraint at `path` uses a variable which was not
+    /// allocated by this constraint system, e.g. one kept from a different
+    /// system. Using variables allocated in other namespaces, including popped
+    /// ones, is fine.
+    #[cfg(debug_assertions)]
+    fn check_allocated(&self, path: &str, lcs: &[&LinearCombination<E>]) {
+        for (var, _) in lcs.iter().flat_map(|lc| lc.iter()) {
+            let allocated = match var.get_unchecked() {
+                Index::Input(i) => i < self.inputs.len(),
+                Index::Aux(i) => i < self.aux.len(),
+            };
+            assert!(
+                allocated,
+                "constraint `{}` uses {:?}, which was not allocated by this constraint system \
+                 (current namespace `{}`, {} inputs and {} aux variables allocated)",
+                path,
+                var,
+                self.current_namespace.join("/"),
+                self.inputs.len(),
+                self.aux.len()
+            );
+        }
+    }
+
     fn set_named_obj(&mut self, path: String, to: NamedObject) {
         if self.named_objects.contains_key(&path) {
             panic!("tried to create object at existing path: {}", path);
@@ -347,6 +582,7 @@ impl<E: Engine> ConstraintSystem<E> for TestConstraintSystem<E> {
             constraints: vec![],
             inputs: vec![(E::Fr::one(), "ONE".into())],
             aux: vec![],
+            powers_of_two: RefCell::new(None),
         }
     }
 
@@ -396,6 +632,9 @@ impl<E: Engine> ConstraintSystem<E> for TestConstraintSystem<E> {
         let b = b(LinearCombination::zero());
         let c = c(LinearCombination::zero());
 
+        #[cfg(debug_assertions)]
+        self.check_allocated(&path, &[&a, &b, &c]);
+
         self.constraints.push((a, b, c, path));
     }
 
@@ -461,3 +700,322 @@ fn test_cs() {
 
     assert!(cs.get("test1/test2/hehe") == Fr::one());
 }
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(
+    expected = "constraint `outer/inner/eq` uses Aux(1), which was not allocated by this \
+                constraint system (current namespace `outer/inner`"
+)]
+fn test_enforce_foreign_variable() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    // A variable kept around after its constraint system is gone.
+    let foreign = {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        cs.alloc(|| "a", || Ok(Fr::one())).unwrap();
+        let mut ns = cs.namespace(|| "ns");
+        ns.alloc(|| "b", || Ok(Fr::one())).unwrap()
+    };
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let a = cs.alloc(|| "a", || Ok(Fr::one())).unwrap();
+    let mut outer = cs.namespace(|| "outer");
+    let mut inner = outer.namespace(|| "inner");
+    inner.enforce(|| "eq", |lc| lc + a, |lc| lc + foreign, |lc| lc + a);
+}
+
+#[test]
+fn test_to_dot() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    // The constraints of `test_cs`.
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let a = cs
+        .namespace(|| "a")
+        .alloc(|| "var", || Ok(Fr::from(10u64)))
+        .unwrap();
+    let b = cs
+        .namespace(|| "b")
+        .alloc(|| "var", || Ok(Fr::from(4u64)))
+        .unwrap();
+    let c = cs.alloc(|| "product", || Ok(Fr::from(40u64))).unwrap();
+    cs.enforce(|| "mult", |lc| lc + a, |lc| lc + b, |lc| lc + c);
+    let one = TestConstraintSystem::<Bls12>::one();
+    cs.enforce(|| "eq", |lc| lc + a, |lc| lc + one, |lc| lc + b);
+
+    let dot = cs.to_dot(usize::MAX);
+    let lines = dot.lines().collect::<Vec<_>>();
+    assert_eq!(lines.first(), Some(&"graph circuit {"));
+    assert_eq!(lines.last(), Some(&"}"));
+    for line in &lines[1..lines.len() - 1] {
+        assert!(line.starts_with("  ") && line.ends_with(';'), "{}", line);
+        assert_eq!(line.matches('"').count() % 2, 0, "{}", line);
+    }
+    assert_eq!(
+        &lines[1..lines.len() - 1],
+        &[
+            "  i0 [label=\"ONE\", shape=box];",
+            "  a0 [label=\"a/var\", shape=ellipse];",
+            "  a1 [label=\"b/var\", shape=ellipse];",
+            "  a2 [label=\"product\", shape=ellipse];",
+            "  c0 [label=\"mult\", shape=diamond];",
+            "  c0 -- a0;",
+            "  c0 -- a1;",
+            "  c0 -- a2;",
+            "  c1 [label=\"eq\", shape=diamond];",
+            "  c1 -- i0;",
+            "  c1 -- a0;",
+            "  c1 -- a1;",
+        ][..]
+    );
+
+    // Only the first constraint and its variables are rendered.
+    let dot = cs.to_dot(1);
+    assert!(!dot.contains("i0") && !dot.contains("c1"));
+    assert_eq!(dot.matches(" -- ").count(), 3);
+}
+
+#[test]
+fn test_which_are_unsatisfied() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let a = cs.alloc(|| "a", || Ok(Fr::from(3u64))).unwrap();
+    let b = cs.alloc(|| "b", || Ok(Fr::from(5u64))).unwrap();
+    let one = TestConstraintSystem::<Bls12>::one();
+
+    cs.enforce(
+        || "a is 3",
+        |lc| lc + a,
+        |lc| lc + one,
+        |lc| lc + (Fr::from(3u64), one),
+    );
+    assert!(cs.which_are_unsatisfied().is_empty());
+    assert!(cs.which_are_unsatisfied_parallel().is_empty());
+    assert_eq!(cs.unsatisfied_count(), 0);
+
+    cs.namespace(|| "broken")
+        .enforce(|| "a is b", |lc| lc + a, |lc| lc + one, |lc| lc + b);
+    cs.enforce(
+        || "a * b",
+        |lc| lc + a,
+        |lc| lc + b,
+        |lc| lc + (Fr::from(16u64), one),
+    );
+    cs.enforce(
+        || "b * b",
+        |lc| lc + b,
+        |lc| lc + b,
+        |lc| lc + (Fr::from(25u64), one),
+    );
+
+    assert_eq!(cs.which_are_unsatisfied(), vec!["broken/a is b", "a * b"]);
+    assert_eq!(
+        cs.which_are_unsatisfied_parallel(),
+        cs.which_are_unsatisfied()
+    );
+    assert_eq!(cs.unsatisfied_count(), 2);
+    assert_eq!(cs.which_is_unsatisfied(), Some("broken/a is b"));
+}
+
+#[test]
+fn test_which_are_unsatisfied_parallel() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    // x_{i+1} = x_i * x_i
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let mut value = Fr::from(3u64);
+    let mut prev = cs.alloc(|| "x 0", || Ok(value)).unwrap();
+    for i in 1..10_000 {
+        value = value.square();
+        let cur = cs.alloc(|| format!("x {}", i), || Ok(value)).unwrap();
+        cs.enforce(
+            || format!("square {}", i),
+            |lc| lc + prev,
+            |lc| lc + prev,
+            |lc| lc + cur,
+        );
+        prev = cur;
+    }
+    assert!(cs.which_are_unsatisfied_parallel().is_empty());
+
+    for &i in &[9_999, 3, 5_000, 1] {
+        cs.set(&format!("x {}", i), Fr::from(7u64));
+    }
+
+    let expected = [1, 2, 3, 4, 5_000, 5_001, 9_999]
+        .iter()
+        .map(|i| format!("square {}", i))
+        .collect::<Vec<_>>();
+    assert_eq!(cs.which_are_unsatisfied(), expected);
+    assert_eq!(cs.which_are_unsatisfied_parallel(), expected);
+}
+
+#[test]
+fn test_namespace_stats() {
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::num::AllocatedNum;
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let a = AllocatedBit::alloc(cs.namespace(|| "a"), Some(true)).unwrap();
+    let b = AllocatedBit::alloc(cs.namespace(|| "b"), Some(false)).unwrap();
+    {
+        let mut cs = cs.namespace(|| "xor");
+        let x = AllocatedBit::xor(cs.namespace(|| "first"), &a, &b).unwrap();
+        AllocatedBit::xor(cs.namespace(|| "second"), &x, &a).unwrap();
+    }
+    {
+        let mut cs = cs.namespace(|| "square");
+        let x = AllocatedNum::alloc(cs.namespace(|| "x"), || Ok(Fr::from(3u64))).unwrap();
+        x.square(cs.namespace(|| "x^2")).unwrap();
+    }
+    Boolean::enforce_equal(&mut cs, &Boolean::from(a), &Boolean::constant(true)).unwrap();
+
+    let stats = cs.namespace_stats();
+    assert_eq!(stats.len(), 5);
+    assert_eq!(stats["a"], 1);
+    assert_eq!(stats["b"], 1);
+    assert_eq!(stats["xor"], 2);
+    assert_eq!(stats["square"], 1);
+    assert_eq!(stats[""], 1);
+    assert_eq!(stats.values().sum::<usize>(), cs.num_constraints());
+
+    // Booleanity, twice: (1 - a) * (a) = 0 has 2, 1 and 0 terms.
+    // XOR, twice: (2a) * (b) = (a + b - c) has 1, 1 and 3 terms.
+    // Squaring: (x) * (x) = (x^2) has 1, 1 and 1 terms.
+    // Equality to one: 0 * 0 = (1 - a) has 0, 0 and 2 terms.
+    assert_eq!(cs.density_histogram(), vec![4, 9, 3, 2]);
+}
+
+#[test]
+fn test_structurally_eq() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let synthesize = |name: &str, coeffs: (u64, u64)| {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let a = cs.alloc(|| name, || Ok(Fr::from(3u64))).unwrap();
+        let b = cs.alloc(|| "b", || Ok(Fr::from(12u64))).unwrap();
+        cs.enforce(
+            || format!("{} and b", name),
+            |lc| lc + (Fr::from(coeffs.0), a) + (Fr::from(coeffs.1), b),
+            |lc| lc + TestConstraintSystem::<Bls12>::one(),
+            |lc| lc + b + a - a,
+        );
+        cs
+    };
+
+    let cs = synthesize("a", (2, 5));
+    // Names do not matter.
+    assert!(cs.structurally_eq(&synthesize("renamed", (2, 5))));
+    assert!(cs.diff(&synthesize("renamed", (2, 5))).is_empty());
+
+    // Neither does the order of the terms.
+    let mut reordered = TestConstraintSystem::<Bls12>::new();
+    let a = reordered.alloc(|| "a", || Ok(Fr::from(3u64))).unwrap();
+    let b = reordered.alloc(|| "b", || Ok(Fr::from(12u64))).unwrap();
+    reordered.enforce(
+        || "a and b",
+        |lc| lc + (Fr::from(5u64), b) + (Fr::from(2u64), a),
+        |lc| lc + TestConstraintSystem::<Bls12>::one(),
+        |lc| lc + b,
+    );
+    assert!(cs.structurally_eq(&reordered));
+
+    // Swapping the coefficients of `a` and `b` does.
+    let swapped = synthesize("a", (5, 2));
+    assert!(!cs.structurally_eq(&swapped));
+    let diff = cs.diff(&swapped);
+    assert_eq!(diff.len(), 1);
+    assert!(
+        diff[0].starts_with("constraint 0 (`a and b` vs `a and b`): A is "),
+        "{}",
+        diff[0]
+    );
+
+    let mut longer = synthesize("a", (2, 5));
+    longer.alloc(|| "c", || Ok(Fr::one())).unwrap();
+    assert_eq!(cs.diff(&longer), vec!["2 aux variables != 3 aux variables"]);
+}
+
+#[test]
+fn test_pretty_print() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let a = cs.alloc(|| "a", || Ok(Fr::from(3u64))).unwrap();
+    let b = cs.alloc(|| "b", || Ok(Fr::from(12u64))).unwrap();
+    cs.enforce(
+        || "scale",
+        |lc| lc + (Fr::from(4u64), a),
+        |lc| lc + TestConstraintSystem::<Bls12>::one(),
+        |lc| lc + b,
+    );
+
+    let first = cs.pretty_print();
+    assert!(first.contains("scale: (2^2 . "));
+    assert_eq!(cs.pretty_print(), first);
+}
+
+#[test]
+fn test_explain_unsatisfied() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let a = cs.alloc(|| "a", || Ok(Fr::from(3u64))).unwrap();
+    let b = cs.alloc(|| "b", || Ok(Fr::from(12u64))).unwrap();
+    let one = TestConstraintSystem::<Bls12>::one();
+    cs.enforce(
+        || "scale",
+        |lc| lc + (Fr::from(4u64), a),
+        |lc| lc + one,
+        |lc| lc + b,
+    );
+    cs.enforce(|| "square", |lc| lc + a, |lc| lc + a, |lc| lc + b);
+
+    assert_eq!(
+        cs.eval_constraint("scale"),
+        Some((Fr::from(12u64), Fr::one(), Fr::from(12u64)))
+    );
+    let (a_value, b_value, c_value) = cs.eval_constraint("square").unwrap();
+    assert_eq!(
+        (a_value, b_value, c_value),
+        (Fr::from(3u64), Fr::from(3u64), Fr::from(12u64))
+    );
+    let mut delta = a_value * b_value;
+    delta.sub_assign(&c_value);
+    assert!(!bool::from(delta.is_zero()));
+    assert_eq!(cs.eval_constraint("a"), None);
+    assert_eq!(cs.eval_constraint("missing"), None);
+
+    let explanation = cs.explain_unsatisfied();
+    assert!(!explanation.contains("scale"));
+    assert!(explanation.starts_with("square: (`a`) * (`a`) = (`b`)\n"));
+    assert!(explanation.contains(&format!("    A * B - C = {:?}\n", -Fr::from(3u64))));
+}
+
+#[test]
+fn test_is_constraint_satisfied() {
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let a = cs.alloc(|| "a", || Ok(Fr::from(3u64))).unwrap();
+    let b = cs.alloc(|| "b", || Ok(Fr::from(9u64))).unwrap();
+    let one = TestConstraintSystem::<Bls12>::one();
+    cs.enforce(|| "square", |lc| lc + a, |lc| lc + a, |lc| lc + b);
+    {
+        let mut cs = cs.namespace(|| "ns");
+        cs.enforce(|| "equal", |lc| lc + a, |lc| lc + one, |lc| lc + b);
+    }
+
+    assert_eq!(cs.is_constraint_satisfied("square"), Some(true));
+    assert_eq!(cs.is_constraint_satisfied("ns/equal"), Some(false));
+    assert_eq!(cs.is_constraint_satisfied("ns"), None);
+    assert_eq!(cs.is_constraint_satisfied("a"), None);
+    assert_eq!(cs.is_constraint_satisfied("missing"), None);
+
+    cs.set("b", Fr::from(3u64));
+    assert_eq!(cs.is_constraint_satisfied("square"), Some(false));
+    assert_eq!(cs.is_constraint_satisfied("ns/equal"), Some(true));
+}
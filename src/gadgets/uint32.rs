@@ -6,7 +6,7 @@ use pairing::Engine;
 
 use crate::{ConstraintSystem, LinearCombination, SynthesisError};
 
-use super::boolean::{AllocatedBit, Boolean};
+use super::boolean::{sha256_ch, AllocatedBit, Boolean};
 
 use super::multieq::MultiEq;
 
@@ -114,6 +114,16 @@ impl UInt32 {
         self.bits
     }
 
+    /// Turns this `UInt32` into its bits, least significant first.
+    pub fn into_bits_le(self) -> Vec<Boolean> {
+        self.bits
+    }
+
+    /// Converts bits, least significant first, into a `UInt32`.
+    pub fn from_bits_le(bits: &[Boolean]) -> Self {
+        Self::from_bits(bits)
+    }
+
     /// Converts a little-endian byte order representation of bits into a
     /// `UInt32`.
     pub fn from_bits(bits: &[Boolean]) -> Self {
@@ -264,7 +274,7 @@ impl UInt32 {
             b,
             c,
             |a, b, c| (a & b) ^ ((!a) & c),
-            |cs, i, a, b, c| Boolean::sha256_ch(cs.namespace(|| format!("ch {}", i)), a, b, c),
+            |cs, i, a, b, c| sha256_ch(cs.namespace(|| format!("ch {}", i)), a, b, c),
         )
     }
 
@@ -293,6 +303,31 @@ impl UInt32 {
         })
     }
 
+    /// AND this `UInt32` with another `UInt32`
+    pub fn and<E, CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        let new_value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a & b),
+            _ => None,
+        };
+
+        let bits = self
+            .bits
+            .iter()
+            .zip(other.bits.iter())
+            .enumerate()
+            .map(|(i, (a, b))| Boolean::and(cs.namespace(|| format!("and of bit {}", i)), a, b))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt32 {
+            bits,
+            value: new_value,
+        })
+    }
+
     /// Perform modular addition of several `UInt32` objects.
     #[allow(clippy::unnecessary_unwrap)]
     pub fn addmany<E, CS, M>(mut cs: M, operands: &[Self]) -> Result<Self, SynthesisError>
@@ -626,6 +661,67 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_uint32_and() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = rng.next_u32();
+            let b = rng.next_u32();
+            let c = rng.next_u32();
+
+            let a_bit = UInt32::alloc(cs.namespace(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt32::constant(b);
+            let c_bit = UInt32::alloc(cs.namespace(|| "c_bit"), Some(c)).unwrap();
+
+            let r = a_bit.and(cs.namespace(|| "first and"), &b_bit).unwrap();
+            let r = r.and(cs.namespace(|| "second and"), &c_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+
+            let expected = a & b & c;
+            assert!(r.value == Some(expected));
+            assert_eq!(
+                UInt32::from_bits_le(&r.clone().into_bits_le()).value,
+                Some(expected)
+            );
+
+            for (i, b) in r.into_bits_le().iter().enumerate() {
+                assert_eq!(b.get_value().unwrap(), (expected >> i) & 1 == 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_uint32_addmany_wraparound() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+
+        let a = UInt32::alloc(cs.namespace(|| "a"), Some(u32::max_value())).unwrap();
+        let b = UInt32::alloc(cs.namespace(|| "b"), Some(u32::max_value() - 2)).unwrap();
+        let c = UInt32::constant(5);
+
+        let r = {
+            let mut cs = MultiEq::new(&mut cs);
+            UInt32::addmany(cs.namespace(|| "addition"), &[a, b, c]).unwrap()
+        };
+
+        assert!(cs.is_satisfied());
+
+        let expected = u32::max_value()
+            .wrapping_add(u32::max_value() - 2)
+            .wrapping_add(5);
+        assert_eq!(expected, 1);
+        assert!(r.value == Some(expected));
+        for (i, b) in r.into_bits_le().iter().enumerate() {
+            assert_eq!(b.get_value().unwrap(), (expected >> i) & 1 == 1);
+        }
+    }
+
     #[test]
     fn test_uint32_rotr() {
         let mut rng = XorShiftRng::from_seed([
@@ -0,0 +1,583 @@
+//! Circuit representation of a [`u64`], with helpers for the [`sha512`]
+//! gadgets.
+//!
+//! [`sha512`]: super::sha512
+
+use ff::{Field, PrimeField};
+use pairing::Engine;
+
+use crate::{ConstraintSystem, LinearCombination, SynthesisError};
+
+use super::boolean::{sha256_ch, AllocatedBit, Boolean};
+
+use super::multieq::MultiEq;
+use super::uint32::UInt32;
+
+/// Represents an interpretation of 64 `Boolean` objects as an
+/// unsigned integer.
+#[derive(Clone)]
+pub struct UInt64 {
+    // Least significant bit first
+    bits: Vec<Boolean>,
+    value: Option<u64>,
+}
+
+impl UInt64 {
+    /// Construct a constant `UInt64` from a `u64`
+    pub fn constant(value: u64) -> Self {
+        let bits = (0..64)
+            .map(|i| Boolean::constant((value >> i) & 1 == 1))
+            .collect();
+
+        UInt64 {
+            bits,
+            value: Some(value),
+        }
+    }
+
+    /// Allocate a `UInt64` in the constraint system
+    pub fn alloc<E, CS>(mut cs: CS, value: Option<u64>) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        let bits = (0..64)
+            .map(|i| {
+                Ok(Boolean::from(AllocatedBit::alloc(
+                    cs.namespace(|| format!("allocated bit {}", i)),
+                    value.map(|v| (v >> i) & 1 == 1),
+                )?))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        Ok(UInt64 { bits, value })
+    }
+
+    pub fn get_value(&self) -> Option<u64> {
+        self.value
+    }
+
+    pub fn into_bits_be(self) -> Vec<Boolean> {
+        let mut ret = self.bits;
+        ret.reverse();
+        ret
+    }
+
+    pub fn from_bits_be(bits: &[Boolean]) -> Self {
+        assert_eq!(bits.len(), 64);
+
+        let mut value = Some(0u64);
+        for b in bits {
+            value = match (value, b.get_value()) {
+                (Some(v), Some(b)) => Some((v << 1) | u64::from(b)),
+                _ => None,
+            };
+        }
+
+        UInt64 {
+            value,
+            bits: bits.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Turns this `UInt64` into its bits, least significant first.
+    pub fn into_bits_le(self) -> Vec<Boolean> {
+        self.bits
+    }
+
+    /// Converts bits, least significant first, into a `UInt64`.
+    pub fn from_bits_le(bits: &[Boolean]) -> Self {
+        assert_eq!(bits.len(), 64);
+
+        let value = bits.iter().rev().try_fold(0u64, |acc, bit| {
+            bit.get_value().map(|bit| (acc << 1) | u64::from(bit))
+        });
+
+        UInt64 {
+            bits: bits.to_vec(),
+            value,
+        }
+    }
+
+    /// Splits this `UInt64` into its low and high 32-bit halves, without any
+    /// constraints.
+    pub fn to_uint32_pair(&self) -> (UInt32, UInt32) {
+        (
+            UInt32::from_bits(&self.bits[..32]),
+            UInt32::from_bits(&self.bits[32..]),
+        )
+    }
+
+    pub fn rotr(&self, by: usize) -> Self {
+        let by = by % 64;
+
+        let new_bits = self
+            .bits
+            .iter()
+            .skip(by)
+            .chain(self.bits.iter())
+            .take(64)
+            .cloned()
+            .collect();
+
+        UInt64 {
+            bits: new_bits,
+            value: self.value.map(|v| v.rotate_right(by as u32)),
+        }
+    }
+
+    pub fn shr(&self, by: usize) -> Self {
+        let by = by % 64;
+
+        let fill = Boolean::constant(false);
+
+        let new_bits = self
+            .bits
+            .iter() // The bits are least significant first
+            .skip(by) // Skip the bits that will be lost during the shift
+            .chain(Some(&fill).into_iter().cycle()) // Rest will be zeros
+            .take(64) // Only 64 bits needed!
+            .cloned()
+            .collect();
+
+        UInt64 {
+            bits: new_bits,
+            value: self.value.map(|v| v >> by as u32),
+        }
+    }
+
+    fn triop<E, CS, F, U>(
+        mut cs: CS,
+        a: &Self,
+        b: &Self,
+        c: &Self,
+        tri_fn: F,
+        circuit_fn: U,
+    ) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+        F: Fn(u64, u64, u64) -> u64,
+        U: Fn(&mut CS, usize, &Boolean, &Boolean, &Boolean) -> Result<Boolean, SynthesisError>,
+    {
+        let new_value = match (a.value, b.value, c.value) {
+            (Some(a), Some(b), Some(c)) => Some(tri_fn(a, b, c)),
+            _ => None,
+        };
+
+        let bits = a
+            .bits
+            .iter()
+            .zip(b.bits.iter())
+            .zip(c.bits.iter())
+            .enumerate()
+            .map(|(i, ((a, b), c))| circuit_fn(&mut cs, i, a, b, c))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt64 {
+            bits,
+            value: new_value,
+        })
+    }
+
+    /// Compute the `maj` value (a and b) xor (a and c) xor (b and c)
+    /// during SHA512.
+    pub fn sha512_maj<E, CS>(cs: CS, a: &Self, b: &Self, c: &Self) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        Self::triop(
+            cs,
+            a,
+            b,
+            c,
+            |a, b, c| (a & b) ^ (a & c) ^ (b & c),
+            |cs, i, a, b, c| Boolean::sha256_maj(cs.namespace(|| format!("maj {}", i)), a, b, c),
+        )
+    }
+
+    /// Compute the `ch` value `(a and b) xor ((not a) and c)`
+    /// during SHA512.
+    pub fn sha512_ch<E, CS>(cs: CS, a: &Self, b: &Self, c: &Self) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        Self::triop(
+            cs,
+            a,
+            b,
+            c,
+            |a, b, c| (a & b) ^ ((!a) & c),
+            |cs, i, a, b, c| sha256_ch(cs.namespace(|| format!("ch {}", i)), a, b, c),
+        )
+    }
+
+    /// XOR this `UInt64` with another `UInt64`
+    pub fn xor<E, CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        let new_value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a ^ b),
+            _ => None,
+        };
+
+        let bits = self
+            .bits
+            .iter()
+            .zip(other.bits.iter())
+            .enumerate()
+            .map(|(i, (a, b))| Boolean::xor(cs.namespace(|| format!("xor of bit {}", i)), a, b))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt64 {
+            bits,
+            value: new_value,
+        })
+    }
+
+    /// AND this `UInt64` with another `UInt64`
+    pub fn and<E, CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        let new_value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a & b),
+            _ => None,
+        };
+
+        let bits = self
+            .bits
+            .iter()
+            .zip(other.bits.iter())
+            .enumerate()
+            .map(|(i, (a, b))| Boolean::and(cs.namespace(|| format!("and of bit {}", i)), a, b))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt64 {
+            bits,
+            value: new_value,
+        })
+    }
+
+    /// Perform modular addition of several `UInt64` objects.
+    #[allow(clippy::unnecessary_unwrap)]
+    pub fn addmany<E, CS, M>(mut cs: M, operands: &[Self]) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+        M: ConstraintSystem<E, Root = MultiEq<E, CS>>,
+    {
+        assert!(operands.len() >= 2); // Weird trivial cases that should never happen
+
+        // Compute the maximum value of the sum so we allocate enough bits for
+        // the result
+        let mut max_value = (operands.len() as u128)
+            .checked_mul(u128::from(u64::max_value()))
+            .expect("too many operands");
+
+        // The sum, carry included, must not wrap around in the scalar field,
+        // or the result bits would not be the integer sum. `enforce_equal`
+        // also needs its bits to stay below the capacity.
+        let result_bits_len = 128 - max_value.leading_zeros();
+        assert!(result_bits_len < E::Fr::CAPACITY);
+
+        // Keep track of the resulting value
+        let mut result_value = Some(0u128);
+
+        // This is a linear combination that we will enforce to equal the
+        // output
+        let mut lc = LinearCombination::zero();
+
+        let mut all_constants = true;
+
+        // Iterate over the operands
+        for op in operands {
+            // Accumulate the value
+            match op.value {
+                Some(val) => {
+                    if let Some(v) = result_value.as_mut() {
+                        *v += u128::from(val);
+                    }
+                }
+                None => {
+                    // If any of our operands have unknown value, we won't
+                    // know the value of the result
+                    result_value = None;
+                }
+            }
+
+            // Iterate over each bit of the operand and add the operand to
+            // the linear combination
+            let mut coeff = E::Fr::one();
+            for bit in &op.bits {
+                lc = lc + &bit.lc(CS::one(), coeff);
+
+                all_constants &= bit.is_constant();
+
+                coeff = coeff.double();
+            }
+        }
+
+        // The value of the actual result is modulo 2^64
+        let modular_value = result_value.map(|v| v as u64);
+
+        if all_constants && modular_value.is_some() {
+            // We can just return a constant, rather than
+            // unpacking the result into allocated bits.
+
+            return Ok(UInt64::constant(modular_value.unwrap()));
+        }
+
+        // Storage area for the resulting bits
+        let mut result_bits = vec![];
+
+        // Linear combination representing the output,
+        // for comparison with the sum of the operands
+        let mut result_lc = LinearCombination::zero();
+
+        // Allocate each bit of the result
+        let mut coeff = E::Fr::one();
+        let mut i = 0;
+        while max_value != 0 {
+            // Allocate the bit
+            let b = AllocatedBit::alloc(
+                cs.namespace(|| format!("result bit {}", i)),
+                result_value.map(|v| (v >> i) & 1 == 1),
+            )?;
+
+            // Add this bit to the result combination
+            result_lc = result_lc + (coeff, b.get_variable());
+
+            result_bits.push(b.into());
+
+            max_value >>= 1;
+            i += 1;
+            coeff = coeff.double();
+        }
+
+        // Enforce equality between the sum and result
+        cs.get_root().enforce_equal(i, &lc, &result_lc);
+
+        // Discard carry bits that we don't care about
+        result_bits.truncate(64);
+
+        Ok(UInt64 {
+            bits: result_bits,
+            value: modular_value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UInt64;
+    use crate::gadgets::boolean::Boolean;
+    use crate::gadgets::multieq::MultiEq;
+    use crate::gadgets::test::*;
+    use crate::ConstraintSystem;
+    use blstrs::Bls12;
+    use ff::Field;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    fn check_bits(r: &UInt64, mut expected: u64) {
+        for b in r.bits.iter() {
+            assert_eq!(b.get_value().unwrap(), expected & 1 == 1);
+            expected >>= 1;
+        }
+    }
+
+    #[test]
+    fn test_uint64_from_bits_be() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..1000 {
+            let v = rng.next_u64();
+            let bits = (0..64)
+                .rev()
+                .map(|i| Boolean::constant((v >> i) & 1 == 1))
+                .collect::<Vec<_>>();
+
+            let b = UInt64::from_bits_be(&bits);
+            assert_eq!(b.value, Some(v));
+            check_bits(&b, v);
+
+            let back = b.into_bits_be();
+            assert!(back
+                .iter()
+                .zip(bits.iter())
+                .all(|(a, b)| a.get_value() == b.get_value()));
+        }
+    }
+
+    #[test]
+    fn test_uint64_addmany() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = rng.next_u64();
+            let b = rng.next_u64();
+            let c = rng.next_u64();
+            let d = rng.next_u64();
+
+            let expected = (a ^ b).wrapping_add(c).wrapping_add(d);
+
+            let a_bit = UInt64::alloc(cs.namespace(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt64::constant(b);
+            let c_bit = UInt64::constant(c);
+            let d_bit = UInt64::alloc(cs.namespace(|| "d_bit"), Some(d)).unwrap();
+
+            let r = a_bit.xor(cs.namespace(|| "xor"), &b_bit).unwrap();
+            let r = {
+                let mut cs = MultiEq::new(&mut cs);
+                UInt64::addmany(cs.namespace(|| "addition"), &[r, c_bit, d_bit]).unwrap()
+            };
+
+            assert!(cs.is_satisfied());
+            assert_eq!(r.value, Some(expected));
+            check_bits(&r, expected);
+
+            // Flip a bit and see if the addition constraint still works
+            if cs.get("addition/result bit 0/boolean").is_zero().into() {
+                cs.set("addition/result bit 0/boolean", Field::one());
+            } else {
+                cs.set("addition/result bit 0/boolean", Field::zero());
+            }
+
+            assert!(!cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_uint64_addmany_wraparound() {
+        // Far more operands than SHA-512 needs, the carry takes 9 bits.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let operands = (0..300)
+            .map(|i| {
+                UInt64::alloc(
+                    cs.namespace(|| format!("operand {}", i)),
+                    Some(u64::max_value() - i),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let expected = (0..300).fold(0u64, |acc, i| acc.wrapping_add(u64::max_value() - i));
+
+        let r = {
+            let mut cs = MultiEq::new(&mut cs);
+            UInt64::addmany(cs.namespace(|| "addition"), &operands).unwrap()
+        };
+
+        assert!(cs.is_satisfied());
+        assert_eq!(r.value, Some(expected));
+        check_bits(&r, expected);
+
+        // The carry bits are constrained too.
+        let path = "addition/result bit 70/boolean";
+        if cs.get(path).is_zero().into() {
+            cs.set(path, Field::one());
+        } else {
+            cs.set(path, Field::zero());
+        }
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint64_and_and_split() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = rng.next_u64();
+            let b = rng.next_u64();
+
+            let a_bit = UInt64::alloc(cs.namespace(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt64::constant(b);
+            let r = a_bit.and(cs.namespace(|| "and"), &b_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(r.value, Some(a & b));
+            check_bits(&r, a & b);
+
+            let (low, high) = r.to_uint32_pair();
+            let words = low
+                .into_bits()
+                .into_iter()
+                .chain(high.into_bits())
+                .collect::<Vec<_>>();
+            assert_eq!(UInt64::from_bits_le(&words).value, Some(a & b));
+            assert_eq!(UInt64::from_bits_le(&r.into_bits_le()).value, Some(a & b));
+        }
+    }
+
+    #[test]
+    fn test_uint64_rotr_shr() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let num = rng.next_u64();
+        let a = UInt64::constant(num);
+
+        for i in 0..64 {
+            let b = a.rotr(i);
+            assert_eq!(b.value, Some(num.rotate_right(i as u32)));
+            check_bits(&b, num.rotate_right(i as u32));
+
+            let b = a.shr(i);
+            assert_eq!(b.value, Some(num >> i));
+            check_bits(&b, num >> i);
+        }
+    }
+
+    #[test]
+    fn test_uint64_sha512_maj_ch() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = rng.next_u64();
+            let b = rng.next_u64();
+            let c = rng.next_u64();
+
+            let a_bit = UInt64::alloc(cs.namespace(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt64::constant(b);
+            let c_bit = UInt64::alloc(cs.namespace(|| "c_bit"), Some(c)).unwrap();
+
+            let maj = UInt64::sha512_maj(cs.namespace(|| "maj"), &a_bit, &b_bit, &c_bit).unwrap();
+            let ch = UInt64::sha512_ch(cs.namespace(|| "ch"), &a_bit, &b_bit, &c_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+
+            let expected = (a & b) ^ (a & c) ^ (b & c);
+            assert_eq!(maj.value, Some(expected));
+            check_bits(&maj, expected);
+
+            let expected = (a & b) ^ ((!a) & c);
+            assert_eq!(ch.value, Some(expected));
+            check_bits(&ch, expected);
+        }
+    }
+}
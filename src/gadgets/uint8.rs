@@ -0,0 +1,103 @@
+//! Circuit representation of a [`u8`], for byte-oriented gadgets such as the
+//! [`sbox`].
+//!
+//! [`sbox`]: super::sbox
+
+use pairing::Engine;
+
+use crate::{ConstraintSystem, SynthesisError};
+
+use super::boolean::{AllocatedBit, Boolean};
+
+/// Represents an interpretation of 8 `Boolean` objects as an
+/// unsigned integer.
+#[derive(Clone)]
+pub struct UInt8 {
+    // Least significant bit first
+    bits: Vec<Boolean>,
+    value: Option<u8>,
+}
+
+impl UInt8 {
+    /// Construct a constant `UInt8` from a `u8`
+    pub fn constant(value: u8) -> Self {
+        let bits = (0..8)
+            .map(|i| Boolean::constant((value >> i) & 1 == 1))
+            .collect();
+
+        UInt8 {
+            bits,
+            value: Some(value),
+        }
+    }
+
+    /// Allocate a `UInt8` in the constraint system
+    pub fn alloc<E, CS>(mut cs: CS, value: Option<u8>) -> Result<Self, SynthesisError>
+    where
+        E: Engine,
+        CS: ConstraintSystem<E>,
+    {
+        let bits = (0..8)
+            .map(|i| {
+                Ok(Boolean::from(AllocatedBit::alloc(
+                    cs.namespace(|| format!("allocated bit {}", i)),
+                    value.map(|v| (v >> i) & 1 == 1),
+                )?))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        Ok(UInt8 { bits, value })
+    }
+
+    pub fn get_value(&self) -> Option<u8> {
+        self.value
+    }
+
+    /// Turns this `UInt8` into its bits, least significant first.
+    pub fn into_bits_le(self) -> Vec<Boolean> {
+        self.bits
+    }
+
+    /// Converts bits, least significant first, into a `UInt8`.
+    pub fn from_bits_le(bits: &[Boolean]) -> Self {
+        assert_eq!(bits.len(), 8);
+
+        let value = bits.iter().rev().try_fold(0u8, |acc, bit| {
+            bit.get_value().map(|bit| (acc << 1) | u8::from(bit))
+        });
+
+        UInt8 {
+            bits: bits.to_vec(),
+            value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UInt8;
+    use crate::gadgets::test::*;
+    use crate::ConstraintSystem;
+    use blstrs::Bls12;
+
+    #[test]
+    fn test_uint8_bits_le() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+
+        for v in 0..=255u8 {
+            let a = UInt8::alloc(cs.namespace(|| format!("alloc {}", v)), Some(v)).unwrap();
+            let bits = a.into_bits_le();
+            for (i, bit) in bits.iter().enumerate() {
+                assert_eq!(bit.get_value(), Some((v >> i) & 1 == 1));
+            }
+            assert_eq!(UInt8::from_bits_le(&bits).get_value(), Some(v));
+            assert_eq!(
+                UInt8::from_bits_le(&UInt8::constant(v).into_bits_le()).get_value(),
+                Some(v)
+            );
+        }
+
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 256 * 8);
+    }
+}
@@ -0,0 +1,35 @@
+//! Helpers for gadgets which depend on the size of the scalar field.
+
+use ff::PrimeField;
+use pairing::Engine;
+
+/// The number of bits any value below `2^field_capacity` fits into without
+/// wrapping around the modulus, i.e. `E::Fr::CAPACITY`.
+///
+/// This is one less than `field_num_bits`, since the top bit is only partially
+/// usable: not every value with it set is below the modulus. Range checks and
+/// bit packing must stay within this many bits to be sound.
+pub fn field_capacity<E: Engine>() -> usize {
+    E::Fr::CAPACITY as usize
+}
+
+/// The number of bits needed to represent any field element, i.e. the bit
+/// length of the modulus, `E::Fr::NUM_BITS`.
+///
+/// Decomposing an arbitrary field element into bits takes this many bits, but
+/// not every combination of them is a field element, see `field_capacity`.
+pub fn field_num_bits<E: Engine>() -> usize {
+    E::Fr::NUM_BITS as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::{field_capacity, field_num_bits};
+    use blstrs::Bls12;
+
+    #[test]
+    fn test_field_bits() {
+        assert_eq!(field_capacity::<Bls12>(), 254);
+        assert_eq!(field_num_bits::<Bls12>(), 255);
+    }
+}
@@ -0,0 +1,136 @@
+use blstrs::Compress;
+use ff::PrimeField;
+use pairing::{Engine, MillerLoopResult, MultiMillerLoop};
+use serde::Serialize;
+
+use super::{
+    inner_product, prove::aggregate_with_commitments, AggregateProof, ProverSRS, TranscriptInclude,
+};
+use crate::groth16::Proof;
+use crate::SynthesisError;
+
+/// Aggregates proofs as they arrive, instead of all at once like
+/// [`aggregate_proofs`](super::aggregate_proofs).
+///
+/// The commitments to A, B and C are the most expensive part to compute that
+/// does not depend on the Fiat-Shamir challenges, so they are accumulated as
+/// Miller loop results on every `push`. `finalize` then only needs to run the
+/// final exponentiations before proving TIPP and MIPP, and outputs the same
+/// proof `aggregate_proofs` would for the same proofs, in the same order.
+pub struct AggregatorState<'a, E: MultiMillerLoop> {
+    srs: &'a ProverSRS<E>,
+    a: Vec<E::G1Affine>,
+    b: Vec<E::G2Affine>,
+    c: Vec<E::G1Affine>,
+    com_ab: (
+        <E as MultiMillerLoop>::Result,
+        <E as MultiMillerLoop>::Result,
+    ),
+    com_c: (
+        <E as MultiMillerLoop>::Result,
+        <E as MultiMillerLoop>::Result,
+    ),
+}
+
+impl<'a, E: MultiMillerLoop> AggregatorState<'a, E> {
+    pub fn new(srs: &'a ProverSRS<E>) -> Self {
+        AggregatorState {
+            srs,
+            a: Vec::with_capacity(srs.n),
+            b: Vec::with_capacity(srs.n),
+            c: Vec::with_capacity(srs.n),
+            com_ab: Default::default(),
+            com_c: Default::default(),
+        }
+    }
+
+    /// Number of proofs pushed so far.
+    pub fn len(&self) -> usize {
+        self.a.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+
+    /// Adds a proof to the aggregation. Fails with `MalformedProofs` if the SRS
+    /// has no room left for it.
+    pub fn push(&mut self, proof: &Proof<E>) -> Result<(), SynthesisError> {
+        let i = self.len();
+        if i >= self.srs.n {
+            return Err(SynthesisError::MalformedProofs(format!(
+                "the SRS only allows aggregating {} proofs",
+                self.srs.n
+            )));
+        }
+        let (vkey, wkey) = (&self.srs.vkey, &self.srs.wkey);
+
+        // e(A_i, v_{1,i})e(w_{1,i}, B_i) and e(A_i, v_{2,i})e(w_{2,i}, B_i)
+        self.com_ab.0 += inner_product::pairing_miller_affine::<E>(
+            &[proof.a, wkey.a[i]],
+            &[vkey.a[i], proof.b],
+        )?;
+        self.com_ab.1 += inner_product::pairing_miller_affine::<E>(
+            &[proof.a, wkey.b[i]],
+            &[vkey.b[i], proof.b],
+        )?;
+        // e(C_i, v_{1,i}) and e(C_i, v_{2,i})
+        self.com_c.0 += inner_product::pairing_miller_affine::<E>(&[proof.c], &[vkey.a[i]])?;
+        self.com_c.1 += inner_product::pairing_miller_affine::<E>(&[proof.c], &[vkey.b[i]])?;
+
+        self.a.push(proof.a);
+        self.b.push(proof.b);
+        self.c.push(proof.c);
+
+        Ok(())
+    }
+
+    /// Produces the aggregate proof of all the proofs pushed so far. See
+    /// [`aggregate_proofs`](super::aggregate_proofs) for the meaning of
+    /// `transcript_include`.
+    ///
+    /// As with `aggregate_proofs`, the number of proofs must be a power of two,
+    /// and exactly the number the SRS was specialized for.
+    pub fn finalize(self, transcript_include: &[u8]) -> Result<AggregateProof<E>, SynthesisError>
+    where
+        E: std::fmt::Debug,
+        E::Fr: Serialize,
+        <E::Fr as PrimeField>::Repr: Send + Sync,
+        <E as Engine>::Gt: Compress + Serialize,
+        E::G1: Serialize,
+        E::G1Affine: Serialize,
+        E::G2Affine: Serialize,
+    {
+        let n = self.len();
+        if n < 2 {
+            return Err(SynthesisError::MalformedProofs(
+                "aggregating less than 2 proofs is not allowed".to_string(),
+            ));
+        }
+        if !n.is_power_of_two() {
+            return Err(SynthesisError::NonPowerOfTwo);
+        }
+        if !self.srs.has_correct_len(n) {
+            return Err(SynthesisError::MalformedSrs);
+        }
+
+        let com_ab = (
+            self.com_ab.0.final_exponentiation(),
+            self.com_ab.1.final_exponentiation(),
+        );
+        let com_c = (
+            self.com_c.0.final_exponentiation(),
+            self.com_c.1.final_exponentiation(),
+        );
+
+        aggregate_with_commitments(
+            self.srs,
+            TranscriptInclude::Bytes(transcript_include),
+            self.a,
+            self.b,
+            self.c,
+            com_ab,
+            com_c,
+        )
+    }
+}
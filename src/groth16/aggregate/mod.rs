@@ -2,13 +2,18 @@ use std::ops::AddAssign;
 
 use ff::Field;
 use group::{prime::PrimeCurveAffine, Curve};
+use pairing::Engine;
 use rayon::prelude::*;
+use serde::Serialize;
+
+use self::transcript::{Challenge, Transcript};
 
 #[macro_use]
 mod macros;
 
 mod accumulator;
 mod commit;
+mod incremental;
 mod inner_product;
 mod msm;
 mod poly;
@@ -19,6 +24,7 @@ mod transcript;
 mod verify;
 
 pub use self::commit::*;
+pub use self::incremental::*;
 pub use self::proof::*;
 pub use self::prove::*;
 pub use self::srs::*;
@@ -36,6 +42,51 @@ fn structured_scalar_power<F: Field>(num: usize, s: &F) -> Vec<F> {
     powers
 }
 
+/// Number of proofs actually aggregated when `num_real` proofs are padded by
+/// `aggregate_proofs_padded`: the next power of two, and at least 2.
+fn padded_len(num_real: usize) -> usize {
+    num_real.next_power_of_two().max(2)
+}
+
+/// Binds the number of real proofs of a padded aggregation to its transcript,
+/// so that the aggregate proof cannot be verified as a padded aggregation of a
+/// different number of proofs.
+fn padded_transcript_include(num_real: usize, transcript_include: &[u8]) -> Vec<u8> {
+    let mut include = (num_real as u64).to_le_bytes().to_vec();
+    include.extend_from_slice(transcript_include);
+    include
+}
+
+/// What the random linear combination of the aggregated proofs is bound to,
+/// besides the commitments to the proofs themselves.
+#[derive(Clone, Copy)]
+enum TranscriptInclude<'a> {
+    /// Arbitrary bytes, see `aggregate_proofs`.
+    Bytes(&'a [u8]),
+    /// A digest computed by the caller, see `aggregate_proofs_with_digest`.
+    Digest(&'a [u8; 32]),
+}
+
+impl TranscriptInclude<'_> {
+    /// Derives the challenge `r` of the random linear combination. Digests use
+    /// their own transcript tag, so that they can never be confused with bytes.
+    fn random_r<E: Engine>(self, hcom: &Challenge<E>) -> Challenge<E>
+    where
+        E::Fr: Serialize,
+    {
+        match self {
+            TranscriptInclude::Bytes(include) => Transcript::<E>::new("random-r")
+                .write(hcom)
+                .write(&include)
+                .into_challenge(),
+            TranscriptInclude::Digest(digest) => Transcript::<E>::new("random-r-digest")
+                .write(hcom)
+                .write(digest)
+                .into_challenge(),
+        }
+    }
+}
+
 /// compress is similar to commit::{V,W}KEY::compress: it modifies the `vec`
 /// vector by setting the value at index $i:0 -> split$  $vec[i] = vec[i] +
 /// vec[i+split]^scaler$. The `vec` vector is half of its size after this call.
@@ -8,6 +8,12 @@ use serde::{Deserialize, Serialize};
 use crate::groth16::aggregate::{commit, srs};
 use crate::SynthesisError;
 
+/// Magic bytes starting an aggregate proof written by `write_versioned`.
+const MAGIC: [u8; 4] = *b"BPAG";
+/// Version of the format written by `write_versioned`, to be bumped whenever the
+/// encoding written by `write` changes.
+const VERSION: u32 = 1;
+
 /// AggregateProof contains all elements to verify n aggregated Groth16 proofs
 /// using inner pairing product arguments. This proof can be created by any
 /// party in possession of valid Groth16 proofs.
@@ -131,6 +137,50 @@ where
         out.len()
     }
 
+    /// Writes the proof as `write` does, prefixed with 4 magic bytes and the
+    /// little-endian `u32` version of the format, so that proofs stored on disk
+    /// can be told apart from proofs written by other versions.
+    pub fn write_versioned(&self, mut out: impl Write) -> std::io::Result<()> {
+        out.write_all(&MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        self.write(out)
+    }
+
+    /// Reads a proof written by `write_versioned`. Fails if the magic bytes or
+    /// the version do not match, if any point is not a valid encoding of a point
+    /// in the prime order subgroup, or if the proof does not pass
+    /// `parsing_check`.
+    pub fn read_versioned(mut source: impl Read) -> std::io::Result<Self> {
+        let mut magic = [0u8; 4];
+        source.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "not a versioned aggregate proof",
+            ));
+        }
+
+        let mut version = [0u8; 4];
+        source.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported aggregate proof version {}, expected {}",
+                    version, VERSION
+                ),
+            ));
+        }
+
+        let proof = Self::read(source)?;
+        proof
+            .parsing_check()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        Ok(proof)
+    }
+
     pub fn read(mut source: impl Read) -> std::io::Result<Self> {
         let com_ab = (
             <<E as Engine>::Gt as Compress>::read_compressed(&mut source)?,
@@ -523,6 +573,38 @@ mod tests {
         assert_eq!(des_proof, proof);
     }
 
+    #[test]
+    fn test_proof_io_versioned() {
+        let proof = fake_proof();
+        let mut buffer = Vec::new();
+        proof.write_versioned(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 8 + proof.serialized_len());
+        assert_eq!(&buffer[..4], b"BPAG");
+
+        let out = AggregateProof::<Bls12>::read_versioned(&buffer[..]).unwrap();
+        assert_eq!(proof, out);
+
+        // Unversioned proofs are rejected.
+        let mut raw = Vec::new();
+        proof.write(&mut raw).unwrap();
+        assert!(AggregateProof::<Bls12>::read_versioned(&raw[..]).is_err());
+
+        // So are other versions.
+        let mut other = buffer.clone();
+        other[4..8].copy_from_slice(&2u32.to_le_bytes());
+        let err = AggregateProof::<Bls12>::read_versioned(&other[..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unsupported aggregate proof version 2, expected 1"
+        );
+
+        // And invalid points.
+        let mut invalid = buffer.clone();
+        let len = invalid.len();
+        invalid[len - 1] ^= 1;
+        assert!(AggregateProof::<Bls12>::read_versioned(&invalid[..]).is_err());
+    }
+
     #[test]
     fn test_proof_check() {
         let p = G1Projective::generator().to_affine();
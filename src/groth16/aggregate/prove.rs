@@ -9,11 +9,11 @@ use serde::Serialize;
 use super::{
     commit,
     commit::{VKey, WKey},
-    compress, inner_product,
+    compress, inner_product, padded_len, padded_transcript_include,
     poly::DensePolynomial,
     structured_scalar_power,
     transcript::Transcript,
-    AggregateProof, GipaProof, KZGOpening, ProverSRS, TippMippProof,
+    AggregateProof, GipaProof, KZGOpening, ProverSRS, TippMippProof, TranscriptInclude,
 };
 use crate::groth16::{multiscalar::*, Proof};
 use crate::SynthesisError;
@@ -35,6 +35,45 @@ pub fn aggregate_proofs<E>(
     transcript_include: &[u8],
     proofs: &[Proof<E>],
 ) -> Result<AggregateProof<E>, SynthesisError>
+where
+    E: MultiMillerLoop + std::fmt::Debug,
+    E::Fr: Serialize,
+    <E::Fr as PrimeField>::Repr: Send + Sync,
+    <E as Engine>::Gt: Compress + Serialize,
+    E::G1: Serialize,
+    E::G1Affine: Serialize,
+    E::G2Affine: Serialize,
+{
+    aggregate_proofs_inner(srs, TranscriptInclude::Bytes(transcript_include), proofs)
+}
+
+/// Like [`aggregate_proofs`], but binds the aggregation to a 32-byte digest
+/// computed by the caller, e.g. a commitment to the statements, instead of
+/// hashing `transcript_include` into the transcript. The result must be checked
+/// with [`verify_aggregate_proof_with_digest`](super::verify_aggregate_proof_with_digest)
+/// and the same digest.
+pub fn aggregate_proofs_with_digest<E>(
+    srs: &ProverSRS<E>,
+    transcript_digest: [u8; 32],
+    proofs: &[Proof<E>],
+) -> Result<AggregateProof<E>, SynthesisError>
+where
+    E: MultiMillerLoop + std::fmt::Debug,
+    E::Fr: Serialize,
+    <E::Fr as PrimeField>::Repr: Send + Sync,
+    <E as Engine>::Gt: Compress + Serialize,
+    E::G1: Serialize,
+    E::G1Affine: Serialize,
+    E::G2Affine: Serialize,
+{
+    aggregate_proofs_inner(srs, TranscriptInclude::Digest(&transcript_digest), proofs)
+}
+
+fn aggregate_proofs_inner<E>(
+    srs: &ProverSRS<E>,
+    include: TranscriptInclude,
+    proofs: &[Proof<E>],
+) -> Result<AggregateProof<E>, SynthesisError>
 where
     E: MultiMillerLoop + std::fmt::Debug,
     E::Fr: Serialize,
@@ -75,19 +114,79 @@ where
         let com_c = commit::single_g1::<E>(&srs.vkey, refc)
     };
 
+    aggregate_with_commitments(srs, include, a, b, c, com_ab, com_c)
+}
+
+/// Aggregates any number of proofs, by padding them to the next power of two
+/// (and at least 2) with copies of the last proof. `srs` must be specialized
+/// for the padded number of proofs.
+///
+/// The padding proofs are valid proofs of the last statement, which the
+/// verifier repeats itself, so they do not prove anything beyond the real
+/// statements. The number of real proofs is included in the transcript. The
+/// result must be checked with [`verify_aggregate_proof_padded`](super::verify_aggregate_proof_padded),
+/// with the public inputs of the real proofs only.
+pub fn aggregate_proofs_padded<E>(
+    srs: &ProverSRS<E>,
+    transcript_include: &[u8],
+    proofs: &[Proof<E>],
+) -> Result<AggregateProof<E>, SynthesisError>
+where
+    E: MultiMillerLoop + std::fmt::Debug,
+    E::Fr: Serialize,
+    <E::Fr as PrimeField>::Repr: Send + Sync,
+    <E as Engine>::Gt: Compress + Serialize,
+    E::G1: Serialize,
+    E::G1Affine: Serialize,
+    E::G2Affine: Serialize,
+{
+    let last = proofs
+        .last()
+        .ok_or_else(|| SynthesisError::MalformedProofs("no proofs to aggregate".to_string()))?;
+
+    let mut padded = proofs.to_vec();
+    padded.resize(padded_len(proofs.len()), last.clone());
+
+    aggregate_proofs(
+        srs,
+        &padded_transcript_include(proofs.len(), transcript_include),
+        &padded,
+    )
+}
+
+/// The rest of [`aggregate_proofs`], once the A, B and C elements of the proofs
+/// have been committed to.
+pub(super) fn aggregate_with_commitments<E>(
+    srs: &ProverSRS<E>,
+    include: TranscriptInclude,
+    a: Vec<E::G1Affine>,
+    b: Vec<E::G2Affine>,
+    c: Vec<E::G1Affine>,
+    com_ab: commit::Output<E>,
+    com_c: commit::Output<E>,
+) -> Result<AggregateProof<E>, SynthesisError>
+where
+    E: MultiMillerLoop + std::fmt::Debug,
+    E::Fr: Serialize,
+    <E::Fr as PrimeField>::Repr: Send + Sync,
+    <E as Engine>::Gt: Compress + Serialize,
+    E::G1: Serialize,
+    E::G1Affine: Serialize,
+    E::G2Affine: Serialize,
+{
+    let refa = &a;
+    let refc = &c;
+
     let hcom = Transcript::<E>::new("hcom")
         .write(&com_ab)
         .write(&com_c)
         .into_challenge();
 
     // Derive a random scalar to perform a linear combination of proofs
-    let r = Transcript::<E>::new("random-r")
-        .write(&hcom)
-        .write(&transcript_include)
-        .into_challenge();
+    let r = include.random_r(&hcom);
 
     // 1,r, r^2, r^3, r^4 ...
-    let r_vec: Vec<E::Fr> = structured_scalar_power(proofs.len(), &*r);
+    let r_vec: Vec<E::Fr> = structured_scalar_power(a.len(), &*r);
     // 1,r^-1, r^-2, r^-3
     let r_inv = r_vec
         .par_iter()
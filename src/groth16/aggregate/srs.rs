@@ -10,6 +10,7 @@ use group::{
 };
 use memmap::Mmap;
 use pairing::Engine;
+use rand_core::SeedableRng;
 use rayon::prelude::*;
 use sha2::Sha256;
 use std::convert::TryFrom;
@@ -105,6 +106,20 @@ impl<E: Engine> PartialEq for VerifierSRS<E> {
     }
 }
 
+impl<E: Engine> PartialEq for ProverSRS<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n
+            && self.g_alpha_powers_table == other.g_alpha_powers_table
+            && self.g_beta_powers_table == other.g_beta_powers_table
+            && self.h_alpha_powers_table == other.h_alpha_powers_table
+            && self.h_beta_powers_table == other.h_beta_powers_table
+            && self.vkey.a == other.vkey.a
+            && self.vkey.b == other.vkey.b
+            && self.wkey.a == other.wkey.a
+            && self.wkey.b == other.wkey.b
+    }
+}
+
 impl<E: Engine> ProverSRS<E> {
     /// Returns true if commitment keys have the exact required length.
     /// It is necessary for the IPP scheme to work that commitment
@@ -303,6 +318,19 @@ where
     }
 }
 
+/// Like [`setup_fake_srs`], but draws the secret exponents from a `ChaChaRng`
+/// seeded with `seed`, so that the same seed always gives the same SRS. This is
+/// only meant for reproducible tests and benchmarks: anyone knowing the seed
+/// knows the exponents.
+pub fn setup_fake_srs_from_seed<E>(seed: [u8; 32], size: usize) -> GenericSRS<E>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+{
+    let mut rng = rand_chacha::ChaChaRng::from_seed(seed);
+    setup_fake_srs(&mut rng, size)
+}
+
 pub(crate) fn structured_generators_scalar_power<G>(
     num: usize,
     g: &G,
@@ -385,9 +413,21 @@ where
 mod test {
     use super::*;
     use blstrs::Bls12;
-    use rand_core::SeedableRng;
     use std::io::Cursor;
 
+    #[test]
+    fn test_setup_fake_srs_from_seed() {
+        let srs = setup_fake_srs_from_seed::<Bls12>([7u8; 32], 8);
+        assert!(srs == setup_fake_srs_from_seed::<Bls12>([7u8; 32], 8));
+        assert!(srs != setup_fake_srs_from_seed::<Bls12>([8u8; 32], 8));
+
+        let (pk, vk) = srs.specialize(4);
+        let (pk2, vk2) = setup_fake_srs_from_seed::<Bls12>([7u8; 32], 8).specialize(4);
+        assert!(pk == pk2);
+        assert!(vk == vk2);
+        assert!(pk != srs.specialize(2).0);
+    }
+
     #[test]
     fn test_srs_invalid_length() {
         let mut rng = rand_chacha::ChaChaRng::seed_from_u64(0u64);
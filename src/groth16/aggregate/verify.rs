@@ -10,11 +10,11 @@ use serde::Serialize;
 
 use super::{
     accumulator::PairingChecks,
-    inner_product,
+    inner_product, padded_len, padded_transcript_include,
     prove::polynomial_evaluation_product_form_from_transcript,
     structured_scalar_power,
     transcript::{Challenge, Transcript},
-    AggregateProof, KZGOpening, VerifierSRS,
+    AggregateProof, KZGOpening, TranscriptInclude, VerifierSRS,
 };
 use crate::groth16::{
     multiscalar::{par_multiscalar, MultiscalarPrecomp, ScalarList},
@@ -30,6 +30,10 @@ use std::time::Instant;
 /// verifier SRS from the aggregation scheme, all the public inputs of the
 /// proofs and the aggregated proof.
 ///
+/// `public_inputs[i]` holds the public inputs of the i-th aggregated proof, so
+/// every proof is checked against its own inputs; when all proofs share the
+/// same statement, the same vector is simply repeated.
+///
 /// WARNING: transcript_include represents everything that should be included in
 /// the transcript from outside the boundary of this function. This is especially
 /// relevant for ALL public inputs of ALL individual proofs. In the regular case,
@@ -48,6 +52,64 @@ pub fn verify_aggregate_proof<E, R>(
     proof: &AggregateProof<E>,
     transcript_include: &[u8],
 ) -> Result<bool, SynthesisError>
+where
+    E: MultiMillerLoop + std::fmt::Debug,
+    E::Fr: Serialize,
+    <E as Engine>::Gt: Compress + Serialize,
+    E::G1: Serialize,
+    E::G1Affine: Serialize,
+    E::G2Affine: Serialize,
+    R: rand_core::RngCore + Send,
+{
+    verify_aggregate_proof_inner(
+        ip_verifier_srs,
+        pvk,
+        rng,
+        public_inputs,
+        proof,
+        TranscriptInclude::Bytes(transcript_include),
+    )
+}
+
+/// Verifies an aggregate proof produced by
+/// [`aggregate_proofs_with_digest`](super::aggregate_proofs_with_digest), which
+/// is bound to `transcript_digest` instead of bytes hashed into the transcript.
+/// Otherwise like [`verify_aggregate_proof`].
+pub fn verify_aggregate_proof_with_digest<E, R>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    rng: R,
+    public_inputs: &[Vec<E::Fr>],
+    proof: &AggregateProof<E>,
+    transcript_digest: [u8; 32],
+) -> Result<bool, SynthesisError>
+where
+    E: MultiMillerLoop + std::fmt::Debug,
+    E::Fr: Serialize,
+    <E as Engine>::Gt: Compress + Serialize,
+    E::G1: Serialize,
+    E::G1Affine: Serialize,
+    E::G2Affine: Serialize,
+    R: rand_core::RngCore + Send,
+{
+    verify_aggregate_proof_inner(
+        ip_verifier_srs,
+        pvk,
+        rng,
+        public_inputs,
+        proof,
+        TranscriptInclude::Digest(&transcript_digest),
+    )
+}
+
+fn verify_aggregate_proof_inner<E, R>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    rng: R,
+    public_inputs: &[Vec<E::Fr>],
+    proof: &AggregateProof<E>,
+    include: TranscriptInclude,
+) -> Result<bool, SynthesisError>
 where
     E: MultiMillerLoop + std::fmt::Debug,
     E::Fr: Serialize,
@@ -71,16 +133,20 @@ where
         ));
     }
 
+    if ip_verifier_srs.n != proof.tmipp.gipa.nproofs as usize {
+        return Err(SynthesisError::MalformedProofs(format!(
+            "verifier SRS is specialized for {} proofs but the aggregate proof contains {}",
+            ip_verifier_srs.n, proof.tmipp.gipa.nproofs
+        )));
+    }
+
     let hcom = Transcript::<E>::new("hcom")
         .write(&proof.com_ab)
         .write(&proof.com_c)
         .into_challenge();
 
     // Random linear combination of proofs
-    let r = Transcript::<E>::new("random-r")
-        .write(&hcom)
-        .write(&transcript_include)
-        .into_challenge();
+    let r = include.random_r(&hcom);
 
     let pairing_checks = PairingChecks::new(rng);
     let pairing_checks_copy = &pairing_checks;
@@ -199,6 +265,57 @@ where
     res
 }
 
+/// Verifies an aggregate proof produced by
+/// [`aggregate_proofs_padded`](super::aggregate_proofs_padded).
+/// `public_inputs` holds the inputs of the real proofs only: the verifier pads
+/// them with copies of the last one, as the prover padded the proofs, so the
+/// padding can only ever prove the last real statement again.
+///
+/// The aggregate proof must contain exactly the padded number of proofs, and
+/// `ip_verifier_srs` must be specialized for it.
+pub fn verify_aggregate_proof_padded<E, R>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    rng: R,
+    public_inputs: &[Vec<E::Fr>],
+    proof: &AggregateProof<E>,
+    transcript_include: &[u8],
+) -> Result<bool, SynthesisError>
+where
+    E: MultiMillerLoop + std::fmt::Debug,
+    E::Fr: Serialize,
+    <E as Engine>::Gt: Compress + Serialize,
+    E::G1: Serialize,
+    E::G1Affine: Serialize,
+    E::G2Affine: Serialize,
+    R: rand_core::RngCore + Send,
+{
+    let last = public_inputs
+        .last()
+        .ok_or_else(|| SynthesisError::MalformedProofs("no public inputs to verify".to_string()))?;
+    let num_padded = padded_len(public_inputs.len());
+    if proof.tmipp.gipa.nproofs as usize != num_padded {
+        return Err(SynthesisError::MalformedProofs(format!(
+            "{} proofs are padded to {} but the aggregate proof contains {}",
+            public_inputs.len(),
+            num_padded,
+            proof.tmipp.gipa.nproofs
+        )));
+    }
+
+    let mut padded = public_inputs.to_vec();
+    padded.resize(num_padded, last.clone());
+
+    verify_aggregate_proof(
+        ip_verifier_srs,
+        pvk,
+        rng,
+        &padded,
+        proof,
+        &padded_transcript_include(public_inputs.len(), transcript_include),
+    )
+}
+
 /// verify_tipp_mipp returns a pairing equation to check the tipp proof.  $r$ is
 /// the randomness used to produce a random linear combination of A and B and
 /// used in the MIPP part with C
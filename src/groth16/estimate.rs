@@ -0,0 +1,140 @@
+use ff::PrimeField;
+use pairing::Engine;
+
+use crate::multiexp::window_size;
+
+/// Number of FFTs over the full domain run by the prover: an inverse FFT and a
+/// coset FFT for each of A, B and C, and a final inverse coset FFT.
+const NUM_FFTS: u64 = 7;
+
+/// Shape of a circuit, as far as the prover's cost is concerned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitStats {
+    pub num_constraints: usize,
+    /// Number of public inputs, not counting the implicit "one" input.
+    pub num_inputs: usize,
+    /// Number of private (auxiliary) variables.
+    pub num_aux: usize,
+}
+
+/// Estimated amount of work, per thread, the prover does for a circuit. These
+/// are operation counts derived from the circuit's shape, not timings, and
+/// assume every variable is used in every query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProvingCostEstimate {
+    /// Size of the evaluation domain used for the FFTs.
+    pub domain_size: usize,
+    /// Field multiplications done by the FFTs.
+    pub fft_ops: u64,
+    /// G1 additions done by the multiexps.
+    pub g1_multiexp_ops: u64,
+    /// G2 additions done by the multiexps.
+    pub g2_multiexp_ops: u64,
+}
+
+/// Estimates the cost of proving a circuit with the given `stats`, with the work
+/// spread over `num_threads` threads.
+pub fn estimate_proving_cost<E: Engine>(
+    stats: &CircuitStats,
+    num_threads: usize,
+) -> ProvingCostEstimate {
+    let num_threads = num_threads.max(1) as u64;
+    let scalar_bits = u64::from(E::Fr::NUM_BITS);
+
+    // The prover adds an `x * 0 = 0` constraint for every input, including "one".
+    let domain_size = (stats.num_constraints + stats.num_inputs + 1).next_power_of_two();
+    let log_d = u64::from(domain_size.trailing_zeros());
+
+    // Each FFT does `log_d` rounds of `domain_size / 2` butterflies, and the
+    // coset shifts and pointwise operations take another pass each.
+    let fft_ops = NUM_FFTS * (domain_size as u64 / 2 * log_d + domain_size as u64);
+
+    let inputs = stats.num_inputs + 1;
+    let g1_multiexp_ops = [
+        domain_size - 1,
+        stats.num_aux,
+        inputs,
+        stats.num_aux,
+        inputs,
+        stats.num_aux,
+    ]
+    .iter()
+    .map(|&n| multiexp_ops(n, scalar_bits))
+    .sum::<u64>();
+    let g2_multiexp_ops =
+        multiexp_ops(inputs, scalar_bits) + multiexp_ops(stats.num_aux, scalar_bits);
+
+    ProvingCostEstimate {
+        domain_size,
+        fft_ops: div_ceil(fft_ops, num_threads),
+        g1_multiexp_ops: div_ceil(g1_multiexp_ops, num_threads),
+        g2_multiexp_ops: div_ceil(g2_multiexp_ops, num_threads),
+    }
+}
+
+/// Group operations done by a multiexp of `n` terms, using the same window size
+/// as `multiexp`: every window adds each term into a bucket and then sums up the
+/// buckets, and the windows are combined with one doubling per bit.
+fn multiexp_ops(n: usize, scalar_bits: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+
+    let c = u64::from(window_size(n));
+    let num_windows = div_ceil(scalar_bits, c);
+
+    num_windows * (n as u64 + (2 << c)) + scalar_bits
+}
+
+fn div_ceil(a: u64, b: u64) -> u64 {
+    (a + b - 1) / b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blstrs::Bls12;
+
+    #[test]
+    fn test_estimate_proving_cost() {
+        let stats = CircuitStats {
+            num_constraints: 1 << 16,
+            num_inputs: 10,
+            num_aux: 1 << 16,
+        };
+        let estimate = estimate_proving_cost::<Bls12>(&stats, 1);
+        assert_eq!(estimate, estimate_proving_cost::<Bls12>(&stats, 1));
+        assert_eq!(estimate.domain_size, 1 << 17);
+
+        // Doubling the circuit roughly doubles the multiexps and FFTs.
+        let doubled = estimate_proving_cost::<Bls12>(
+            &CircuitStats {
+                num_constraints: stats.num_constraints * 2,
+                num_inputs: stats.num_inputs,
+                num_aux: stats.num_aux * 2,
+            },
+            1,
+        );
+        assert_eq!(doubled.domain_size, estimate.domain_size * 2);
+        for &(before, after) in &[
+            (estimate.g1_multiexp_ops, doubled.g1_multiexp_ops),
+            (estimate.g2_multiexp_ops, doubled.g2_multiexp_ops),
+            (estimate.fft_ops, doubled.fft_ops),
+        ] {
+            let ratio = after as f64 / before as f64;
+            assert!(ratio > 1.7 && ratio < 2.2, "ratio {}", ratio);
+        }
+
+        // The work is spread over the threads.
+        let parallel = estimate_proving_cost::<Bls12>(&stats, 8);
+        assert_eq!(parallel.domain_size, estimate.domain_size);
+        assert_eq!(
+            parallel.g1_multiexp_ops,
+            div_ceil(estimate.g1_multiexp_ops, 8)
+        );
+        assert_eq!(
+            estimate_proving_cost::<Bls12>(&stats, 0),
+            estimate_proving_cost::<Bls12>(&stats, 1)
+        );
+    }
+}
@@ -1,6 +1,11 @@
-use super::{create_proof_batch_priority, create_random_proof_batch_priority};
-use super::{ParameterSource, Proof};
+use super::{
+    create_proof_batch_priority, create_proof_batch_priority_reuse,
+    create_proof_batch_with_max_concurrency, create_proof_deterministic,
+    create_random_proof_batch_priority,
+};
+use super::{ParameterSource, Proof, ProverContext};
 use crate::{gpu, Circuit, SynthesisError};
+use ff::Field;
 use pairing::MultiMillerLoop;
 use rand_core::RngCore;
 
@@ -14,9 +19,7 @@ where
     E: gpu::GpuEngine + MultiMillerLoop,
     C: Circuit<E> + Send,
 {
-    let proofs =
-        create_proof_batch_priority::<E, C, P>(vec![circuit], params, vec![r], vec![s], false)?;
-    Ok(proofs.into_iter().next().unwrap())
+    create_proof_deterministic::<E, C, P>(circuit, params, r, s)
 }
 
 pub fn create_random_proof<E, C, R, P: ParameterSource<E>>(
@@ -29,9 +32,10 @@ where
     C: Circuit<E> + Send,
     R: RngCore,
 {
-    let proofs =
-        create_random_proof_batch_priority::<E, C, R, P>(vec![circuit], params, rng, false)?;
-    Ok(proofs.into_iter().next().unwrap())
+    let r = E::Fr::random(&mut *rng);
+    let s = E::Fr::random(&mut *rng);
+
+    create_proof_deterministic::<E, C, P>(circuit, params, r, s)
 }
 
 pub fn create_proof_batch<E, C, P: ParameterSource<E>>(
@@ -60,6 +64,61 @@ where
     create_random_proof_batch_priority::<E, C, R, P>(circuits, params, rng, false)
 }
 
+/// Like [`create_random_proof_batch`], but proves at most `max_concurrency`
+/// circuits at once, see [`create_proof_batch_with_max_concurrency`]. A
+/// `max_concurrency` of 0 proves the whole batch at once.
+pub fn create_random_proof_batch_with_max_concurrency<E, C, R, P>(
+    circuits: Vec<C>,
+    params: P,
+    rng: &mut R,
+    max_concurrency: usize,
+) -> Result<Vec<Proof<E>>, SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    C: Circuit<E> + Send,
+    R: RngCore,
+    P: ParameterSource<E> + Clone,
+{
+    let r_s = (0..circuits.len())
+        .map(|_| E::Fr::random(&mut *rng))
+        .collect();
+    let s_s = (0..circuits.len())
+        .map(|_| E::Fr::random(&mut *rng))
+        .collect();
+
+    create_proof_batch_with_max_concurrency::<E, C, P>(
+        circuits,
+        params,
+        r_s,
+        s_s,
+        false,
+        max_concurrency,
+    )
+}
+
+/// Like [`create_random_proof_batch`], but reuses the per-proof buffers kept in `ctx`
+/// across calls.
+pub fn create_random_proof_batch_reuse<E, C, R, P: ParameterSource<E>>(
+    circuits: Vec<C>,
+    params: P,
+    rng: &mut R,
+    ctx: &mut ProverContext<E>,
+) -> Result<Vec<Proof<E>>, SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    C: Circuit<E> + Send,
+    R: RngCore,
+{
+    let r_s = (0..circuits.len())
+        .map(|_| E::Fr::random(&mut *rng))
+        .collect();
+    let s_s = (0..circuits.len())
+        .map(|_| E::Fr::random(&mut *rng))
+        .collect();
+
+    create_proof_batch_priority_reuse::<E, C, P>(circuits, params, r_s, s_s, false, ctx)
+}
+
 pub fn create_proof_in_priority<E, C, P: ParameterSource<E>>(
     circuit: C,
     params: P,
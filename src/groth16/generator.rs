@@ -1,4 +1,4 @@
-use std::ops::{AddAssign, Mul, MulAssign};
+use std::ops::{AddAssign, MulAssign};
 
 use std::sync::Arc;
 
@@ -15,7 +15,9 @@ use super::{Parameters, VerifyingKey};
 use crate::domain::EvaluationDomain;
 use crate::gpu;
 use crate::multicore::Worker;
-use crate::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use crate::{
+    Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, SynthesisMode, Variable,
+};
 
 /// Generates a random common reference string for
 /// a circuit.
@@ -179,6 +181,10 @@ impl<E: Engine> ConstraintSystem<E> for KeypairAssembly<E> {
     fn get_root(&mut self) -> &mut Self::Root {
         self
     }
+
+    fn mode(&self) -> SynthesisMode {
+        SynthesisMode::Setup
+    }
 }
 
 /// Create parameters for a circuit, given some toxic waste.
@@ -471,16 +477,20 @@ where
         }
     }
 
-    let g1 = g1.to_affine();
-    let g2 = g2.to_affine();
+    // The queries above are normalized in parallel chunks, normalize the
+    // remaining points of the verifying key together as well.
+    let mut vk_g1 = [<E::G1 as PrimeCurve>::Affine::identity(); 3];
+    E::G1::batch_normalize(&[g1 * alpha, g1 * beta, g1 * delta], &mut vk_g1);
+    let mut vk_g2 = [<E::G2 as PrimeCurve>::Affine::identity(); 3];
+    E::G2::batch_normalize(&[g2 * beta, g2 * gamma, g2 * delta], &mut vk_g2);
 
     let vk = VerifyingKey::<E> {
-        alpha_g1: g1.mul(alpha).to_affine(),
-        beta_g1: g1.mul(beta).to_affine(),
-        beta_g2: g2.mul(beta).to_affine(),
-        gamma_g2: g2.mul(gamma).to_affine(),
-        delta_g1: g1.mul(delta).to_affine(),
-        delta_g2: g2.mul(delta).to_affine(),
+        alpha_g1: vk_g1[0],
+        beta_g1: vk_g1[1],
+        beta_g2: vk_g2[0],
+        gamma_g2: vk_g2[1],
+        delta_g1: vk_g1[2],
+        delta_g2: vk_g2[2],
         ic: ic_affine,
     };
 
@@ -8,17 +8,20 @@
 mod tests;
 
 pub mod aggregate;
+mod estimate;
 mod ext;
 mod generator;
 mod mapped_params;
 mod params;
 mod proof;
 mod prover;
+pub mod test_utils;
 mod verifier;
 mod verifying_key;
 
 mod multiscalar;
 
+pub use self::estimate::*;
 pub use self::ext::*;
 pub use self::generator::*;
 pub use self::mapped_params::*;
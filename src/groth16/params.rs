@@ -6,15 +6,32 @@ use crate::SynthesisError;
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use memmap::{Mmap, MmapOptions};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp;
 use std::fs::File;
-use std::io::{self, Read, Write};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::mem;
 use std::ops::Range;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use super::proof::{
+    deserialize_bytes, ensure_consumed, read_compressed_points, write_compressed_points,
+};
 use super::{MappedParameters, VerifyingKey};
 
+/// Number of points `Parameters::read_streaming` decodes at once.
+const STREAMING_CHUNK_SIZE: usize = 1024;
+
+/// Tags of the parts of the parameters in the manifest of a shard written by
+/// `Parameters::write_sharded`, in the order of `write`.
+const SHARD_VK: u8 = 0;
+const SHARD_H: u8 = 1;
+const SHARD_L: u8 = 2;
+const SHARD_A: u8 = 3;
+const SHARD_B_G1: u8 = 4;
+const SHARD_B_G2: u8 = 5;
+
 #[derive(Clone)]
 pub struct Parameters<E>
 where
@@ -57,6 +74,35 @@ where
     }
 }
 
+/// Serialized as the bytes written by `write_compressed`, about half the size
+/// of those written by `write`.
+impl<E> Serialize for Parameters<E>
+where
+    E: MultiMillerLoop,
+{
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut v = vec![];
+        self.write_compressed(&mut v).map_err(ser::Error::custom)?;
+        s.serialize_bytes(&v)
+    }
+}
+
+/// Equivalent to `read_compressed`, so points which are not in the prime order
+/// subgroup are rejected.
+impl<'de, E> Deserialize<'de> for Parameters<E>
+where
+    E: MultiMillerLoop,
+{
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let bytes = deserialize_bytes(d)?;
+        let mut reader = &bytes[..];
+        let params = Parameters::read_compressed(&mut reader).map_err(de::Error::custom)?;
+        ensure_consumed(reader)?;
+
+        Ok(params)
+    }
+}
+
 impl<E> Parameters<E>
 where
     E: MultiMillerLoop,
@@ -92,6 +138,31 @@ where
         Ok(())
     }
 
+    /// Writes the parameters like `write`, but with compressed points. This is
+    /// half the size, but slower to read back since square roots are needed.
+    pub fn write_compressed<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.vk.write_compressed(&mut writer)?;
+        write_compressed_points(&mut writer, &self.h[..])?;
+        write_compressed_points(&mut writer, &self.l[..])?;
+        write_compressed_points(&mut writer, &self.a[..])?;
+        write_compressed_points(&mut writer, &self.b_g1[..])?;
+        write_compressed_points(&mut writer, &self.b_g2[..])
+    }
+
+    /// Reads parameters written by `write_compressed`, decompressing the points
+    /// in parallel. Points which are not in the prime order subgroup and points
+    /// at infinity are rejected.
+    pub fn read_compressed<R: Read>(mut reader: R) -> io::Result<Self> {
+        Ok(Parameters {
+            vk: VerifyingKey::read_compressed(&mut reader)?,
+            h: Arc::new(read_compressed_points(&mut reader)?),
+            l: Arc::new(read_compressed_points(&mut reader)?),
+            a: Arc::new(read_compressed_points(&mut reader)?),
+            b_g1: Arc::new(read_compressed_points(&mut reader)?),
+            b_g2: Arc::new(read_compressed_points(&mut reader)?),
+        })
+    }
+
     // Quickly iterates through the parameter file, recording all
     // parameter offsets and caches the verifying key (vk) for quick
     // access via reference.
@@ -393,6 +464,309 @@ where
             b_g2: Arc::new(b_g2),
         })
     }
+
+    /// Reads parameters in the format of `write`, like `read`, while bounding
+    /// the memory used to do so: every point vector is allocated once with its
+    /// exact length, and filled by decoding `STREAMING_CHUNK_SIZE` points at a
+    /// time. Lengths are checked against the remaining size of the stream before
+    /// anything is allocated.
+    ///
+    /// To avoid loading the parameters into memory at all, map the file with
+    /// `build_mapped_parameters` instead.
+    pub fn read_streaming<R: Read + Seek>(mut reader: R, checked: bool) -> io::Result<Self> {
+        let start = reader.seek(SeekFrom::Current(0))?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let vk = VerifyingKey::<E>::read(&mut reader)?;
+
+        let h = read_points_streaming::<E::G1Affine, _>(&mut reader, end, checked)?;
+        let l = read_points_streaming::<E::G1Affine, _>(&mut reader, end, checked)?;
+        let a = read_points_streaming::<E::G1Affine, _>(&mut reader, end, checked)?;
+        let b_g1 = read_points_streaming::<E::G1Affine, _>(&mut reader, end, checked)?;
+        let b_g2 = read_points_streaming::<E::G2Affine, _>(&mut reader, end, checked)?;
+
+        Ok(Parameters {
+            vk,
+            h: Arc::new(h),
+            l: Arc::new(l),
+            a: Arc::new(a),
+            b_g1: Arc::new(b_g1),
+            b_g2: Arc::new(b_g2),
+        })
+    }
+
+    /// Writes the parameters split across the files at `paths`, which are
+    /// created or truncated. Every point vector is split into `paths.len()`
+    /// consecutive ranges of about the same size, one per shard, and the
+    /// verifying key is written to the first shard.
+    ///
+    /// Each shard starts with a manifest: the number of shards and the number
+    /// of entries, followed by the tag, offset, number of points and total
+    /// length of the whole vector of each entry. The entries follow in the
+    /// same order, the verifying key as in `write`, and the points as a
+    /// length-prefixed vector like in `write`. See `read_sharded`.
+    pub fn write_sharded(&self, paths: &[&Path]) -> io::Result<()> {
+        if paths.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "at least one shard is needed",
+            ));
+        }
+
+        let range = |len: usize, shard: usize| -> Range<usize> {
+            (len * shard / paths.len())..(len * (shard + 1) / paths.len())
+        };
+        let write_points = |writer: &mut BufWriter<File>, points: &[E::G1Affine]| {
+            writer.write_u32::<BigEndian>(points.len() as u32)?;
+            for g in points {
+                writer.write_all(g.to_uncompressed().as_ref())?;
+            }
+            Ok::<_, io::Error>(())
+        };
+
+        for (shard, path) in paths.iter().enumerate() {
+            let g1 = [
+                (SHARD_H, &self.h),
+                (SHARD_L, &self.l),
+                (SHARD_A, &self.a),
+                (SHARD_B_G1, &self.b_g1),
+            ];
+            let b_g2 = range(self.b_g2.len(), shard);
+
+            let mut manifest = vec![];
+            if shard == 0 {
+                manifest.push((SHARD_VK, 0, 0, 0));
+            }
+            for (tag, points) in &g1 {
+                let range = range(points.len(), shard);
+                manifest.push((*tag, range.start, range.len(), points.len()));
+            }
+            manifest.push((SHARD_B_G2, b_g2.start, b_g2.len(), self.b_g2.len()));
+
+            let mut writer = BufWriter::new(File::create(path)?);
+            writer.write_u32::<BigEndian>(paths.len() as u32)?;
+            writer.write_u32::<BigEndian>(manifest.len() as u32)?;
+            for (tag, start, len, total) in &manifest {
+                writer.write_u8(*tag)?;
+                writer.write_u32::<BigEndian>(*start as u32)?;
+                writer.write_u32::<BigEndian>(*len as u32)?;
+                writer.write_u32::<BigEndian>(*total as u32)?;
+            }
+
+            if shard == 0 {
+                self.vk.write(&mut writer)?;
+            }
+            for (_, points) in &g1 {
+                write_points(&mut writer, &points[range(points.len(), shard)])?;
+            }
+            writer.write_u32::<BigEndian>(b_g2.len() as u32)?;
+            for g in &self.b_g2[b_g2] {
+                writer.write_all(g.to_uncompressed().as_ref())?;
+            }
+
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads parameters written by `write_sharded`. The shards can be given in
+    /// any order, and split the point vectors differently than `write_sharded`
+    /// does, as long as all the shards listed in the manifests are given, every
+    /// point is held by exactly one shard and the verifying key by exactly one
+    /// shard. Points are checked to be on the curve and in the prime order
+    /// subgroup.
+    ///
+    /// Fails with `MalformedShards` if the manifests are inconsistent with each
+    /// other, with the number of shards given or with the contents of the
+    /// shards.
+    pub fn read_sharded(paths: &[&Path]) -> Result<Self, SynthesisError> {
+        let mut vk = None;
+        let mut g1: [Vec<(usize, Vec<E::G1Affine>)>; 4] = Default::default();
+        let mut b_g2 = vec![];
+        // The total length of each point vector, indexed by tag.
+        let mut totals = [None; SHARD_B_G2 as usize + 1];
+
+        for path in paths {
+            let malformed = |msg: String| {
+                SynthesisError::MalformedShards(format!("{}: {}", path.display(), msg))
+            };
+
+            let mut reader = BufReader::new(File::open(path)?);
+            let end = reader.seek(SeekFrom::End(0))?;
+            reader.seek(SeekFrom::Start(0))?;
+
+            let shards = reader.read_u32::<BigEndian>()? as usize;
+            if shards != paths.len() {
+                return Err(malformed(format!(
+                    "manifest lists {} shards, {} given",
+                    shards,
+                    paths.len()
+                )));
+            }
+
+            let entries = reader.read_u32::<BigEndian>()?;
+            let mut manifest = vec![];
+            for _ in 0..entries {
+                let tag = reader.read_u8()?;
+                let start = reader.read_u32::<BigEndian>()? as usize;
+                let len = reader.read_u32::<BigEndian>()? as usize;
+                let total = reader.read_u32::<BigEndian>()? as usize;
+                manifest.push((tag, start, len, total));
+            }
+
+            for (tag, start, len, total) in manifest {
+                if tag != SHARD_VK {
+                    match totals.get_mut(usize::from(tag)) {
+                        Some(Some(expected)) if *expected != total => {
+                            return Err(malformed(format!(
+                                "manifest lists {} points in total for entry {}, {} elsewhere",
+                                total, tag, expected
+                            )));
+                        }
+                        Some(expected) => *expected = Some(total),
+                        None => return Err(malformed(format!("unknown entry {}", tag))),
+                    }
+                }
+
+                let read_len = match tag {
+                    SHARD_VK => {
+                        if vk.is_some() {
+                            return Err(malformed("duplicate verifying key".to_string()));
+                        }
+                        vk = Some(VerifyingKey::<E>::read(&mut reader)?);
+                        continue;
+                    }
+                    SHARD_H | SHARD_L | SHARD_A | SHARD_B_G1 => {
+                        let points =
+                            read_points_streaming::<E::G1Affine, _>(&mut reader, end, true)?;
+                        let read_len = points.len();
+                        g1[usize::from(tag - SHARD_H)].push((start, points));
+                        read_len
+                    }
+                    SHARD_B_G2 => {
+                        let points =
+                            read_points_streaming::<E::G2Affine, _>(&mut reader, end, true)?;
+                        let read_len = points.len();
+                        b_g2.push((start, points));
+                        read_len
+                    }
+                    _ => return Err(malformed(format!("unknown entry {}", tag))),
+                };
+                if read_len != len {
+                    return Err(malformed(format!(
+                        "manifest lists {} points for entry {}, found {}",
+                        len, tag, read_len
+                    )));
+                }
+            }
+        }
+
+        let vk = vk.ok_or_else(|| {
+            SynthesisError::MalformedShards("no shard holds the verifying key".to_string())
+        })?;
+        let [h, l, a, b_g1] = g1;
+
+        Ok(Parameters {
+            vk,
+            h: Arc::new(assemble_shards(SHARD_H, h, &totals)?),
+            l: Arc::new(assemble_shards(SHARD_L, l, &totals)?),
+            a: Arc::new(assemble_shards(SHARD_A, a, &totals)?),
+            b_g1: Arc::new(assemble_shards(SHARD_B_G1, b_g1, &totals)?),
+            b_g2: Arc::new(assemble_shards(SHARD_B_G2, b_g2, &totals)?),
+        })
+    }
+}
+
+/// Concatenates the ranges of a point vector read from different shards, which
+/// must cover it without gaps or overlaps, from the start up to the total
+/// length in `totals` listed by the manifests.
+fn assemble_shards<G>(
+    tag: u8,
+    mut ranges: Vec<(usize, Vec<G>)>,
+    totals: &[Option<usize>],
+) -> Result<Vec<G>, SynthesisError> {
+    let total = totals[usize::from(tag)]
+        .ok_or_else(|| SynthesisError::MalformedShards(format!("no shard holds entry {}", tag)))?;
+    ranges.sort_by_key(|(start, _)| *start);
+
+    let mut points = Vec::with_capacity(ranges.iter().map(|(_, range)| range.len()).sum());
+    for (start, range) in ranges {
+        if start != points.len() {
+            return Err(SynthesisError::MalformedShards(format!(
+                "entry {} continues at point {} instead of {}",
+                tag,
+                start,
+                points.len()
+            )));
+        }
+        points.extend(range);
+    }
+    if points.len() != total {
+        return Err(SynthesisError::MalformedShards(format!(
+            "entry {} ends at point {} instead of {}",
+            tag,
+            points.len(),
+            total
+        )));
+    }
+
+    Ok(points)
+}
+
+/// Reads a length-prefixed vector of uncompressed points, ending at or before
+/// the stream position `end`.
+fn read_points_streaming<G, R>(reader: &mut R, end: u64, checked: bool) -> io::Result<Vec<G>>
+where
+    G: PrimeCurveAffine + UncompressedEncoding,
+    R: Read + Seek,
+{
+    let len = reader.read_u32::<BigEndian>()? as usize;
+    let point_len = mem::size_of::<G::Uncompressed>();
+
+    let position = reader.seek(SeekFrom::Current(0))?;
+    if (len as u64) * (point_len as u64) > end.saturating_sub(position) {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "not enough points left in the stream",
+        ));
+    }
+
+    let mut points = Vec::with_capacity(len);
+    let mut buf = vec![0u8; cmp::min(len, STREAMING_CHUNK_SIZE) * point_len];
+
+    while points.len() < len {
+        let chunk_len = cmp::min(len - points.len(), STREAMING_CHUNK_SIZE);
+        let chunk = &mut buf[..chunk_len * point_len];
+        reader.read_exact(chunk)?;
+
+        for bytes in chunk.chunks(point_len) {
+            let mut repr = G::Uncompressed::default();
+            repr.as_mut().copy_from_slice(bytes);
+
+            let affine: G = {
+                let affine_opt = if checked {
+                    G::from_uncompressed(&repr)
+                } else {
+                    G::from_uncompressed_unchecked(&repr)
+                };
+                Option::from(affine_opt)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not on curve"))
+            }?;
+
+            if affine.is_identity().into() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "point at infinity",
+                ));
+            }
+
+            points.push(affine);
+        }
+    }
+
+    Ok(points)
 }
 
 pub trait ParameterSource<E>: Send + Sync
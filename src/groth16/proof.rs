@@ -1,14 +1,16 @@
 use std::fmt;
 use std::io::{self, Read, Write};
-use std::marker::PhantomData;
 
-use group::{prime::PrimeCurveAffine, GroupEncoding};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use group::{prime::PrimeCurveAffine, GroupEncoding, UncompressedEncoding};
 use pairing::Engine;
 use rayon::prelude::*;
 
-use serde::de::Visitor;
+use serde::de::{self, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::SynthesisError;
+
 #[derive(Clone, Debug)]
 pub struct Proof<E: Engine> {
     pub a: E::G1Affine,
@@ -24,29 +26,57 @@ impl<E: Engine> Serialize for Proof<E> {
     }
 }
 
-fn deserialize_proof<'de, D: Deserializer<'de>, E: Engine>(d: D) -> Result<Proof<E>, D::Error> {
-    struct BytesVisitor<E> {
-        _ph: PhantomData<E>,
-    }
+/// Deserializes a byte string, as written by `Serializer::serialize_bytes`.
+/// Formats without native byte strings (e.g. JSON) write a sequence of bytes.
+pub(crate) fn deserialize_bytes<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+    struct BytesVisitor;
 
-    impl<'de, E: Engine> Visitor<'de> for BytesVisitor<E> {
-        type Value = Proof<E>;
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
 
         fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "a proof")
+            write!(f, "a byte string")
+        }
+
+        #[inline]
+        fn visit_bytes<F: de::Error>(self, v: &[u8]) -> Result<Self::Value, F> {
+            Ok(v.to_vec())
         }
+
         #[inline]
-        fn visit_bytes<F: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, F> {
-            let p = Proof::read(v).unwrap();
-            Ok(p)
+        fn visit_byte_buf<F: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, F> {
+            Ok(v)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut v = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(b) = seq.next_element()? {
+                v.push(b);
+            }
+            Ok(v)
         }
     }
-    d.deserialize_bytes(BytesVisitor { _ph: PhantomData })
+
+    d.deserialize_bytes(BytesVisitor)
+}
+
+/// Fails if `rest`, the input left over after decoding a value, is not empty.
+pub(crate) fn ensure_consumed<Err: de::Error>(rest: &[u8]) -> Result<(), Err> {
+    if !rest.is_empty() {
+        return Err(Err::custom(format!("{} trailing bytes", rest.len())));
+    }
+
+    Ok(())
 }
 
 impl<'de, E: Engine> Deserialize<'de> for Proof<E> {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
-        deserialize_proof(d)
+        let bytes = deserialize_bytes(d)?;
+        let mut reader = &bytes[..];
+        let proof = Proof::read(&mut reader).map_err(de::Error::custom)?;
+        ensure_consumed(reader)?;
+
+        Ok(proof)
     }
 }
 
@@ -65,10 +95,52 @@ impl<E: Engine> Proof<E> {
         Ok(())
     }
 
-    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+    /// Assembles a proof from the compressed encodings of its points, e.g. as
+    /// produced by another implementation. Each point must be a canonical
+    /// encoding of a point in the prime order subgroup, other than the identity.
+    pub fn from_parts(a: &[u8], b: &[u8], c: &[u8]) -> Result<Self, SynthesisError> {
+        Ok(Proof {
+            a: decode_point(a)?,
+            b: decode_point(b)?,
+            c: decode_point(c)?,
+        })
+    }
+
+    /// Returns the hex encoding of the bytes written by `write`.
+    pub fn to_hex(&self) -> String {
+        let mut bytes = Vec::with_capacity(Self::size());
+        self.write(&mut bytes)
+            .expect("writing to a vec never fails");
+        hex::encode(bytes)
+    }
+
+    /// Reads a proof from the hex encoding of the bytes written by `write`.
+    pub fn from_hex(s: &str) -> Result<Self, SynthesisError> {
+        let bytes = decode_hex(s)?;
+        if bytes.len() != Self::size() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid proof length").into());
+        }
+
+        Ok(Self::read(&bytes[..])?)
+    }
+
+    /// Reads a proof, checking that its points are valid encodings of points in
+    /// the prime order subgroup, other than the identity.
+    pub fn read<R: Read>(reader: R) -> io::Result<Self> {
+        Self::read_inner(reader, true)
+    }
+
+    /// Reads a proof without checking that its points are in the prime order
+    /// subgroup. Only use this for proofs which are known to be valid, e.g.
+    /// because they were written by this process.
+    pub fn read_unchecked<R: Read>(reader: R) -> io::Result<Self> {
+        Self::read_inner(reader, false)
+    }
+
+    fn read_inner<R: Read>(mut reader: R, checked: bool) -> io::Result<Self> {
         let mut bytes = vec![0u8; Self::size()];
         reader.read_exact(&mut bytes)?;
-        let proof = Self::read_many(&bytes, 1)?.pop().unwrap();
+        let proof = Self::read_many_inner(&bytes, 1, checked)?.pop().unwrap();
 
         Ok(proof)
     }
@@ -83,7 +155,104 @@ impl<E: Engine> Proof<E> {
         2 * g1_compressed_size + g2_compressed_size
     }
 
+    /// Writes the proof with uncompressed points. This is twice the size of
+    /// `write`, but is faster to read back since no square roots are needed.
+    pub fn write_uncompressed<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(self.a.to_uncompressed().as_ref())?;
+        writer.write_all(self.b.to_uncompressed().as_ref())?;
+        writer.write_all(self.c.to_uncompressed().as_ref())?;
+
+        Ok(())
+    }
+
+    /// Reads a proof written by `write_uncompressed`, with the same checks as
+    /// `read`.
+    pub fn read_uncompressed<R: Read>(reader: R) -> io::Result<Self> {
+        Self::read_uncompressed_inner(reader, true)
+    }
+
+    /// Reads a proof written by `write_uncompressed`, with the same checks as
+    /// `read_unchecked`.
+    pub fn read_uncompressed_unchecked<R: Read>(reader: R) -> io::Result<Self> {
+        Self::read_uncompressed_inner(reader, false)
+    }
+
+    fn read_uncompressed_inner<R: Read>(mut reader: R, checked: bool) -> io::Result<Self> {
+        Ok(Proof {
+            a: read_uncompressed_point(&mut reader, checked)?,
+            b: read_uncompressed_point(&mut reader, checked)?,
+            c: read_uncompressed_point(&mut reader, checked)?,
+        })
+    }
+
+    /// Size of a proof written by `write_uncompressed`.
+    pub fn uncompressed_size() -> usize {
+        let g1_uncompressed_size = <E::G1Affine as UncompressedEncoding>::Uncompressed::default()
+            .as_ref()
+            .len();
+        let g2_uncompressed_size = <E::G2Affine as UncompressedEncoding>::Uncompressed::default()
+            .as_ref()
+            .len();
+        2 * g1_uncompressed_size + g2_uncompressed_size
+    }
+
+    /// Reads `num_proofs` consecutive proofs, with the same checks as `read`.
     pub fn read_many(proof_bytes: &[u8], num_proofs: usize) -> io::Result<Vec<Self>> {
+        Self::read_many_inner(proof_bytes, num_proofs, true)
+    }
+
+    /// Reads `num_proofs` consecutive proofs, with the same checks as
+    /// `read_unchecked`.
+    pub fn read_many_unchecked(proof_bytes: &[u8], num_proofs: usize) -> io::Result<Vec<Self>> {
+        Self::read_many_inner(proof_bytes, num_proofs, false)
+    }
+
+    /// Reads `num_proofs` consecutive proofs written by `write_uncompressed`,
+    /// with the same checks as `read_uncompressed`. Fails unless `proof_bytes`
+    /// holds exactly `num_proofs` proofs.
+    pub fn read_many_uncompressed(proof_bytes: &[u8], num_proofs: usize) -> io::Result<Vec<Self>> {
+        Self::read_many_uncompressed_inner(proof_bytes, num_proofs, true)
+    }
+
+    /// Reads `num_proofs` consecutive proofs written by `write_uncompressed`,
+    /// with the same checks as `read_uncompressed_unchecked`.
+    pub fn read_many_uncompressed_unchecked(
+        proof_bytes: &[u8],
+        num_proofs: usize,
+    ) -> io::Result<Vec<Self>> {
+        Self::read_many_uncompressed_inner(proof_bytes, num_proofs, false)
+    }
+
+    fn read_many_uncompressed_inner(
+        proof_bytes: &[u8],
+        num_proofs: usize,
+        checked: bool,
+    ) -> io::Result<Vec<Self>> {
+        if proof_bytes.len() != num_proofs * Self::uncompressed_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected {} bytes for {} uncompressed proofs, got {}",
+                    num_proofs * Self::uncompressed_size(),
+                    num_proofs,
+                    proof_bytes.len()
+                ),
+            ));
+        }
+
+        // No square roots are needed, so the subgroup checks dominate and
+        // whole proofs are split across threads.
+        proof_bytes
+            .par_chunks(Self::uncompressed_size())
+            .map(|bytes| Self::read_uncompressed_inner(bytes, checked))
+            .collect()
+    }
+
+    fn read_many_inner(
+        proof_bytes: &[u8],
+        num_proofs: usize,
+        checked: bool,
+    ) -> io::Result<Vec<Self>> {
         debug_assert_eq!(proof_bytes.len(), num_proofs * Self::size());
 
         // Decompress and group check in parallel
@@ -111,65 +280,18 @@ impl<E: Engine> Proof<E> {
                 let offset = Self::size() * p;
                 match c {
                     0 => {
-                        let mut g2_repr = <E::G2Affine as GroupEncoding>::Repr::default();
                         let start = offset + g1_len;
-                        let end = start + g2_len;
-                        g2_repr.as_mut().copy_from_slice(&proof_bytes[start..end]);
-
-                        let b: E::G2Affine = {
-                            let opt = E::G2Affine::from_bytes(&g2_repr);
-                            Option::from(opt).ok_or_else(|| {
-                                io::Error::new(io::ErrorKind::InvalidData, "not on curve")
-                            })
-                        }?;
-                        if b.is_identity().into() {
-                            return Err(io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                "point at infinity",
-                            ));
-                        }
+                        let b = read_point(&proof_bytes[start..start + g2_len], checked)?;
                         Ok(ProofPart::<E>::B(b))
                     }
                     1 => {
-                        let mut g1_repr = <E::G1Affine as GroupEncoding>::Repr::default();
                         let start = offset;
-                        let end = start + g1_len;
-                        g1_repr.as_mut().copy_from_slice(&proof_bytes[start..end]);
-                        let a: E::G1Affine = {
-                            let opt = E::G1Affine::from_bytes(&g1_repr);
-                            Option::from(opt).ok_or_else(|| {
-                                io::Error::new(io::ErrorKind::InvalidData, "not on curve")
-                            })
-                        }?;
-
-                        if a.is_identity().into() {
-                            return Err(io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                "point at infinity",
-                            ));
-                        }
+                        let a = read_point(&proof_bytes[start..start + g1_len], checked)?;
                         Ok(ProofPart::<E>::A(a))
                     }
                     2 => {
-                        let mut g1_repr = <E::G1Affine as GroupEncoding>::Repr::default();
                         let start = offset + g1_len + g2_len;
-                        let end = start + g1_len;
-
-                        g1_repr.as_mut().copy_from_slice(&proof_bytes[start..end]);
-                        let c: E::G1Affine = {
-                            let opt = E::G1Affine::from_bytes(&g1_repr);
-                            Option::from(opt).ok_or_else(|| {
-                                io::Error::new(io::ErrorKind::InvalidData, "not on curve")
-                            })
-                        }?;
-
-                        if c.is_identity().into() {
-                            return Err(io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                "point at infinity",
-                            ));
-                        }
-
+                        let c = read_point(&proof_bytes[start..start + g1_len], checked)?;
                         Ok(ProofPart::<E>::C(c))
                     }
                     _ => unreachable!("invalid math {}", c),
@@ -220,6 +342,140 @@ impl<E: Engine> Proof<E> {
     }
 }
 
+/// Decodes a compressed point, rejecting the identity. If `checked` is set,
+/// points outside of the prime order subgroup are rejected as well.
+fn read_point<G: PrimeCurveAffine + GroupEncoding>(bytes: &[u8], checked: bool) -> io::Result<G> {
+    let mut repr = G::Repr::default();
+    repr.as_mut().copy_from_slice(bytes);
+
+    let point: G = if checked {
+        Option::from(G::from_bytes(&repr)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not on curve or not in subgroup",
+            )
+        })?
+    } else {
+        Option::from(G::from_bytes_unchecked(&repr))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not on curve"))?
+    };
+
+    if point.is_identity().into() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "point at infinity",
+        ));
+    }
+
+    Ok(point)
+}
+
+/// Reads an uncompressed point, with the same checks as `read_point`.
+fn read_uncompressed_point<G, R>(mut reader: R, checked: bool) -> io::Result<G>
+where
+    G: PrimeCurveAffine + UncompressedEncoding,
+    R: Read,
+{
+    let mut repr = G::Uncompressed::default();
+    reader.read_exact(repr.as_mut())?;
+
+    let point: G = if checked {
+        Option::from(G::from_uncompressed(&repr)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not on curve or not in subgroup",
+            )
+        })?
+    } else {
+        Option::from(G::from_uncompressed_unchecked(&repr))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not on curve"))?
+    };
+
+    if point.is_identity().into() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "point at infinity",
+        ));
+    }
+
+    Ok(point)
+}
+
+/// Reads a compressed point, rejecting points which are not in the prime order
+/// subgroup and the identity.
+pub(crate) fn read_compressed_point<G, R>(mut reader: R) -> io::Result<G>
+where
+    G: PrimeCurveAffine + GroupEncoding,
+    R: Read,
+{
+    let mut repr = G::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+
+    read_point(repr.as_ref(), true)
+}
+
+/// Writes the number of `points`, followed by their compressed encodings.
+pub(crate) fn write_compressed_points<G, W>(mut writer: W, points: &[G]) -> io::Result<()>
+where
+    G: GroupEncoding,
+    W: Write,
+{
+    writer.write_u32::<BigEndian>(points.len() as u32)?;
+    for point in points {
+        writer.write_all(point.to_bytes().as_ref())?;
+    }
+
+    Ok(())
+}
+
+/// Reads points written by `write_compressed_points`, decompressing them in
+/// parallel, with the same checks as `read_compressed_point`.
+pub(crate) fn read_compressed_points<G, R>(mut reader: R) -> io::Result<Vec<G>>
+where
+    G: PrimeCurveAffine + GroupEncoding,
+    R: Read,
+{
+    let len = reader.read_u32::<BigEndian>()? as usize;
+    let point_len = G::Repr::default().as_ref().len();
+
+    // Grows with the input rather than trusting `len` for the allocation.
+    let mut bytes = vec![];
+    (&mut reader)
+        .take((len * point_len) as u64)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() != len * point_len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "not enough points left in the stream",
+        ));
+    }
+
+    bytes
+        .par_chunks(point_len)
+        .map(|bytes| read_point(bytes, true))
+        .collect()
+}
+
+pub(crate) fn decode_hex(s: &str) -> Result<Vec<u8>, SynthesisError> {
+    hex::decode(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+}
+
+fn decode_point<G: PrimeCurveAffine + GroupEncoding>(bytes: &[u8]) -> Result<G, SynthesisError> {
+    let mut repr = G::Repr::default();
+    if bytes.len() != repr.as_ref().len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid point length").into());
+    }
+    repr.as_mut().copy_from_slice(bytes);
+
+    let point: G = Option::from(G::from_bytes(&repr))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not on curve"))?;
+    if point.is_identity().into() {
+        return Err(SynthesisError::UnexpectedIdentity);
+    }
+
+    Ok(point)
+}
+
 #[cfg(test)]
 mod test_with_bls12_381 {
     use std::ops::MulAssign;
@@ -330,6 +586,182 @@ mod test_with_bls12_381 {
             let serialized_proof = serialize(&proof).unwrap();
             let de_proof: Proof<Bls12> = deserialize(&serialized_proof).unwrap();
             assert_eq!(de_proof, proof);
+
+            // Assemble from the individual points
+            let de_proof = Proof::<Bls12>::from_parts(
+                proof.a.to_bytes().as_ref(),
+                proof.b.to_bytes().as_ref(),
+                proof.c.to_bytes().as_ref(),
+            )
+            .unwrap();
+            assert_eq!(de_proof, proof);
+            assert!(verify_proof(&pvk, &de_proof, &[c]).unwrap());
         }
     }
+
+    #[test]
+    fn uncompressed() {
+        use group::{Curve, Group};
+
+        let rng = &mut thread_rng();
+        let proof = Proof::<Bls12> {
+            a: <Bls12 as Engine>::G1::random(&mut *rng).to_affine(),
+            b: <Bls12 as Engine>::G2::random(&mut *rng).to_affine(),
+            c: <Bls12 as Engine>::G1::random(&mut *rng).to_affine(),
+        };
+        assert_eq!(
+            Proof::<Bls12>::uncompressed_size(),
+            2 * Proof::<Bls12>::size()
+        );
+
+        let mut v = vec![];
+        proof.write_uncompressed(&mut v).unwrap();
+        assert_eq!(v.len(), Proof::<Bls12>::uncompressed_size());
+        assert_eq!(Proof::<Bls12>::read_uncompressed(&v[..]).unwrap(), proof);
+        assert_eq!(
+            Proof::<Bls12>::read_uncompressed_unchecked(&v[..]).unwrap(),
+            proof
+        );
+
+        // Read two proofs at once.
+        proof.write_uncompressed(&mut v).unwrap();
+        assert_eq!(
+            Proof::<Bls12>::read_many_uncompressed(&v, 2).unwrap(),
+            vec![proof.clone(), proof.clone()]
+        );
+        assert_eq!(
+            Proof::<Bls12>::read_many_uncompressed_unchecked(&v, 2).unwrap(),
+            vec![proof.clone(), proof]
+        );
+
+        // Too few or too many bytes for the number of proofs.
+        assert!(Proof::<Bls12>::read_many_uncompressed(&v, 3).is_err());
+        assert!(Proof::<Bls12>::read_many_uncompressed_unchecked(&v, 3).is_err());
+        assert!(Proof::<Bls12>::read_many_uncompressed(&v, 1).is_err());
+
+        v.truncate(Proof::<Bls12>::uncompressed_size());
+
+        // Truncated, and not a point.
+        assert!(Proof::<Bls12>::read_uncompressed(&v[..v.len() - 1]).is_err());
+        v[1] ^= 1;
+        assert!(Proof::<Bls12>::read_uncompressed(&v[..]).is_err());
+    }
+
+    #[test]
+    fn hex() {
+        use group::{Curve, Group};
+
+        let rng = &mut thread_rng();
+        let proof = Proof::<Bls12> {
+            a: <Bls12 as Engine>::G1::random(&mut *rng).to_affine(),
+            b: <Bls12 as Engine>::G2::random(&mut *rng).to_affine(),
+            c: <Bls12 as Engine>::G1::random(&mut *rng).to_affine(),
+        };
+
+        let hex = proof.to_hex();
+        assert_eq!(hex.len(), 2 * Proof::<Bls12>::size());
+        assert_eq!(Proof::<Bls12>::from_hex(&hex).unwrap(), proof);
+        assert_eq!(
+            Proof::<Bls12>::from_hex(&hex.to_uppercase()).unwrap(),
+            proof
+        );
+
+        // Not hex, odd length, and wrong length.
+        assert!(Proof::<Bls12>::from_hex(&hex.replace('a', "g")).is_err());
+        assert!(Proof::<Bls12>::from_hex(&hex[1..]).is_err());
+        assert!(Proof::<Bls12>::from_hex(&hex[2..]).is_err());
+        assert!(Proof::<Bls12>::from_hex(&format!("{}00", hex)).is_err());
+        assert!(Proof::<Bls12>::from_hex("").is_err());
+    }
+
+    #[test]
+    fn read_rejects_points_outside_subgroup() {
+        use blstrs::{G1Affine, G1Compressed};
+        use group::{Curve, Group};
+
+        // Find a point on the curve which is not in the prime order subgroup, as
+        // almost all points on the curve are.
+        let mut x = 0u8;
+        let bad_a = loop {
+            x += 1;
+            let mut repr = G1Compressed::default();
+            repr.as_mut()[0] = 0x80; // compressed
+            repr.as_mut()[47] = x;
+            if let Some(p) = Option::<G1Affine>::from(G1Affine::from_bytes_unchecked(&repr)) {
+                assert!(bool::from(G1Affine::from_bytes(&repr).is_none()));
+                break p;
+            }
+        };
+
+        let rng = &mut thread_rng();
+        let b = <Bls12 as Engine>::G2::random(&mut *rng).to_affine();
+        let c = <Bls12 as Engine>::G1::random(&mut *rng).to_affine();
+
+        let mut bytes = vec![];
+        bytes.extend_from_slice(bad_a.to_bytes().as_ref());
+        bytes.extend_from_slice(b.to_bytes().as_ref());
+        bytes.extend_from_slice(c.to_bytes().as_ref());
+
+        assert!(Proof::<Bls12>::read(&bytes[..]).is_err());
+        assert!(Proof::<Bls12>::read_many(&bytes, 1).is_err());
+        assert!(Proof::<Bls12>::from_parts(
+            bad_a.to_bytes().as_ref(),
+            b.to_bytes().as_ref(),
+            c.to_bytes().as_ref()
+        )
+        .is_err());
+
+        let proof = Proof::<Bls12>::read_unchecked(&bytes[..]).unwrap();
+        assert_eq!(proof.a, bad_a);
+        assert_eq!(proof.b, b);
+        assert_eq!(proof.c, c);
+        assert_eq!(
+            Proof::<Bls12>::read_many_unchecked(&bytes, 1).unwrap()[0],
+            proof
+        );
+    }
+
+    #[test]
+    fn from_parts_invalid() {
+        use blstrs::{G1Affine, G2Affine};
+        use group::{Curve, Group};
+
+        let rng = &mut thread_rng();
+        let a = <Bls12 as Engine>::G1::random(&mut *rng).to_affine();
+        let b = <Bls12 as Engine>::G2::random(&mut *rng).to_affine();
+        let a_bytes = a.to_bytes();
+        let b_bytes = b.to_bytes();
+
+        assert!(
+            Proof::<Bls12>::from_parts(a_bytes.as_ref(), b_bytes.as_ref(), a_bytes.as_ref())
+                .is_ok()
+        );
+
+        let identity_g1 = G1Affine::identity().to_bytes();
+        let identity_g2 = G2Affine::identity().to_bytes();
+        assert!(matches!(
+            Proof::<Bls12>::from_parts(identity_g1.as_ref(), b_bytes.as_ref(), a_bytes.as_ref()),
+            Err(SynthesisError::UnexpectedIdentity)
+        ));
+        assert!(matches!(
+            Proof::<Bls12>::from_parts(a_bytes.as_ref(), identity_g2.as_ref(), a_bytes.as_ref()),
+            Err(SynthesisError::UnexpectedIdentity)
+        ));
+        assert!(matches!(
+            Proof::<Bls12>::from_parts(a_bytes.as_ref(), b_bytes.as_ref(), identity_g1.as_ref()),
+            Err(SynthesisError::UnexpectedIdentity)
+        ));
+
+        // Wrong lengths and invalid encodings
+        assert!(matches!(
+            Proof::<Bls12>::from_parts(b_bytes.as_ref(), b_bytes.as_ref(), a_bytes.as_ref()),
+            Err(SynthesisError::IoError(_))
+        ));
+        let mut invalid = a_bytes;
+        invalid.as_mut()[0] ^= 0xff;
+        assert!(matches!(
+            Proof::<Bls12>::from_parts(invalid.as_ref(), b_bytes.as_ref(), a_bytes.as_ref()),
+            Err(SynthesisError::IoError(_))
+        ));
+    }
 }
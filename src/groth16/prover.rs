@@ -37,9 +37,133 @@ struct ProvingAssignment<E: Engine> {
     // Assignments of variables
     input_assignment: Vec<E::Fr>,
     aux_assignment: Vec<E::Fr>,
+
+    // Current namespace, only tracked when `BELLMAN_ERROR_CONTEXT` is set, to
+    // report where an assignment failed without allocating for every namespace
+    // otherwise
+    namespace: Option<Vec<String>>,
 }
 use std::fmt;
 
+/// Returns an empty namespace path to track if `BELLMAN_ERROR_CONTEXT` is set,
+/// so that assignment errors report their full path.
+fn namespace_tracking() -> Option<Vec<String>> {
+    std::env::var("BELLMAN_ERROR_CONTEXT")
+        .ok()
+        .map(|_| Vec::new())
+}
+
+impl<E: Engine> ProvingAssignment<E> {
+    /// Creates an empty assignment which pushes into the given buffers, keeping their capacity.
+    fn with_buffers(buffers: ProverBuffers<E>) -> Self {
+        let ProverBuffers {
+            mut a,
+            mut b,
+            mut c,
+            mut input_assignment,
+            mut aux_assignment,
+        } = buffers;
+        a.clear();
+        b.clear();
+        c.clear();
+        input_assignment.clear();
+        aux_assignment.clear();
+
+        Self {
+            a_aux_density: DensityTracker::new(),
+            b_input_density: DensityTracker::new(),
+            b_aux_density: DensityTracker::new(),
+            a,
+            b,
+            c,
+            input_assignment,
+            aux_assignment,
+            namespace: namespace_tracking(),
+        }
+    }
+
+    /// Attaches the current namespace path, followed by `annotation`, to `err`.
+    fn error_in_namespace<A, AR>(&self, err: SynthesisError, annotation: A) -> SynthesisError
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let mut path = match &self.namespace {
+            Some(namespace) if !namespace.is_empty() => namespace.join("/") + "/",
+            _ => String::new(),
+        };
+        path.push_str(&annotation().into());
+
+        err.in_namespace(&path)
+    }
+
+    /// Moves the evaluation and assignment vectors out, so they can be reused for another proof.
+    fn take_buffers(&mut self) -> ProverBuffers<E> {
+        ProverBuffers {
+            a: std::mem::replace(&mut self.a, Vec::new()),
+            b: std::mem::replace(&mut self.b, Vec::new()),
+            c: std::mem::replace(&mut self.c, Vec::new()),
+            input_assignment: std::mem::replace(&mut self.input_assignment, Vec::new()),
+            aux_assignment: std::mem::replace(&mut self.aux_assignment, Vec::new()),
+        }
+    }
+}
+
+/// The per-proof vectors a [`ProverContext`] holds on to between batches.
+struct ProverBuffers<E: Engine> {
+    a: Vec<E::Fr>,
+    b: Vec<E::Fr>,
+    c: Vec<E::Fr>,
+    input_assignment: Vec<E::Fr>,
+    aux_assignment: Vec<E::Fr>,
+}
+
+impl<E: Engine> Default for ProverBuffers<E> {
+    fn default() -> Self {
+        Self {
+            a: Vec::new(),
+            b: Vec::new(),
+            c: Vec::new(),
+            input_assignment: Vec::new(),
+            aux_assignment: Vec::new(),
+        }
+    }
+}
+
+/// Keeps the per-proof assignment and FFT buffers alive across batches.
+///
+/// Every proof in a batch needs vectors for the A, B and C evaluations (which
+/// are also used as FFT scratch space) and for the variable assignments. When
+/// proving many batches of the same circuit, passing the same context to
+/// [`create_proof_batch_priority_reuse`] lets each batch reuse the allocations
+/// of the previous one instead of allocating them again.
+pub struct ProverContext<E: Engine> {
+    buffers: Vec<ProverBuffers<E>>,
+}
+
+impl<E: Engine> Default for ProverContext<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Engine> ProverContext<E> {
+    pub fn new() -> Self {
+        ProverContext {
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Hands out buffers for `count` proofs, allocating empty ones if not enough are stored.
+    fn take(&mut self, count: usize) -> Vec<ProverBuffers<E>> {
+        let mut buffers = self
+            .buffers
+            .split_off(self.buffers.len().saturating_sub(count));
+        buffers.resize_with(count, Default::default);
+        buffers
+    }
+}
+
 impl<E: Engine> fmt::Debug for ProvingAssignment<E> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("ProvingAssignment")
@@ -102,34 +226,67 @@ impl<E: Engine> ConstraintSystem<E> for ProvingAssignment<E> {
             c: vec![],
             input_assignment: vec![],
             aux_assignment: vec![],
+            namespace: namespace_tracking(),
         }
     }
 
-    fn alloc<F, A, AR>(&mut self, _: A, f: F) -> Result<Variable, SynthesisError>
+    fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
     where
         F: FnOnce() -> Result<E::Fr, SynthesisError>,
         A: FnOnce() -> AR,
         AR: Into<String>,
     {
-        self.aux_assignment.push(f()?);
+        let value = f().map_err(|err| self.error_in_namespace(err, annotation))?;
+        self.aux_assignment.push(value);
         self.a_aux_density.add_element();
         self.b_aux_density.add_element();
 
         Ok(Variable(Index::Aux(self.aux_assignment.len() - 1)))
     }
 
-    fn alloc_input<F, A, AR>(&mut self, _: A, f: F) -> Result<Variable, SynthesisError>
+    fn alloc_input<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
     where
         F: FnOnce() -> Result<E::Fr, SynthesisError>,
         A: FnOnce() -> AR,
         AR: Into<String>,
     {
-        self.input_assignment.push(f()?);
+        let value = f().map_err(|err| self.error_in_namespace(err, annotation))?;
+        self.input_assignment.push(value);
         self.b_input_density.add_element();
 
         Ok(Variable(Index::Input(self.input_assignment.len() - 1)))
     }
 
+    fn alloc_parallel<F, A, AR>(
+        &mut self,
+        annotation: A,
+        fs: Vec<F>,
+    ) -> Result<Vec<Variable>, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError> + Send,
+        A: Fn(usize) -> AR,
+        AR: Into<String>,
+    {
+        let mut fs = fs.into_iter().map(Some).collect::<Vec<_>>();
+        let mut values = (0..fs.len()).map(|_| None).collect::<Vec<_>>();
+
+        Worker::new().scope(fs.len(), |scope, chunk| {
+            for (fs, values) in fs.chunks_mut(chunk).zip(values.chunks_mut(chunk)) {
+                scope.execute(move || {
+                    for (f, value) in fs.iter_mut().zip(values.iter_mut()) {
+                        *value = f.take().map(|f| f());
+                    }
+                });
+            }
+        });
+
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| self.alloc(|| annotation(i), || value.unwrap()))
+            .collect()
+    }
+
     fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
     where
         A: FnOnce() -> AR,
@@ -148,7 +305,7 @@ impl<E: Engine> ConstraintSystem<E> for ProvingAssignment<E> {
         let b_input_density = &mut self.b_input_density;
         let b_aux_density = &mut self.b_aux_density;
 
-        let a_res = a.eval(
+        let a_res = a.eval_with_density(
             // Inputs have full density in the A query
             // because there are constraints of the
             // form x * 0 = 0 for each input.
@@ -158,14 +315,14 @@ impl<E: Engine> ConstraintSystem<E> for ProvingAssignment<E> {
             aux_assignment,
         );
 
-        let b_res = b.eval(
+        let b_res = b.eval_with_density(
             Some(b_input_density),
             Some(b_aux_density),
             input_assignment,
             aux_assignment,
         );
 
-        let c_res = c.eval(
+        let c_res = c.eval_with_density(
             // There is no C polynomial query,
             // though there is an (beta)A + (alpha)B + C
             // query for all aux variables.
@@ -181,16 +338,21 @@ impl<E: Engine> ConstraintSystem<E> for ProvingAssignment<E> {
         self.c.push(c_res);
     }
 
-    fn push_namespace<NR, N>(&mut self, _: N)
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        // Do nothing; we don't care about namespaces in this context.
+        // Only kept to give context to assignment errors, if asked for.
+        if let Some(namespace) = self.namespace.as_mut() {
+            namespace.push(name_fn().into());
+        }
     }
 
     fn pop_namespace(&mut self) {
-        // Do nothing; we don't care about namespaces in this context.
+        if let Some(namespace) = self.namespace.as_mut() {
+            namespace.pop();
+        }
     }
 
     fn get_root(&mut self) -> &mut Self::Root {
@@ -238,7 +400,62 @@ where
     create_proof_batch_priority::<E, C, P>(circuits, params, r_s, s_s, priority)
 }
 
-#[allow(clippy::clippy::needless_collect)]
+/// Creates a proof using the given blinding scalars `r` and `s` instead of
+/// sampling them, so that the same circuit, parameters and `(r, s)` always
+/// produce the same proof. The scalars must still be uniformly random and secret
+/// for the proof to be zero-knowledge.
+pub fn create_proof_deterministic<E, C, P: ParameterSource<E>>(
+    circuit: C,
+    params: P,
+    r: E::Fr,
+    s: E::Fr,
+) -> Result<Proof<E>, SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    C: Circuit<E> + Send,
+{
+    let proofs =
+        create_proof_batch_priority::<E, C, P>(vec![circuit], params, vec![r], vec![s], false)?;
+    Ok(proofs.into_iter().next().unwrap())
+}
+
+/// Like [`create_proof_deterministic`], but also returns the assignment the
+/// proof was computed from: the public inputs, starting with the implicit "one"
+/// input, and the auxiliary variables, indexed like `Index::Input` and
+/// `Index::Aux`. The circuit is only synthesized once.
+#[allow(clippy::type_complexity)]
+pub fn create_proof_with_assignment<E, C, P: ParameterSource<E>>(
+    circuit: C,
+    params: P,
+    r: E::Fr,
+    s: E::Fr,
+) -> Result<(Proof<E>, Vec<E::Fr>, Vec<E::Fr>), SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    C: Circuit<E> + Send,
+{
+    // Keeping the buffers around also keeps the assignment.
+    let mut ctx = ProverContext::new();
+    let proofs = create_proof_batch_priority_with_context::<E, C, P>(
+        vec![circuit],
+        params,
+        vec![r],
+        vec![s],
+        false,
+        Some(&mut ctx),
+    )?;
+    let buffers = ctx
+        .buffers
+        .pop()
+        .expect("buffers are returned to the context");
+
+    Ok((
+        proofs.into_iter().next().unwrap(),
+        buffers.input_assignment,
+        buffers.aux_assignment,
+    ))
+}
+
 pub fn create_proof_batch_priority<E, C, P: ParameterSource<E>>(
     circuits: Vec<C>,
     params: P,
@@ -246,14 +463,109 @@ pub fn create_proof_batch_priority<E, C, P: ParameterSource<E>>(
     s_s: Vec<E::Fr>,
     priority: bool,
 ) -> Result<Vec<Proof<E>>, SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    C: Circuit<E> + Send,
+{
+    create_proof_batch_priority_with_context::<E, C, P>(circuits, params, r_s, s_s, priority, None)
+}
+
+/// Like [`create_proof_batch_priority`], but proves at most `max_concurrency`
+/// circuits at once, by proving the batch in consecutive chunks of that size.
+/// This bounds the memory, including GPU memory, held by the proofs in flight.
+/// A `max_concurrency` of 0 proves the whole batch at once.
+///
+/// Each chunk is still proven on the global thread pool, so the number of
+/// threads is controlled by `RAYON_NUM_THREADS` (or the deprecated
+/// `BELLMAN_NUM_CPUS`) independently of `max_concurrency`.
+pub fn create_proof_batch_with_max_concurrency<E, C, P>(
+    circuits: Vec<C>,
+    params: P,
+    r_s: Vec<E::Fr>,
+    s_s: Vec<E::Fr>,
+    priority: bool,
+    max_concurrency: usize,
+) -> Result<Vec<Proof<E>>, SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    C: Circuit<E> + Send,
+    P: ParameterSource<E> + Clone,
+{
+    if max_concurrency == 0 || circuits.len() <= max_concurrency {
+        return create_proof_batch_priority::<E, C, P>(circuits, params, r_s, s_s, priority);
+    }
+    assert_eq!(circuits.len(), r_s.len(), "one r is needed per circuit");
+    assert_eq!(circuits.len(), s_s.len(), "one s is needed per circuit");
+
+    let mut proofs = Vec::with_capacity(circuits.len());
+    let mut circuits = circuits.into_iter();
+    let mut r_s = r_s.into_iter();
+    let mut s_s = s_s.into_iter();
+    loop {
+        let chunk = circuits.by_ref().take(max_concurrency).collect::<Vec<_>>();
+        if chunk.is_empty() {
+            break;
+        }
+        let len = chunk.len();
+        proofs.extend(create_proof_batch_priority::<E, C, P>(
+            chunk,
+            params.clone(),
+            r_s.by_ref().take(len).collect(),
+            s_s.by_ref().take(len).collect(),
+            priority,
+        )?);
+    }
+
+    Ok(proofs)
+}
+
+/// Like [`create_proof_batch_priority`], but takes the per-proof buffers from `ctx` and
+/// returns them to it once they are no longer needed, so they can be reused by the next batch.
+pub fn create_proof_batch_priority_reuse<E, C, P: ParameterSource<E>>(
+    circuits: Vec<C>,
+    params: P,
+    r_s: Vec<E::Fr>,
+    s_s: Vec<E::Fr>,
+    priority: bool,
+    ctx: &mut ProverContext<E>,
+) -> Result<Vec<Proof<E>>, SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    C: Circuit<E> + Send,
+{
+    create_proof_batch_priority_with_context::<E, C, P>(
+        circuits,
+        params,
+        r_s,
+        s_s,
+        priority,
+        Some(ctx),
+    )
+}
+
+#[allow(clippy::clippy::needless_collect)]
+fn create_proof_batch_priority_with_context<E, C, P: ParameterSource<E>>(
+    circuits: Vec<C>,
+    params: P,
+    r_s: Vec<E::Fr>,
+    s_s: Vec<E::Fr>,
+    priority: bool,
+    mut ctx: Option<&mut ProverContext<E>>,
+) -> Result<Vec<Proof<E>>, SynthesisError>
 where
     E: gpu::GpuEngine + MultiMillerLoop,
     C: Circuit<E> + Send,
 {
     info!("Bellperson {} is being used!", BELLMAN_VERSION);
 
+    let keep_buffers = ctx.is_some();
+    let buffers = match ctx.as_mut() {
+        Some(ctx) => ctx.take(circuits.len()),
+        None => (0..circuits.len()).map(|_| Default::default()).collect(),
+    };
+
     let (start, mut provers, input_assignments, aux_assignments) =
-        create_proof_batch_priority_inner(circuits)?;
+        create_proof_batch_priority_inner(circuits, buffers, keep_buffers)?;
 
     let worker = Worker::new();
     let input_len = input_assignments[0].len();
@@ -317,11 +629,16 @@ where
 
         let mut fft_kern = Some(LockedFFTKernel::<E>::new(log_d, priority));
         for prover in provers_ref {
-            a_s.push(execute_fft(worker, prover, &mut fft_kern)?);
+            a_s.push(execute_fft(worker, prover, &mut fft_kern, keep_buffers)?);
         }
         Ok(())
     })?;
 
+    if let Some(ctx) = ctx {
+        ctx.buffers
+            .extend(provers.iter_mut().map(|prover| prover.take_buffers()));
+    }
+
     let mut multiexp_kern = Some(LockedMultiexpKernel::<E>::new(log_d, priority));
     let params_h = params_h.unwrap()?;
 
@@ -528,10 +845,13 @@ where
     Ok(proofs)
 }
 
+/// When `keep_buffers` is set, the A, B and C vectors are put back into `prover` afterwards
+/// instead of being freed.
 fn execute_fft<E>(
     worker: &Worker,
     prover: &mut ProvingAssignment<E>,
     fft_kern: &mut Option<LockedFFTKernel<E>>,
+    keep_buffers: bool,
 ) -> Result<Arc<Vec<<E::Fr as PrimeField>::Repr>>, SynthesisError>
 where
     E: gpu::GpuEngine + MultiMillerLoop,
@@ -544,26 +864,39 @@ where
     EvaluationDomain::coset_fft_many(&mut [&mut a, &mut b, &mut c], &worker, fft_kern)?;
 
     a.mul_assign(&worker, &b);
-    drop(b);
+    if keep_buffers {
+        prover.b = b.into_coeffs();
+    } else {
+        drop(b);
+    }
     a.sub_assign(&worker, &c);
-    drop(c);
+    if keep_buffers {
+        prover.c = c.into_coeffs();
+    } else {
+        drop(c);
+    }
 
     a.divide_by_z_on_coset(&worker);
     a.icoset_fft(&worker, fft_kern)?;
 
     let a = a.into_coeffs();
     let a_len = a.len() - 1;
-    let a = a
-        .into_par_iter()
+    let a_repr = a
+        .par_iter()
         .take(a_len)
         .map(|s| s.to_repr())
         .collect::<Vec<_>>();
-    Ok(Arc::new(a))
+    if keep_buffers {
+        prover.a = a;
+    }
+    Ok(Arc::new(a_repr))
 }
 
 #[allow(clippy::type_complexity)]
 fn create_proof_batch_priority_inner<E, C>(
     circuits: Vec<C>,
+    buffers: Vec<ProverBuffers<E>>,
+    keep_buffers: bool,
 ) -> Result<
     (
         Instant,
@@ -580,8 +913,9 @@ where
     let start = Instant::now();
     let mut provers = circuits
         .into_par_iter()
-        .map(|circuit| -> Result<_, SynthesisError> {
-            let mut prover = ProvingAssignment::new();
+        .zip(buffers.into_par_iter())
+        .map(|(circuit, buffers)| -> Result<_, SynthesisError> {
+            let mut prover = ProvingAssignment::with_buffers(buffers);
 
             prover.alloc_input(|| "", || Ok(E::Fr::one()))?;
 
@@ -605,12 +939,14 @@ where
         .par_iter_mut()
         .map(|prover| {
             let input_assignment = std::mem::replace(&mut prover.input_assignment, Vec::new());
-            Arc::new(
-                input_assignment
-                    .into_iter()
-                    .map(|s| s.to_repr())
-                    .collect::<Vec<_>>(),
-            )
+            let input_repr = input_assignment
+                .iter()
+                .map(|s| s.to_repr())
+                .collect::<Vec<_>>();
+            if keep_buffers {
+                prover.input_assignment = input_assignment;
+            }
+            Arc::new(input_repr)
         })
         .collect::<Vec<_>>();
 
@@ -618,12 +954,14 @@ where
         .par_iter_mut()
         .map(|prover| {
             let aux_assignment = std::mem::replace(&mut prover.aux_assignment, Vec::new());
-            Arc::new(
-                aux_assignment
-                    .into_iter()
-                    .map(|s| s.to_repr())
-                    .collect::<Vec<_>>(),
-            )
+            let aux_repr = aux_assignment
+                .iter()
+                .map(|s| s.to_repr())
+                .collect::<Vec<_>>();
+            if keep_buffers {
+                prover.aux_assignment = aux_assignment;
+            }
+            Arc::new(aux_repr)
         })
         .collect::<Vec<_>>();
 
@@ -639,6 +977,83 @@ mod tests {
     use rand_core::SeedableRng;
     use rand_xorshift::XorShiftRng;
 
+    /// Allocates a missing assignment under two namespaces, returning the path
+    /// the error reports.
+    fn error_context_path() -> String {
+        let mut cs = ProvingAssignment::<Bls12>::new();
+
+        cs.alloc(|| "fine", || Ok(Fr::one())).unwrap();
+
+        let err = {
+            let mut cs = cs.namespace(|| "outer");
+            let mut cs = cs.namespace(|| "inner");
+            cs.alloc(|| "missing", || Err(SynthesisError::AssignmentMissing))
+                .unwrap_err()
+        };
+
+        // Namespaces are popped again.
+        let input_err = cs
+            .alloc_input(|| "input", || Err(SynthesisError::AssignmentMissing))
+            .unwrap_err();
+        assert_eq!(
+            input_err.to_string(),
+            "an assignment for a variable could not be computed (at `input`)"
+        );
+
+        match err {
+            SynthesisError::WithContext(path, err) => {
+                assert!(matches!(*err, SynthesisError::AssignmentMissing));
+                path
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_proving_assignment_error_context() {
+        // Only the annotation is known unless namespaces are tracked.
+        crate::test_utils::with_env_vars(vec![("BELLMAN_ERROR_CONTEXT", None)], || {
+            assert_eq!(error_context_path(), "missing");
+        });
+        crate::test_utils::with_env_vars(vec![("BELLMAN_ERROR_CONTEXT", Some("1"))], || {
+            assert_eq!(error_context_path(), "outer/inner/missing");
+        });
+    }
+
+    #[test]
+    fn test_proving_assignment_alloc_parallel() {
+        let values = (1..=8u64).map(Fr::from).collect::<Vec<_>>();
+
+        let mut sequential = ProvingAssignment::<Bls12>::new();
+        sequential.alloc_input(|| "one", || Ok(Fr::one())).unwrap();
+        sequential.alloc(|| "first", || Ok(Fr::zero())).unwrap();
+        let sequential_vars = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                sequential
+                    .alloc(|| format!("value {}", i), || Ok(value.square()))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut parallel = ProvingAssignment::<Bls12>::new();
+        parallel.alloc_input(|| "one", || Ok(Fr::one())).unwrap();
+        parallel.alloc(|| "first", || Ok(Fr::zero())).unwrap();
+        let parallel_vars = parallel
+            .alloc_parallel(
+                |i| format!("value {}", i),
+                values
+                    .iter()
+                    .map(|value| move || Ok::<_, SynthesisError>(value.square()))
+                    .collect(),
+            )
+            .unwrap();
+
+        assert_eq!(parallel_vars, sequential_vars);
+        assert_eq!(parallel, sequential);
+    }
+
     #[test]
     fn test_proving_assignment_extend() {
         let mut rng = XorShiftRng::from_seed([
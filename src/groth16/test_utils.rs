@@ -0,0 +1,154 @@
+//! Helpers for testing code built on top of Groth16 parameters.
+
+use std::ops::Mul;
+use std::sync::Arc;
+
+use ff::{Field, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve, Group, WnafGroup};
+use pairing::{Engine, MultiMillerLoop};
+use rand_core::RngCore;
+
+use super::{
+    create_random_proof, generate_parameters, generate_random_parameters, prepare_verifying_key,
+    verify_proof, Parameters,
+};
+use crate::util_cs::test_cs::TestConstraintSystem;
+use crate::{gpu, Circuit, SynthesisError};
+
+/// Re-randomizes the `delta` of a CRS, the same way a second phase of a setup
+/// ceremony would.
+///
+/// `delta` in the verifying key is scaled by a fresh random `d`, and the H and L
+/// queries are scaled by `d^-1`. The result is a structurally valid set of
+/// parameters for the same circuit, but proofs created under `params` will not
+/// verify against the returned verifying key.
+pub fn rerandomize_parameters<E, R>(params: &Parameters<E>, rng: &mut R) -> Parameters<E>
+where
+    E: MultiMillerLoop,
+    R: RngCore,
+{
+    let d = loop {
+        let d = E::Fr::random(&mut *rng);
+        if !bool::from(d.is_zero()) {
+            break d;
+        }
+    };
+    let d_inverse = d.invert().unwrap();
+
+    let mut vk = params.vk.clone();
+    vk.delta_g1 = vk.delta_g1.mul(d).to_affine();
+    vk.delta_g2 = vk.delta_g2.mul(d).to_affine();
+
+    let scale = |bases: &[E::G1Affine]| -> Vec<E::G1Affine> {
+        let scaled = bases
+            .iter()
+            .map(|base| base.mul(d_inverse))
+            .collect::<Vec<_>>();
+        let mut affine = vec![E::G1Affine::identity(); scaled.len()];
+        E::G1::batch_normalize(&scaled, &mut affine);
+        affine
+    };
+
+    Parameters {
+        vk,
+        h: Arc::new(scale(&params.h)),
+        l: Arc::new(scale(&params.l)),
+        a: params.a.clone(),
+        b_g1: params.b_g1.clone(),
+        b_g2: params.b_g2.clone(),
+    }
+}
+
+/// The toxic waste of a Groth16 setup: the generators and the secret scalars
+/// the parameters are derived from.
+///
+/// Anyone who knows these values can create proofs for statements that are
+/// false, so they must never exist outside of tests.
+pub struct Trapdoor<E: Engine> {
+    pub g1: E::G1,
+    pub g2: E::G2,
+    pub alpha: E::Fr,
+    pub beta: E::Fr,
+    pub gamma: E::Fr,
+    pub delta: E::Fr,
+    pub tau: E::Fr,
+}
+
+/// Generates random parameters for a circuit like
+/// [`generate_random_parameters`], and also returns the [`Trapdoor`] they
+/// were generated from.
+///
+/// **Not for production use.** The returned trapdoor breaks the soundness of
+/// every proof made with these parameters; it exists to test what happens
+/// when the toxic waste of a setup leaks.
+pub fn generate_parameters_with_trapdoor<C, E, R>(
+    circuit: C,
+    rng: &mut R,
+) -> Result<(Parameters<E>, Trapdoor<E>), SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    <E as Engine>::G1: WnafGroup,
+    <E as Engine>::G2: WnafGroup,
+    C: Circuit<E>,
+    R: RngCore,
+{
+    let trapdoor = Trapdoor {
+        g1: E::G1::random(&mut *rng),
+        g2: E::G2::random(&mut *rng),
+        alpha: E::Fr::random(&mut *rng),
+        beta: E::Fr::random(&mut *rng),
+        gamma: E::Fr::random(&mut *rng),
+        delta: E::Fr::random(&mut *rng),
+        tau: E::Fr::random(&mut *rng),
+    };
+
+    let params = generate_parameters::<E, C>(
+        circuit,
+        trapdoor.g1,
+        trapdoor.g2,
+        trapdoor.alpha,
+        trapdoor.beta,
+        trapdoor.gamma,
+        trapdoor.delta,
+        trapdoor.tau,
+    )?;
+
+    Ok((params, trapdoor))
+}
+
+/// Runs a circuit end to end: synthesizes it into a `TestConstraintSystem` and
+/// asserts that it is satisfied by its witness and exposes `inputs`, then
+/// generates random parameters for it, proves it and returns whether the proof
+/// verifies against `inputs`.
+///
+/// The parameters are generated from `circuit` itself, the witness is ignored
+/// during setup.
+pub fn prove_and_check<E, C, R>(
+    circuit: C,
+    inputs: &[E::Fr],
+    rng: &mut R,
+) -> Result<bool, SynthesisError>
+where
+    E: gpu::GpuEngine + MultiMillerLoop,
+    <E as Engine>::G1: WnafGroup,
+    <E as Engine>::G2: WnafGroup,
+    <E::Fr as PrimeField>::Repr: Sync,
+    C: Circuit<E> + Clone + Send,
+    R: RngCore,
+{
+    let mut cs = TestConstraintSystem::<E>::new();
+    circuit.clone().synthesize(&mut cs)?;
+    if let Some(path) = cs.which_is_unsatisfied() {
+        panic!("constraint `{}` is not satisfied", path);
+    }
+    assert!(
+        cs.verify(inputs),
+        "the circuit does not expose the given inputs"
+    );
+
+    let params = generate_random_parameters::<E, _, _>(circuit.clone(), &mut *rng)?;
+    let pvk = prepare_verifying_key(&params.vk);
+    let proof = create_random_proof(circuit, &params, &mut *rng)?;
+
+    verify_proof(&pvk, &proof, inputs)
+}
@@ -14,7 +14,7 @@ use std::ops::{AddAssign, Mul, MulAssign, SubAssign};
 use super::{
     create_proof, create_proof_batch, generate_parameters, prepare_verifying_key, verify_proof,
 };
-use crate::{Circuit, ConstraintSystem, SynthesisError};
+use crate::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
 
 #[derive(Clone)]
 struct XorDemo<E: Engine> {
@@ -515,6 +515,60 @@ fn test_verify_random_single() {
     }
 }
 
+#[test]
+fn test_verify_precomputed_ic() {
+    use crate::groth16::{create_random_proof, generate_random_parameters, Proof, VerifyingKey};
+    use blstrs::{Bls12, Scalar as Fr};
+    use group::prime::PrimeCurveAffine;
+
+    // Verifies without the prepared key's precomputed `ic` tables.
+    fn verify_naive(vk: &VerifyingKey<Bls12>, proof: &Proof<Bls12>, inputs: &[Fr]) -> bool {
+        let mut acc = vk.ic[0].to_curve();
+        for (base, input) in vk.ic[1..].iter().zip(inputs) {
+            acc.add_assign(&base.mul(*input));
+        }
+
+        Bls12::pairing(&proof.a, &proof.b)
+            == Bls12::pairing(&vk.alpha_g1, &vk.beta_g2)
+                + Bls12::pairing(&acc.to_affine(), &vk.gamma_g2)
+                + Bls12::pairing(&proof.c, &vk.delta_g2)
+    }
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+
+    // One prepared key for all proofs.
+    let pvk = prepare_verifying_key(&params.vk);
+
+    for &(a, b) in &[(false, false), (false, true), (true, false), (true, true)] {
+        let c = XorDemo {
+            a: Some(a),
+            b: Some(b),
+            _marker: PhantomData,
+        };
+        let proof = create_random_proof(c, &params, &mut rng).unwrap();
+
+        let input = if a ^ b { Fr::one() } else { Fr::zero() };
+        for inputs in &[[input], [Fr::one() - input], [Fr::random(&mut rng)]] {
+            let expected = verify_naive(&params.vk, &proof, inputs);
+            assert_eq!(verify_proof(&pvk, &proof, inputs).unwrap(), expected);
+            assert_eq!(expected, inputs[0] == input);
+        }
+    }
+}
+
 #[test]
 fn test_verify_random_batch() {
     use crate::groth16::{
@@ -649,3 +703,1018 @@ fn test_verify_random_batch() {
         }
     }
 }
+
+#[test]
+fn test_domain_for_constraint_count() {
+    use crate::domain::EvaluationDomain;
+
+    let g1 = Fr::one();
+    let g2 = Fr::one();
+    let alpha = Fr::from(48577u64);
+    let beta = Fr::from(22580u64);
+    let gamma = Fr::from(53332u64);
+    let delta = Fr::from(5481u64);
+    let tau = Fr::from(3673u64);
+
+    let params = {
+        let c = XorDemo::<DummyEngine> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_parameters(c, g1, g2, alpha, beta, gamma, delta, tau).unwrap()
+    };
+
+    // XorDemo has 3 constraints and 1 public input, and the H query holds
+    // one element less than the evaluation domain used while proving.
+    let domain = EvaluationDomain::<DummyEngine>::for_constraint_count(3, 1).unwrap();
+    assert_eq!(domain.as_ref().len(), params.h.len() + 1);
+    assert_eq!(domain.as_ref().len(), 8);
+
+    // Crossing a power of two grows the domain.
+    let domain = EvaluationDomain::<DummyEngine>::for_constraint_count(7, 1).unwrap();
+    assert_eq!(domain.as_ref().len(), 16);
+}
+
+#[test]
+fn test_rerandomize_parameters() {
+    use crate::groth16::test_utils::rerandomize_parameters;
+    use crate::groth16::{create_random_proof, generate_random_parameters};
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+    let rerandomized = rerandomize_parameters(&params, &mut rng);
+    assert!(params.vk != rerandomized.vk);
+
+    let pvk = prepare_verifying_key(&params.vk);
+    let rerandomized_pvk = prepare_verifying_key(&rerandomized.vk);
+
+    let c = XorDemo {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+
+    // Proofs only verify under the parameters they were created with.
+    let proof = create_random_proof(c.clone(), &params, &mut rng).unwrap();
+    assert!(verify_proof(&pvk, &proof, &[Fr::one()]).unwrap());
+    assert!(!verify_proof(&rerandomized_pvk, &proof, &[Fr::one()]).unwrap());
+
+    let proof = create_random_proof(c, &rerandomized, &mut rng).unwrap();
+    assert!(verify_proof(&rerandomized_pvk, &proof, &[Fr::one()]).unwrap());
+    assert!(!verify_proof(&pvk, &proof, &[Fr::one()]).unwrap());
+}
+
+#[test]
+fn test_forge_proof_with_trapdoor() {
+    use crate::groth16::test_utils::generate_parameters_with_trapdoor;
+    use crate::groth16::{create_random_proof, Proof};
+    use blstrs::{Bls12, Scalar as Fr};
+    use group::prime::PrimeCurveAffine;
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let (params, trapdoor) = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_parameters_with_trapdoor::<_, Bls12, _>(c, &mut rng).unwrap()
+    };
+    assert_eq!(
+        params.vk.alpha_g1,
+        (trapdoor.g1 * trapdoor.alpha).to_affine()
+    );
+    assert_eq!(
+        params.vk.delta_g2,
+        (trapdoor.g2 * trapdoor.delta).to_affine()
+    );
+
+    let pvk = prepare_verifying_key(&params.vk);
+
+    // The parameters work as usual.
+    let c = XorDemo {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+    let proof = create_random_proof(c, &params, &mut rng).unwrap();
+    assert!(verify_proof(&pvk, &proof, &[Fr::one()]).unwrap());
+
+    // With the trapdoor, pick any A and B, and solve the verification
+    // equation for C:
+    // C = (a * b * g1 - alpha * beta * g1 - gamma * sum(x_i * ic_i)) / delta
+    // The XOR of two bits is never 2, yet the forged proof verifies.
+    let inputs = [Fr::from(2u64)];
+    let a = Fr::random(&mut rng);
+    let b = Fr::random(&mut rng);
+    let mut acc = params.vk.ic[0].to_curve();
+    for (base, input) in params.vk.ic[1..].iter().zip(&inputs) {
+        acc.add_assign(&base.mul(*input));
+    }
+    let c = (trapdoor.g1 * (a * b - trapdoor.alpha * trapdoor.beta) - acc * trapdoor.gamma)
+        * trapdoor.delta.invert().unwrap();
+    let forged = Proof::<Bls12> {
+        a: (trapdoor.g1 * a).to_affine(),
+        b: (trapdoor.g2 * b).to_affine(),
+        c: c.to_affine(),
+    };
+
+    assert!(verify_proof(&pvk, &forged, &inputs).unwrap());
+    assert!(!verify_proof(&pvk, &forged, &[Fr::one()]).unwrap());
+}
+
+#[test]
+fn test_generate_parameters_deterministic() {
+    use crate::groth16::test_utils::generate_parameters_with_trapdoor;
+    use blstrs::Bls12;
+
+    let generate = || {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_parameters_with_trapdoor::<_, Bls12, _>(c, &mut rng).unwrap()
+    };
+
+    let (params, trapdoor) = generate();
+    let (other, _) = generate();
+    assert!(params == other);
+
+    let mut bytes = vec![];
+    params.write(&mut bytes).unwrap();
+    let mut other_bytes = vec![];
+    other.write(&mut other_bytes).unwrap();
+    assert_eq!(bytes, other_bytes);
+
+    // The batch normalized verifying key matches converting point by point.
+    let g1 = trapdoor.g1.to_affine();
+    let g2 = trapdoor.g2.to_affine();
+    assert_eq!(params.vk.alpha_g1, g1.mul(trapdoor.alpha).to_affine());
+    assert_eq!(params.vk.beta_g1, g1.mul(trapdoor.beta).to_affine());
+    assert_eq!(params.vk.beta_g2, g2.mul(trapdoor.beta).to_affine());
+    assert_eq!(params.vk.gamma_g2, g2.mul(trapdoor.gamma).to_affine());
+    assert_eq!(params.vk.delta_g1, g1.mul(trapdoor.delta).to_affine());
+    assert_eq!(params.vk.delta_g2, g2.mul(trapdoor.delta).to_affine());
+}
+
+#[test]
+fn test_verify_proof_signed() {
+    use crate::groth16::{create_random_proof, generate_random_parameters, verify_proof_signed};
+    use blstrs::{Bls12, Scalar as Fr};
+
+    /// Exposes `x` and `-x` as public inputs.
+    #[derive(Clone)]
+    struct Negation {
+        x: Option<Fr>,
+    }
+
+    impl Circuit<Bls12> for Negation {
+        fn synthesize<CS: ConstraintSystem<Bls12>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            let x = cs.alloc(|| "x", || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let neg = cs.alloc_input(
+                || "neg",
+                || self.x.map(|x| -x).ok_or(SynthesisError::AssignmentMissing),
+            )?;
+            let pos =
+                cs.alloc_input(|| "pos", || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce(
+                || "pos = x",
+                |lc| lc + x,
+                |lc| lc + CS::one(),
+                |lc| lc + pos,
+            );
+            cs.enforce(
+                || "neg = -x",
+                |lc| lc + x + neg,
+                |lc| lc + CS::one(),
+                |lc| lc,
+            );
+
+            Ok(())
+        }
+    }
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = generate_random_parameters::<Bls12, _, _>(Negation { x: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let c = Negation {
+        x: Some(Fr::from(7u64)),
+    };
+    let proof = create_random_proof(c, &params, &mut rng).unwrap();
+
+    // -7 is p - 7 in the field.
+    let reduced = Fr::from_str_vartime(
+        "52435875175126190479447740508185965837690552500527637822603658699938581184506",
+    )
+    .unwrap();
+    assert!(verify_proof(&pvk, &proof, &[reduced, Fr::from(7u64)]).unwrap());
+    assert!(verify_proof_signed(&pvk, &proof, &[-7, 7]).unwrap());
+
+    assert!(!verify_proof_signed(&pvk, &proof, &[7, 7]).unwrap());
+    assert!(!verify_proof_signed(&pvk, &proof, &[-7, -7]).unwrap());
+}
+
+#[test]
+fn test_create_batch_reuse() {
+    use crate::groth16::{create_proof_batch_priority_reuse, ProverContext};
+
+    let g1 = Fr::one();
+    let g2 = Fr::one();
+    let alpha = Fr::from(48577u64);
+    let beta = Fr::from(22580u64);
+    let gamma = Fr::from(53332u64);
+    let delta = Fr::from(5481u64);
+    let tau = Fr::from(3673u64);
+
+    let params = {
+        let c = XorDemo::<DummyEngine> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_parameters(c, g1, g2, alpha, beta, gamma, delta, tau).unwrap()
+    };
+
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let r_s = vec![Fr::from(27134u64), Fr::from(27132u64), Fr::from(27130u64)];
+    let s_s = vec![Fr::from(17146u64), Fr::from(17142u64), Fr::from(17138u64)];
+
+    let circuits = vec![(true, false), (false, false), (true, true)]
+        .into_iter()
+        .map(|(a, b)| XorDemo {
+            a: Some(a),
+            b: Some(b),
+            _marker: PhantomData,
+        })
+        .collect::<Vec<_>>();
+
+    let expected = create_proof_batch(circuits.clone(), &params, r_s.clone(), s_s.clone()).unwrap();
+
+    // The first batch fills the context, the following ones reuse its buffers,
+    // including a smaller and a larger batch.
+    let mut ctx = ProverContext::new();
+    for &count in &[3, 3, 1, 3] {
+        let proofs = create_proof_batch_priority_reuse(
+            circuits[..count].to_vec(),
+            &params,
+            r_s[..count].to_vec(),
+            s_s[..count].to_vec(),
+            false,
+            &mut ctx,
+        )
+        .unwrap();
+        assert_eq!(&proofs[..], &expected[..count]);
+    }
+
+    assert!(verify_proof(&pvk, &expected[0], &[Fr::one()]).unwrap());
+    assert!(verify_proof(&pvk, &expected[1], &[Fr::zero()]).unwrap());
+    assert!(verify_proof(&pvk, &expected[2], &[Fr::zero()]).unwrap());
+}
+
+#[test]
+fn test_create_batch_max_concurrency() {
+    use crate::groth16::create_proof_batch_with_max_concurrency;
+
+    let g1 = Fr::one();
+    let g2 = Fr::one();
+    let alpha = Fr::from(48577u64);
+    let beta = Fr::from(22580u64);
+    let gamma = Fr::from(53332u64);
+    let delta = Fr::from(5481u64);
+    let tau = Fr::from(3673u64);
+
+    let params = {
+        let c = XorDemo::<DummyEngine> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_parameters(c, g1, g2, alpha, beta, gamma, delta, tau).unwrap()
+    };
+
+    let circuits = vec![
+        (true, false),
+        (false, false),
+        (true, true),
+        (false, true),
+        (true, false),
+    ]
+    .into_iter()
+    .map(|(a, b)| XorDemo {
+        a: Some(a),
+        b: Some(b),
+        _marker: PhantomData,
+    })
+    .collect::<Vec<_>>();
+    let r_s = (0..5u64).map(|i| Fr::from(27134 + i)).collect::<Vec<_>>();
+    let s_s = (0..5u64).map(|i| Fr::from(17146 + i)).collect::<Vec<_>>();
+
+    let expected = create_proof_batch(circuits.clone(), &params, r_s.clone(), s_s.clone()).unwrap();
+
+    // No cap, a cap which does and one which does not divide the batch, and a
+    // cap larger than the batch.
+    for &max_concurrency in &[0, 1, 2, 5, 8] {
+        let proofs = create_proof_batch_with_max_concurrency(
+            circuits.clone(),
+            &params,
+            r_s.clone(),
+            s_s.clone(),
+            false,
+            max_concurrency,
+        )
+        .unwrap();
+        assert_eq!(proofs, expected, "max_concurrency {}", max_concurrency);
+    }
+}
+
+#[test]
+fn test_batch_verifier() {
+    use crate::groth16::{create_random_proof_batch, generate_random_parameters, BatchVerifier};
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let circuits = (0..100)
+        .map(|i| XorDemo {
+            a: Some(i % 2 == 0),
+            b: Some(i % 3 == 0),
+            _marker: PhantomData,
+        })
+        .collect::<Vec<_>>();
+    let inputs = circuits
+        .iter()
+        .map(|c| {
+            if c.a.unwrap() ^ c.b.unwrap() {
+                vec![Fr::one()]
+            } else {
+                vec![Fr::zero()]
+            }
+        })
+        .collect::<Vec<_>>();
+    let proofs = create_random_proof_batch(circuits, &params, &mut rng).unwrap();
+
+    let mut verifier = BatchVerifier::new(&pvk);
+    assert!(verifier.verify_all(&mut rng).unwrap());
+
+    for (proof, inputs) in proofs.iter().zip(inputs.iter()) {
+        verifier.queue(proof.clone(), inputs.clone());
+    }
+    assert_eq!(verifier.len(), 100);
+    assert!(verifier.verify_all(&mut rng).unwrap());
+    assert!(verifier.is_empty());
+
+    // One proof with the wrong public input spoils the whole batch.
+    for (i, (proof, inputs)) in proofs.iter().zip(inputs.iter()).enumerate() {
+        let mut inputs = inputs.clone();
+        if i == 42 {
+            inputs[0] += Fr::one();
+        }
+        verifier.queue(proof.clone(), inputs);
+    }
+    assert!(!verifier.verify_all(&mut rng).unwrap());
+}
+
+#[derive(Clone)]
+struct SquareDemo<E: Engine> {
+    x: Option<E::Fr>,
+}
+
+impl<E: Engine> Circuit<E> for SquareDemo<E> {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let x = cs.alloc(|| "x", || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+        let xx = cs.alloc(
+            || "xx",
+            || {
+                self.x
+                    .map(|x| x.square())
+                    .ok_or(SynthesisError::AssignmentMissing)
+            },
+        )?;
+
+        cs.enforce(|| "xx = x * x", |lc| lc + x, |lc| lc + x, |lc| lc + xx);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_verify_no_inputs() {
+    use crate::groth16::{create_random_proof, generate_random_parameters, verify_proofs_batch};
+    use blstrs::{Bls12, G1Projective, Scalar as Fr};
+    use group::prime::PrimeCurveAffine;
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params =
+        generate_random_parameters::<Bls12, _, _>(SquareDemo { x: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+    assert_eq!(pvk.ic.len(), 1);
+
+    let proof = create_random_proof(
+        SquareDemo {
+            x: Some(Fr::from(3u64)),
+        },
+        &params,
+        &mut rng,
+    )
+    .unwrap();
+
+    let mut bad_proof = proof.clone();
+    bad_proof.c = (bad_proof.c.to_curve() + G1Projective::generator()).to_affine();
+
+    for (proof, valid) in &[(proof, true), (bad_proof, false)] {
+        // Batches of more than one proof always go through the multiexp.
+        let batch =
+            verify_proofs_batch(&pvk, &mut rng, &[proof, proof], &[vec![], vec![]]).unwrap();
+        assert_eq!(batch, *valid);
+        assert_eq!(verify_proof(&pvk, proof, &[]).unwrap(), batch);
+    }
+}
+
+#[test]
+fn test_verify_batch_mixed() {
+    use crate::groth16::{
+        create_random_proof, generate_random_parameters, verify_proofs_batch_mixed,
+    };
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let xor = XorDemo::<Bls12> {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+    let xor_params = generate_random_parameters::<Bls12, _, _>(
+        XorDemo {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let xor_pvk = prepare_verifying_key(&xor_params.vk);
+
+    let square_params =
+        generate_random_parameters::<Bls12, _, _>(SquareDemo { x: None }, &mut rng).unwrap();
+    let square_pvk = prepare_verifying_key(&square_params.vk);
+
+    let xor_proofs = (0..2)
+        .map(|_| create_random_proof(xor.clone(), &xor_params, &mut rng).unwrap())
+        .collect::<Vec<_>>();
+    let square_proofs = (1..4)
+        .map(|x| {
+            let c = SquareDemo {
+                x: Some(Fr::from(x)),
+            };
+            create_random_proof(c, &square_params, &mut rng).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let pvks = [&xor_pvk, &square_pvk, &xor_pvk, &square_pvk, &square_pvk];
+    let proofs = [
+        &xor_proofs[0],
+        &square_proofs[0],
+        &xor_proofs[1],
+        &square_proofs[1],
+        &square_proofs[2],
+    ];
+    let inputs = vec![vec![Fr::one()], vec![], vec![Fr::one()], vec![], vec![]];
+
+    assert!(verify_proofs_batch_mixed(&pvks, &mut rng, &proofs, &inputs).unwrap());
+
+    // wrong public input for one of the proofs
+    let mut bad_inputs = inputs.clone();
+    bad_inputs[2][0] = Fr::zero();
+    assert!(!verify_proofs_batch_mixed(&pvks, &mut rng, &proofs, &bad_inputs).unwrap());
+
+    // proofs for the same key can be reordered
+    let mut swapped = proofs;
+    swapped.swap(3, 4);
+    assert!(verify_proofs_batch_mixed(&pvks, &mut rng, &swapped, &inputs).unwrap());
+
+    // a valid proof, checked against the wrong key
+    swapped[1] = &xor_proofs[0];
+    assert!(!verify_proofs_batch_mixed(&pvks, &mut rng, &swapped, &inputs).unwrap());
+
+    // mismatched lengths
+    assert!(matches!(
+        verify_proofs_batch_mixed(&pvks[1..], &mut rng, &proofs, &inputs),
+        Err(SynthesisError::MalformedVerifyingKey)
+    ));
+    assert!(matches!(
+        verify_proofs_batch_mixed(&pvks, &mut rng, &proofs, &bad_inputs[1..]),
+        Err(SynthesisError::MalformedVerifyingKey)
+    ));
+}
+
+#[test]
+fn test_create_proof_deterministic() {
+    use crate::groth16::{create_proof_deterministic, generate_random_parameters};
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let c = XorDemo::<Bls12> {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+    let r = Fr::random(&mut rng);
+    let s = Fr::random(&mut rng);
+
+    let mut first = vec![];
+    let proof = create_proof_deterministic(c.clone(), &params, r, s).unwrap();
+    proof.write(&mut first).unwrap();
+    assert!(verify_proof(&pvk, &proof, &[Fr::one()]).unwrap());
+
+    let mut second = vec![];
+    create_proof_deterministic(c.clone(), &params, r, s)
+        .unwrap()
+        .write(&mut second)
+        .unwrap();
+    assert_eq!(first, second);
+
+    let mut other = vec![];
+    create_proof_deterministic(c, &params, r, s + Fr::one())
+        .unwrap()
+        .write(&mut other)
+        .unwrap();
+    assert_ne!(first, other);
+}
+
+/// Records the constraints of a circuit, to check them against an assignment.
+struct ConstraintRecorder<E: Engine> {
+    num_inputs: usize,
+    num_aux: usize,
+    constraints: Vec<(
+        LinearCombination<E>,
+        LinearCombination<E>,
+        LinearCombination<E>,
+    )>,
+}
+
+impl<E: Engine> ConstraintSystem<E> for ConstraintRecorder<E> {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_aux += 1;
+        Ok(Variable::new_unchecked(Index::Aux(self.num_aux - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_inputs += 1;
+        Ok(Variable::new_unchecked(Index::Input(self.num_inputs - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.constraints.push((
+            a(LinearCombination::zero()),
+            b(LinearCombination::zero()),
+            c(LinearCombination::zero()),
+        ));
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+#[test]
+fn test_create_proof_with_assignment() {
+    use crate::groth16::{create_proof_with_assignment, generate_random_parameters};
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let c = XorDemo::<Bls12> {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+    let r = Fr::random(&mut rng);
+    let s = Fr::random(&mut rng);
+
+    let (proof, inputs, aux) = create_proof_with_assignment(c.clone(), &params, r, s).unwrap();
+    assert!(verify_proof(&pvk, &proof, &inputs[1..]).unwrap());
+    assert!(proof == create_proof(c.clone(), &params, r, s).unwrap());
+
+    let mut recorder = ConstraintRecorder {
+        num_inputs: 1,
+        num_aux: 0,
+        constraints: vec![],
+    };
+    c.synthesize(&mut recorder).unwrap();
+
+    assert_eq!(inputs, vec![Fr::one(), Fr::one()]);
+    assert_eq!(inputs.len(), recorder.num_inputs);
+    assert_eq!(aux.len(), recorder.num_aux);
+    assert!(!recorder.constraints.is_empty());
+    for (a, b, c) in &recorder.constraints {
+        let a = a.eval(&inputs, &aux);
+        let b = b.eval(&inputs, &aux);
+        let c = c.eval(&inputs, &aux);
+        assert_eq!(a * b - c, Fr::zero());
+    }
+}
+
+#[test]
+fn test_prepared_verifying_key_read_write() {
+    use crate::groth16::{create_random_proof, generate_random_parameters, PreparedVerifyingKey};
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let mut bytes = vec![];
+    pvk.write(&mut bytes).unwrap();
+    let read = PreparedVerifyingKey::<Bls12>::read(&bytes[..]).unwrap();
+
+    let mut rewritten = vec![];
+    read.write(&mut rewritten).unwrap();
+    assert_eq!(bytes, rewritten);
+
+    let serialized = serde_json::to_string(&pvk).unwrap();
+    let deserialized: PreparedVerifyingKey<Bls12> = serde_json::from_str(&serialized).unwrap();
+
+    let c = XorDemo::<Bls12> {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+    let proof = create_random_proof(c, &params, &mut rng).unwrap();
+
+    for inputs in &[[Fr::one()], [Fr::zero()]] {
+        let expected = verify_proof(&pvk, &proof, inputs).unwrap();
+        assert_eq!(verify_proof(&read, &proof, inputs).unwrap(), expected);
+        assert_eq!(
+            verify_proof(&deserialized, &proof, inputs).unwrap(),
+            expected
+        );
+    }
+    assert!(verify_proof(&read, &proof, &[Fr::one()]).unwrap());
+
+    // A corrupted `ic` point is rejected.
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    assert!(PreparedVerifyingKey::<Bls12>::read(&bytes[..]).is_err());
+}
+
+#[test]
+fn test_serde_round_trip() {
+    use crate::groth16::{
+        create_random_proof, generate_random_parameters, Parameters, Proof, VerifyingKey,
+    };
+    use blstrs::{Bls12, Scalar as Fr};
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+    let c = XorDemo::<Bls12> {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+    let proof = create_random_proof(c, &params, &mut rng).unwrap();
+
+    // JSON
+    let json_params: Parameters<Bls12> =
+        serde_json::from_str(&serde_json::to_string(&params).unwrap()).unwrap();
+    let json_vk: VerifyingKey<Bls12> =
+        serde_json::from_str(&serde_json::to_string(&params.vk).unwrap()).unwrap();
+    let json_proof: Proof<Bls12> =
+        serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+    assert!(json_params == params);
+    assert!(json_vk == params.vk);
+    assert!(json_proof == proof);
+
+    let pvk = prepare_verifying_key(&json_vk);
+    assert!(verify_proof(&pvk, &json_proof, &[Fr::one()]).unwrap());
+
+    // Bincode
+    let bincode_params: Parameters<Bls12> =
+        bincode::deserialize(&bincode::serialize(&params).unwrap()).unwrap();
+    let vk_bytes = bincode::serialize(&params.vk).unwrap();
+    let bincode_vk: VerifyingKey<Bls12> = bincode::deserialize(&vk_bytes).unwrap();
+    let proof_bytes = bincode::serialize(&proof).unwrap();
+    let bincode_proof: Proof<Bls12> = bincode::deserialize(&proof_bytes).unwrap();
+    assert!(bincode_params == params);
+    assert!(bincode_vk == params.vk);
+    assert!(bincode_proof == proof);
+
+    let pvk = prepare_verifying_key(&bincode_vk);
+    assert!(verify_proof(&pvk, &bincode_proof, &[Fr::one()]).unwrap());
+
+    // Points are compressed, 48 bytes in G1 and 96 bytes in G2, after the 8
+    // byte length prefix of bincode.
+    assert_eq!(
+        vk_bytes.len(),
+        8 + 3 * 48 + 3 * 96 + 4 + 48 * params.vk.ic.len()
+    );
+    let g1_len = params.h.len() + params.l.len() + params.a.len() + params.b_g1.len();
+    assert_eq!(
+        bincode::serialize(&params).unwrap().len(),
+        vk_bytes.len() + 5 * 4 + 48 * g1_len + 96 * params.b_g2.len()
+    );
+
+    // Points which are not on the curve or not in the subgroup are rejected.
+    // Bincode prefixes the encoding with its 8 byte length.
+    let mut bad_vk_bytes = vk_bytes;
+    bad_vk_bytes[8 + 20] ^= 1;
+    assert!(bincode::deserialize::<VerifyingKey<Bls12>>(&bad_vk_bytes).is_err());
+
+    let mut bad_proof_bytes = proof_bytes;
+    bad_proof_bytes[8 + 20] ^= 1;
+    assert!(bincode::deserialize::<Proof<Bls12>>(&bad_proof_bytes).is_err());
+
+    // So are trailing bytes.
+    let mut long_proof = vec![];
+    proof.write(&mut long_proof).unwrap();
+    long_proof.push(0);
+    assert!(
+        bincode::deserialize::<Proof<Bls12>>(&bincode::serialize(&long_proof).unwrap()).is_err()
+    );
+}
+
+#[test]
+fn test_verifying_key_hex() {
+    use crate::groth16::{generate_random_parameters, VerifyingKey};
+    use blstrs::Bls12;
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = XorDemo::<Bls12> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+
+    let hex = params.vk.to_hex();
+    assert_eq!(VerifyingKey::<Bls12>::from_hex(&hex).unwrap(), params.vk);
+
+    // Not hex, truncated, and trailing bytes.
+    assert!(VerifyingKey::<Bls12>::from_hex(&format!("{}zz", hex)).is_err());
+    assert!(VerifyingKey::<Bls12>::from_hex(&hex[..hex.len() - 2]).is_err());
+    assert!(VerifyingKey::<Bls12>::from_hex(&format!("{}00", hex)).is_err());
+}
+
+#[test]
+fn test_witness_cs_inputs() {
+    use crate::util_cs::witness_cs::WitnessCS;
+
+    let g1 = Fr::one();
+    let g2 = Fr::one();
+    let alpha = Fr::from(48577u64);
+    let beta = Fr::from(22580u64);
+    let gamma = Fr::from(53332u64);
+    let delta = Fr::from(5481u64);
+    let tau = Fr::from(3673u64);
+
+    let params = {
+        let c = XorDemo::<DummyEngine> {
+            a: None,
+            b: None,
+            _marker: PhantomData,
+        };
+
+        generate_parameters(c, g1, g2, alpha, beta, gamma, delta, tau).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+
+    for &(a, b) in &[(false, false), (false, true), (true, false), (true, true)] {
+        let c = XorDemo {
+            a: Some(a),
+            b: Some(b),
+            _marker: PhantomData,
+        };
+
+        let mut cs = WitnessCS::<DummyEngine>::new();
+        c.clone().synthesize(&mut cs).unwrap();
+        assert_eq!(cs.input_assignment()[0], Fr::one());
+        assert_eq!(cs.aux_assignment().len(), 2);
+        assert_eq!(cs.input_assignment().len(), 2);
+
+        // The inputs derived without the constraints are the ones the prover
+        // used, so they verify the proof.
+        let proof = create_proof(c, &params, Fr::from(27134u64), Fr::from(17146u64)).unwrap();
+        assert!(verify_proof(&pvk, &proof, &cs.input_assignment()[1..]).unwrap());
+    }
+}
+
+#[test]
+fn test_synthesis_mode() {
+    use crate::groth16::{create_random_proof, generate_random_parameters};
+    use crate::util_cs::metric_cs::MetricCS;
+    use crate::util_cs::test_cs::TestConstraintSystem;
+    use crate::SynthesisMode;
+    use blstrs::{Bls12, Scalar as Fr};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Proves knowledge of `x` such that `x^2` is the public input, only
+    /// computing the witness, standing in for a costly one, when it is going
+    /// to be used.
+    #[derive(Clone)]
+    struct CostlyWitness {
+        x: Option<Fr>,
+        computations: Arc<AtomicUsize>,
+    }
+
+    impl Circuit<Bls12> for CostlyWitness {
+        fn synthesize<CS: ConstraintSystem<Bls12>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            // The mode is the same in namespaces.
+            let mode = cs.namespace(|| "mode").mode();
+            assert_eq!(mode, cs.mode());
+
+            let out = match (cs.mode(), self.x) {
+                (SynthesisMode::Prove, Some(x)) => {
+                    self.computations.fetch_add(1, Ordering::SeqCst);
+                    Some(x.square())
+                }
+                _ => None,
+            };
+
+            let x = cs.alloc(|| "x", || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let out = cs.alloc_input(|| "out", || out.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce(|| "x^2 = out", |lc| lc + x, |lc| lc + x, |lc| lc + out);
+
+            Ok(())
+        }
+    }
+
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let computations = Arc::new(AtomicUsize::new(0));
+    let x = Fr::from(3u64);
+    let c = CostlyWitness {
+        x: Some(x),
+        computations: computations.clone(),
+    };
+
+    // Setup with the witness at hand still skips computing it.
+    let params = generate_random_parameters::<Bls12, _, _>(c.clone(), &mut rng).unwrap();
+    assert_eq!(computations.load(Ordering::SeqCst), 0);
+    let mut cs = MetricCS::<Bls12>::new();
+    c.clone().synthesize(&mut cs).unwrap();
+    assert_eq!(computations.load(Ordering::SeqCst), 0);
+
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    assert_eq!(cs.mode(), SynthesisMode::Prove);
+    c.clone().synthesize(&mut cs).unwrap();
+    assert_eq!(computations.load(Ordering::SeqCst), 1);
+
+    let proof = create_random_proof(c, &params, &mut rng).unwrap();
+    assert_eq!(computations.load(Ordering::SeqCst), 2);
+    let pvk = prepare_verifying_key(&params.vk);
+    assert!(verify_proof(&pvk, &proof, &[x.square()]).unwrap());
+}
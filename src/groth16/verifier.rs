@@ -15,23 +15,14 @@ pub fn prepare_verifying_key<E: Engine + MultiMillerLoop>(
 where
     E: MultiMillerLoop,
 {
-    let neg_gamma = -vk.gamma_g2;
-    let neg_delta = -vk.delta_g2;
-
-    let multiscalar = multiscalar::precompute_fixed_window(&vk.ic, multiscalar::WINDOW_SIZE);
-
-    PreparedVerifyingKey {
-        alpha_g1_beta_g2: E::pairing(&vk.alpha_g1, &vk.beta_g2),
-        neg_gamma_g2: neg_gamma.into(),
-        neg_delta_g2: neg_delta.into(),
-        gamma_g2: vk.gamma_g2.into(),
-        delta_g2: vk.delta_g2.into(),
-        ic: vk.ic.clone(),
-        multiscalar,
-        alpha_g1: vk.alpha_g1.to_curve(),
-        beta_g2: vk.beta_g2.into(),
-        ic_projective: vk.ic.par_iter().map(|i| i.to_curve()).collect(),
-    }
+    PreparedVerifyingKey::from_parts(
+        E::pairing(&vk.alpha_g1, &vk.beta_g2),
+        vk.alpha_g1,
+        vk.beta_g2,
+        vk.gamma_g2,
+        vk.delta_g2,
+        vk.ic.clone(),
+    )
 }
 
 /// Verify a single Proof.
@@ -78,19 +69,26 @@ where
         s.spawn(move |_| *ml_all = E::multi_miller_loop(&[(&proof.c, &pvk.neg_delta_g2)]));
 
         // - Accumulate inputs (on the current thread)
-        let subset = pvk.multiscalar.at_point(1);
-        let public_inputs_repr: Vec<_> = public_inputs.iter().map(PrimeField::to_repr).collect();
-
-        let mut acc = multiscalar::par_multiscalar::<&multiscalar::Getter<E::G1Affine>, E::G1Affine>(
-            &multiscalar::ScalarList::Slice(&public_inputs_repr),
-            &subset,
-            std::mem::size_of::<<E::Fr as PrimeField>::Repr>() * 8,
-        );
+        let acc_aff = if public_inputs.is_empty() {
+            // Only the implicit ONE input, so there is nothing to multiply.
+            pvk.ic[0]
+        } else {
+            let subset = pvk.multiscalar.at_point(1);
+            let public_inputs_repr: Vec<_> =
+                public_inputs.iter().map(PrimeField::to_repr).collect();
+
+            let mut acc =
+                multiscalar::par_multiscalar::<&multiscalar::Getter<E::G1Affine>, E::G1Affine>(
+                    &multiscalar::ScalarList::Slice(&public_inputs_repr),
+                    &subset,
+                    std::mem::size_of::<<E::Fr as PrimeField>::Repr>() * 8,
+                );
 
-        acc.add_assign(&pvk.ic[0]);
+            acc.add_assign(&pvk.ic[0]);
+            acc.to_affine()
+        };
 
         // Calculate ML inputs * (-gamma)
-        let acc_aff = acc.to_affine();
         ml_acc = E::multi_miller_loop(&[(&acc_aff, &pvk.neg_gamma_g2)]);
     });
     // Wait for the threaded miller loops to finish
@@ -105,6 +103,56 @@ where
     Ok(actual == pvk.alpha_g1_beta_g2)
 }
 
+/// Verify a single Proof whose public inputs are signed integers.
+///
+/// Each input is mapped to its field representative, so that a negative `x`
+/// becomes `p - |x|`, before verifying with [`verify_proof`].
+pub fn verify_proof_signed<'a, E>(
+    pvk: &'a PreparedVerifyingKey<E>,
+    proof: &Proof<E>,
+    signed_inputs: &[i64],
+) -> Result<bool, SynthesisError>
+where
+    E: MultiMillerLoop,
+    <<E as Engine>::Fr as PrimeField>::Repr: Sync,
+{
+    let public_inputs = signed_inputs
+        .iter()
+        .map(|&x| {
+            let abs = E::Fr::from(x.unsigned_abs());
+            if x < 0 {
+                -abs
+            } else {
+                abs
+            }
+        })
+        .collect::<Vec<_>>();
+
+    verify_proof(pvk, proof, &public_inputs)
+}
+
+/// Samples a random 128-bit scalar used to combine proofs in batch verification.
+fn random_batch_scalar<E: Engine, R: rand::RngCore>(rng: &mut R) -> E::Fr {
+    use rand::Rng;
+
+    let t: u128 = rng.gen();
+
+    let mut repr = E::Fr::zero().to_repr();
+    let mut repr_u64s = le_bytes_to_u64s(&repr.as_ref());
+    assert!(repr_u64s.len() > 1);
+
+    repr_u64s[0] = (t & (-1i64 as u128) >> 64) as u64;
+    repr_u64s[1] = (t >> 64) as u64;
+
+    for (i, limb) in repr_u64s.iter().enumerate() {
+        let start = i * 8;
+        let stop = start + 8;
+        repr.as_mut()[start..stop].copy_from_slice(&limb.to_le_bytes());
+    }
+
+    E::Fr::from_repr(repr).unwrap()
+}
+
 /// Randomized batch verification - see Appendix B.2 in Zcash spec
 pub fn verify_proofs_batch<'a, E, R>(
     pvk: &'a PreparedVerifyingKey<E>,
@@ -140,24 +188,7 @@ where
     let mut accum_y = E::Fr::zero();
 
     for _ in 0..proof_num {
-        use rand::Rng;
-
-        let t: u128 = rng.gen();
-
-        let mut repr = E::Fr::zero().to_repr();
-        let mut repr_u64s = le_bytes_to_u64s(&repr.as_ref());
-        assert!(repr_u64s.len() > 1);
-
-        repr_u64s[0] = (t & (-1i64 as u128) >> 64) as u64;
-        repr_u64s[1] = (t >> 64) as u64;
-
-        for (i, limb) in repr_u64s.iter().enumerate() {
-            let start = i * 8;
-            let stop = start + 8;
-            repr.as_mut()[start..stop].copy_from_slice(&limb.to_le_bytes());
-        }
-
-        let fr = E::Fr::from_repr(repr).unwrap();
+        let fr = random_batch_scalar::<E, _>(rng);
         let repr = fr.to_repr();
 
         // calculate sum
@@ -274,3 +305,140 @@ where
     let actual = ml_all.final_exponentiation();
     Ok(actual == y)
 }
+
+/// Randomized batch verification of proofs for different circuits.
+///
+/// `pvks[j]` is the verifying key for `proofs[j]` and `public_inputs[j]`, so all
+/// three slices must have the same length. Proofs sharing a verifying key (the
+/// same reference) are combined into a single gamma and delta term, and all
+/// pairings are computed in one multi Miller loop.
+pub fn verify_proofs_batch_mixed<E, R>(
+    pvks: &[&PreparedVerifyingKey<E>],
+    rng: &mut R,
+    proofs: &[&Proof<E>],
+    public_inputs: &[Vec<E::Fr>],
+) -> Result<bool, SynthesisError>
+where
+    E: MultiMillerLoop,
+    R: rand::RngCore,
+{
+    if pvks.len() != proofs.len() || proofs.len() != public_inputs.len() {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+
+    for (pvk, pub_input) in pvks.iter().zip(public_inputs) {
+        if (pub_input.len() + 1) != pvk.ic.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+    }
+
+    // Group the proofs by verifying key, in order of first appearance.
+    let mut groups: Vec<(&PreparedVerifyingKey<E>, Vec<usize>)> = Vec::new();
+    for (j, pvk) in pvks.iter().enumerate() {
+        match groups.iter_mut().find(|(g, _)| std::ptr::eq(*g, *pvk)) {
+            Some((_, indices)) => indices.push(j),
+            None => groups.push((pvk, vec![j])),
+        }
+    }
+
+    let rand_z: Vec<E::Fr> = (0..proofs.len())
+        .map(|_| random_batch_scalar::<E, _>(rng))
+        .collect();
+
+    let mut g1 = Vec::with_capacity(proofs.len() + 2 * groups.len());
+    let mut g2 = Vec::with_capacity(proofs.len() + 2 * groups.len());
+    // Y^-Accum_Y, over all verifying keys
+    let mut y = <E as Engine>::Gt::identity();
+
+    for (pvk, indices) in &groups {
+        let mut acc_gamma = E::G1::identity();
+        let mut acc_delta = E::G1::identity();
+        let mut accum_y = E::Fr::zero();
+
+        for &j in indices {
+            let z = rand_z[j];
+
+            // ic_0 + \sum a_j,i * ic_i
+            let mut acc = pvk.ic_projective[0];
+            for (base, input) in pvk.ic_projective.iter().skip(1).zip(&public_inputs[j]) {
+                acc += *base * input;
+            }
+
+            acc_gamma += acc * z;
+            acc_delta += proofs[j].c.to_curve() * z;
+            accum_y += z;
+        }
+
+        g1.push(acc_gamma.to_affine());
+        g2.push(pvk.gamma_g2.clone());
+        g1.push(acc_delta.to_affine());
+        g2.push(pvk.delta_g2.clone());
+
+        y += pvk.alpha_g1_beta_g2 * -accum_y;
+    }
+
+    for (proof, z) in proofs.iter().zip(&rand_z) {
+        // ([z_j] pi_j,A, -pi_j,B)
+        g1.push(proof.a.mul(*z).to_affine());
+        g2.push((-proof.b.to_curve()).to_affine().into());
+    }
+
+    let terms: Vec<_> = g1.iter().zip(g2.iter()).collect();
+    let actual = E::multi_miller_loop(&terms).final_exponentiation();
+
+    Ok(actual == y)
+}
+
+/// Collects proofs for the same verifying key and checks all of them at once,
+/// using a single random linear combination (see [`verify_proofs_batch`]).
+pub struct BatchVerifier<'a, E>
+where
+    E: MultiMillerLoop,
+{
+    pvk: &'a PreparedVerifyingKey<E>,
+    proofs: Vec<Proof<E>>,
+    public_inputs: Vec<Vec<E::Fr>>,
+}
+
+impl<'a, E> BatchVerifier<'a, E>
+where
+    E: MultiMillerLoop,
+    <E::Fr as PrimeField>::Repr: Sync + Copy,
+{
+    pub fn new(pvk: &'a PreparedVerifyingKey<E>) -> Self {
+        BatchVerifier {
+            pvk,
+            proofs: Vec::new(),
+            public_inputs: Vec::new(),
+        }
+    }
+
+    /// Adds a proof and its public inputs to the next check.
+    pub fn queue(&mut self, proof: Proof<E>, public_inputs: Vec<E::Fr>) {
+        self.proofs.push(proof);
+        self.public_inputs.push(public_inputs);
+    }
+
+    /// Number of proofs waiting to be verified.
+    pub fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
+    }
+
+    /// Verifies all queued proofs, returning `true` only if every one of them is
+    /// valid. The queue is emptied, whatever the result.
+    pub fn verify_all<R: rand::RngCore>(&mut self, rng: &mut R) -> Result<bool, SynthesisError> {
+        if self.proofs.is_empty() {
+            return Ok(true);
+        }
+
+        let proofs = std::mem::replace(&mut self.proofs, Vec::new());
+        let public_inputs = std::mem::replace(&mut self.public_inputs, Vec::new());
+        let proofs = proofs.iter().collect::<Vec<_>>();
+
+        verify_proofs_batch(self.pvk, rng, &proofs, &public_inputs)
+    }
+}
@@ -1,12 +1,20 @@
-use group::{prime::PrimeCurveAffine, UncompressedEncoding};
+use blstrs::Compress;
+use group::{prime::PrimeCurveAffine, Curve, GroupEncoding, UncompressedEncoding};
 use pairing::{Engine, MultiMillerLoop};
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use memmap::Mmap;
+use rayon::prelude::*;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 use std::io::{self, Read, Write};
 use std::mem;
 
 use super::multiscalar;
+use super::proof::{
+    decode_hex, deserialize_bytes, ensure_consumed, read_compressed_point, read_compressed_points,
+    write_compressed_points,
+};
+use crate::SynthesisError;
 
 #[derive(Debug, Clone)]
 pub struct VerifyingKey<E: Engine + MultiMillerLoop> {
@@ -48,6 +56,27 @@ impl<E: Engine + MultiMillerLoop> PartialEq for VerifyingKey<E> {
     }
 }
 
+/// Serialized as the bytes written by `write_compressed`.
+impl<E: Engine + MultiMillerLoop> Serialize for VerifyingKey<E> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut v = vec![];
+        self.write_compressed(&mut v).map_err(ser::Error::custom)?;
+        s.serialize_bytes(&v)
+    }
+}
+
+/// Rejects points which are not on the curve or not in the prime order subgroup.
+impl<'de, E: Engine + MultiMillerLoop> Deserialize<'de> for VerifyingKey<E> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let bytes = deserialize_bytes(d)?;
+        let mut reader = &bytes[..];
+        let vk = VerifyingKey::read_compressed(&mut reader).map_err(de::Error::custom)?;
+        ensure_consumed(reader)?;
+
+        Ok(vk)
+    }
+}
+
 fn read_uncompressed_point<C: UncompressedEncoding>(repr: &C::Uncompressed) -> io::Result<C> {
     let opt = C::from_uncompressed(repr);
     Option::from(opt).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not on curve"))
@@ -69,6 +98,52 @@ impl<E: Engine + MultiMillerLoop> VerifyingKey<E> {
         Ok(())
     }
 
+    /// Writes the key like `write`, but with compressed points. This is half
+    /// the size, but slower to read back since square roots are needed.
+    pub fn write_compressed<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(self.alpha_g1.to_bytes().as_ref())?;
+        writer.write_all(self.beta_g1.to_bytes().as_ref())?;
+        writer.write_all(self.beta_g2.to_bytes().as_ref())?;
+        writer.write_all(self.gamma_g2.to_bytes().as_ref())?;
+        writer.write_all(self.delta_g1.to_bytes().as_ref())?;
+        writer.write_all(self.delta_g2.to_bytes().as_ref())?;
+        write_compressed_points(&mut writer, &self.ic)
+    }
+
+    /// Reads a key written by `write_compressed`, rejecting points which are
+    /// not in the prime order subgroup and points at infinity.
+    pub fn read_compressed<R: Read>(mut reader: R) -> io::Result<Self> {
+        Ok(VerifyingKey {
+            alpha_g1: read_compressed_point(&mut reader)?,
+            beta_g1: read_compressed_point(&mut reader)?,
+            beta_g2: read_compressed_point(&mut reader)?,
+            gamma_g2: read_compressed_point(&mut reader)?,
+            delta_g1: read_compressed_point(&mut reader)?,
+            delta_g2: read_compressed_point(&mut reader)?,
+            ic: read_compressed_points(&mut reader)?,
+        })
+    }
+
+    /// Returns the hex encoding of the bytes written by `write`.
+    pub fn to_hex(&self) -> String {
+        let mut bytes = vec![];
+        self.write(&mut bytes)
+            .expect("writing to a vec never fails");
+        hex::encode(bytes)
+    }
+
+    /// Reads a verifying key from the hex encoding of the bytes written by `write`.
+    pub fn from_hex(s: &str) -> Result<Self, SynthesisError> {
+        let bytes = decode_hex(s)?;
+        let mut reader = &bytes[..];
+        let vk = Self::read(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "trailing bytes").into());
+        }
+
+        Ok(vk)
+    }
+
     pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
         let mut g1_repr = <E::G1Affine as UncompressedEncoding>::Uncompressed::default();
         let mut g2_repr = <E::G2Affine as UncompressedEncoding>::Uncompressed::default();
@@ -217,4 +292,176 @@ where
     pub(crate) alpha_g1: E::G1,
     pub(crate) beta_g2: <E as MultiMillerLoop>::G2Prepared,
     pub(crate) ic_projective: Vec<E::G1>,
+
+    // The G2 elements the prepared forms were computed from, kept so that the
+    // key can be written out.
+    pub(crate) beta_g2_affine: E::G2Affine,
+    pub(crate) gamma_g2_affine: E::G2Affine,
+    pub(crate) delta_g2_affine: E::G2Affine,
+}
+
+impl<E: MultiMillerLoop> PreparedVerifyingKey<E> {
+    /// Builds the prepared key from the already computed `alpha_g1_beta_g2`
+    /// pairing and the points the rest of it is derived from.
+    pub(crate) fn from_parts(
+        alpha_g1_beta_g2: E::Gt,
+        alpha_g1: E::G1Affine,
+        beta_g2: E::G2Affine,
+        gamma_g2: E::G2Affine,
+        delta_g2: E::G2Affine,
+        ic: Vec<E::G1Affine>,
+    ) -> Self {
+        let neg_gamma = -gamma_g2;
+        let neg_delta = -delta_g2;
+
+        let multiscalar = multiscalar::precompute_fixed_window(&ic, multiscalar::WINDOW_SIZE);
+        let ic_projective = ic.par_iter().map(|i| i.to_curve()).collect();
+
+        PreparedVerifyingKey {
+            alpha_g1_beta_g2,
+            neg_gamma_g2: neg_gamma.into(),
+            neg_delta_g2: neg_delta.into(),
+            gamma_g2: gamma_g2.into(),
+            delta_g2: delta_g2.into(),
+            ic,
+            multiscalar,
+            alpha_g1: alpha_g1.to_curve(),
+            beta_g2: beta_g2.into(),
+            ic_projective,
+            beta_g2_affine: beta_g2,
+            gamma_g2_affine: gamma_g2,
+            delta_g2_affine: delta_g2,
+        }
+    }
+}
+
+impl<E> PreparedVerifyingKey<E>
+where
+    E: MultiMillerLoop,
+    E::Gt: Compress,
+{
+    /// Writes the precomputed `alpha_g1_beta_g2` pairing, followed by the points
+    /// the rest of the key is derived from: alpha in G1, beta, gamma and delta in
+    /// G2, and `ic`.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.alpha_g1_beta_g2.write_compressed(&mut writer)?;
+        writer.write_all(self.alpha_g1.to_affine().to_uncompressed().as_ref())?;
+        writer.write_all(self.beta_g2_affine.to_uncompressed().as_ref())?;
+        writer.write_all(self.gamma_g2_affine.to_uncompressed().as_ref())?;
+        writer.write_all(self.delta_g2_affine.to_uncompressed().as_ref())?;
+        writer.write_u32::<BigEndian>(self.ic.len() as u32)?;
+        for ic in &self.ic {
+            writer.write_all(ic.to_uncompressed().as_ref())?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a key written by `write`, without computing any pairings.
+    ///
+    /// Rejects points which are not on the curve or not in the prime order
+    /// subgroup, and `ic` elements at infinity.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut g1_repr = <E::G1Affine as UncompressedEncoding>::Uncompressed::default();
+        let mut g2_repr = <E::G2Affine as UncompressedEncoding>::Uncompressed::default();
+
+        let alpha_g1_beta_g2 = <E::Gt as Compress>::read_compressed(&mut reader)?;
+
+        reader.read_exact(g1_repr.as_mut())?;
+        let alpha_g1 = read_uncompressed_point(&g1_repr)?;
+
+        reader.read_exact(g2_repr.as_mut())?;
+        let beta_g2 = read_uncompressed_point(&g2_repr)?;
+
+        reader.read_exact(g2_repr.as_mut())?;
+        let gamma_g2 = read_uncompressed_point(&g2_repr)?;
+
+        reader.read_exact(g2_repr.as_mut())?;
+        let delta_g2 = read_uncompressed_point(&g2_repr)?;
+
+        let ic_len = reader.read_u32::<BigEndian>()? as usize;
+
+        let mut ic = vec![];
+
+        for _ in 0..ic_len {
+            reader.read_exact(g1_repr.as_mut())?;
+            let g1: E::G1Affine = read_uncompressed_point(&g1_repr)?;
+            if g1.is_identity().into() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "point at infinity",
+                ));
+            }
+            ic.push(g1);
+        }
+
+        Ok(Self::from_parts(
+            alpha_g1_beta_g2,
+            alpha_g1,
+            beta_g2,
+            gamma_g2,
+            delta_g2,
+            ic,
+        ))
+    }
+
+    /// Writes the key like `write`, but with compressed points.
+    pub fn write_compressed<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.alpha_g1_beta_g2.write_compressed(&mut writer)?;
+        writer.write_all(self.alpha_g1.to_affine().to_bytes().as_ref())?;
+        writer.write_all(self.beta_g2_affine.to_bytes().as_ref())?;
+        writer.write_all(self.gamma_g2_affine.to_bytes().as_ref())?;
+        writer.write_all(self.delta_g2_affine.to_bytes().as_ref())?;
+        write_compressed_points(&mut writer, &self.ic)
+    }
+
+    /// Reads a key written by `write_compressed`, with the same checks as
+    /// `read`.
+    pub fn read_compressed<R: Read>(mut reader: R) -> io::Result<Self> {
+        let alpha_g1_beta_g2 = <E::Gt as Compress>::read_compressed(&mut reader)?;
+        let alpha_g1 = read_compressed_point(&mut reader)?;
+        let beta_g2 = read_compressed_point(&mut reader)?;
+        let gamma_g2 = read_compressed_point(&mut reader)?;
+        let delta_g2 = read_compressed_point(&mut reader)?;
+        let ic = read_compressed_points(&mut reader)?;
+
+        Ok(Self::from_parts(
+            alpha_g1_beta_g2,
+            alpha_g1,
+            beta_g2,
+            gamma_g2,
+            delta_g2,
+            ic,
+        ))
+    }
+}
+
+/// Serialized as the bytes written by `write_compressed`, from which only the
+/// pairing-free parts of the key are recomputed.
+impl<E> Serialize for PreparedVerifyingKey<E>
+where
+    E: MultiMillerLoop,
+    E::Gt: Compress,
+{
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut v = vec![];
+        self.write_compressed(&mut v).map_err(ser::Error::custom)?;
+        s.serialize_bytes(&v)
+    }
+}
+
+/// Rejects points which are not on the curve or not in the prime order subgroup.
+impl<'de, E> Deserialize<'de> for PreparedVerifyingKey<E>
+where
+    E: MultiMillerLoop,
+    E::Gt: Compress,
+{
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let bytes = deserialize_bytes(d)?;
+        let mut reader = &bytes[..];
+        let pvk = PreparedVerifyingKey::read_compressed(&mut reader).map_err(de::Error::custom)?;
+        ensure_consumed(reader)?;
+
+        Ok(pvk)
+    }
 }
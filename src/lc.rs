@@ -1,4 +1,5 @@
-use std::ops::{Add, Sub};
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
 
 use ff::Field;
 use pairing::Engine;
@@ -6,7 +7,10 @@ use pairing::Engine;
 use crate::multiexp::DensityTracker;
 
 /// Represents a variable in our constraint system.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// Variables are ordered by their `Index`: all input variables come before all
+/// auxiliary variables, and variables of the same kind are ordered by index.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Variable(pub(crate) Index);
 
 impl Variable {
@@ -23,9 +27,25 @@ impl Variable {
     }
 }
 
+/// Formats the variable as its index, e.g. `Input(3)` or `Aux(7)`.
+impl fmt::Debug for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_unchecked() {
+            Index::Input(i) => write!(f, "Input({})", i),
+            Index::Aux(i) => write!(f, "Aux({})", i),
+        }
+    }
+}
+
 /// Represents the index of either an input variable or
 /// auxiliary variable.
-#[derive(Copy, Clone, PartialEq, Debug, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Debug, Eq, PartialOrd, Ord, Hash)]
 pub enum Index {
     Input(usize),
     Aux(usize),
@@ -122,6 +142,33 @@ impl<T> Indexer<T> {
     }
 }
 
+impl<T: Field> Indexer<T> {
+    /// Sorts the values by key, merging values with the same key and dropping zeroes.
+    pub fn normalize(&mut self) {
+        self.values.sort_by_key(|(key, _)| *key);
+
+        let mut normalized: Vec<(usize, T)> = Vec::with_capacity(self.values.len());
+        for (key, value) in self.values.drain(..) {
+            match normalized.last_mut() {
+                Some((last_key, last_value)) if *last_key == key => *last_value += value,
+                _ => normalized.push((key, value)),
+            }
+        }
+        normalized.retain(|(_, value)| !bool::from(value.is_zero()));
+
+        self.values = normalized;
+        self.last_inserted = None;
+    }
+
+    pub fn is_normalized(&self) -> bool {
+        self.values.windows(2).all(|w| w[0].0 < w[1].0)
+            && self
+                .values
+                .iter()
+                .all(|(_, value)| !bool::from(value.is_zero()))
+    }
+}
+
 impl<E: Engine> Default for LinearCombination<E> {
     fn default() -> Self {
         Self::zero()
@@ -153,6 +200,14 @@ impl<E: Engine> LinearCombination<E> {
         Self::from_coeff(var, E::Fr::one())
     }
 
+    /// Iterates over the `(variable, coefficient)` terms of the linear combination,
+    /// input variables first, each group in order of increasing index.
+    ///
+    /// Variables are not stored as such, so they are yielded by value.
+    pub fn iter_terms(&self) -> impl Iterator<Item = (Variable, &E::Fr)> + '_ {
+        self.iter()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (Variable, &E::Fr)> + '_ {
         self.inputs
             .iter()
@@ -229,15 +284,78 @@ impl<E: Engine> LinearCombination<E> {
         self
     }
 
+    /// Returns the number of terms in the linear combination.
     pub fn len(&self) -> usize {
         self.inputs.len() + self.aux.len()
     }
 
+    /// Returns true if the linear combination has no terms.
     pub fn is_empty(&self) -> bool {
         self.inputs.is_empty() && self.aux.is_empty()
     }
 
-    pub(crate) fn eval(
+    /// Multiplies every coefficient by `by`. The set of variables is left
+    /// unchanged, even when scaling by zero.
+    pub fn scale(&mut self, by: &E::Fr) {
+        for (_, coeff) in self.iter_mut() {
+            *coeff *= by;
+        }
+    }
+
+    /// Flips the sign of every coefficient.
+    pub fn negate(&mut self) {
+        for (_, coeff) in self.iter_mut() {
+            *coeff = -*coeff;
+        }
+    }
+
+    /// Merges terms of the same variable by summing their coefficients, and drops
+    /// terms whose coefficient is zero.
+    pub fn normalize(&mut self) {
+        self.inputs.normalize();
+        self.aux.normalize();
+    }
+
+    /// Returns true if every variable appears at most once, with a non-zero
+    /// coefficient.
+    pub fn is_normalized(&self) -> bool {
+        self.inputs.is_normalized() && self.aux.is_normalized()
+    }
+
+    /// Evaluates the linear combination against the given assignment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a variable's index is out of range of `inputs` or `aux`.
+    pub fn eval(&self, inputs: &[E::Fr], aux: &[E::Fr]) -> E::Fr {
+        let mut acc = E::Fr::zero();
+
+        for (index, coeff) in self.iter_inputs() {
+            let value = inputs.get(*index).unwrap_or_else(|| {
+                panic!(
+                    "input variable {} is out of range ({} inputs given)",
+                    index,
+                    inputs.len()
+                )
+            });
+            acc += *value * coeff;
+        }
+
+        for (index, coeff) in self.iter_aux() {
+            let value = aux.get(*index).unwrap_or_else(|| {
+                panic!(
+                    "aux variable {} is out of range ({} aux values given)",
+                    index,
+                    aux.len()
+                )
+            });
+            acc += *value * coeff;
+        }
+
+        acc
+    }
+
+    pub(crate) fn eval_with_density(
         &self,
         mut input_density: Option<&mut DensityTracker>,
         mut aux_density: Option<&mut DensityTracker>,
@@ -280,6 +398,28 @@ impl<E: Engine> LinearCombination<E> {
     }
 }
 
+/// Formats the normalized terms by raw variable index, e.g.
+/// `LinearCombination(c0 * Input(0) + c1 * Aux(2))`, without needing a
+/// constraint system to name the variables.
+impl<E: Engine> fmt::Debug for LinearCombination<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lc = self.clone();
+        lc.normalize();
+
+        let mut terms = lc.iter().collect::<Vec<_>>();
+        terms.sort_by_key(|(var, _)| *var);
+
+        write!(f, "LinearCombination(")?;
+        for (i, (var, coeff)) in terms.iter().enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{:?} * {:?}", coeff, var)?;
+        }
+        write!(f, ")")
+    }
+}
+
 impl<E: Engine> Add<(E::Fr, Variable)> for LinearCombination<E> {
     type Output = LinearCombination<E>;
 
@@ -297,6 +437,24 @@ impl<E: Engine> Sub<(E::Fr, Variable)> for LinearCombination<E> {
     }
 }
 
+impl<E: Engine> Mul<E::Fr> for LinearCombination<E> {
+    type Output = LinearCombination<E>;
+
+    fn mul(mut self, by: E::Fr) -> LinearCombination<E> {
+        self.scale(&by);
+        self
+    }
+}
+
+impl<E: Engine> Neg for LinearCombination<E> {
+    type Output = LinearCombination<E>;
+
+    fn neg(mut self) -> LinearCombination<E> {
+        self.negate();
+        self
+    }
+}
+
 impl<E: Engine> Add<Variable> for LinearCombination<E> {
     type Output = LinearCombination<E>;
 
@@ -382,6 +540,31 @@ mod tests {
     use super::*;
     use blstrs::Bls12;
 
+    #[test]
+    fn test_debug() {
+        type Fr = <Bls12 as Engine>::Fr;
+
+        let a = Variable::new_unchecked(Index::Aux(2));
+        let x = Variable::new_unchecked(Index::Input(1));
+        let lc = LinearCombination::<Bls12>::zero()
+            .add_unsimplified((Fr::from(3u64), a))
+            .add_unsimplified((Fr::one(), x))
+            .add_unsimplified((Fr::from(2u64), a));
+
+        assert_eq!(
+            format!("{:?}", lc),
+            format!(
+                "LinearCombination({:?} * Input(1) + {:?} * Aux(2))",
+                Fr::one(),
+                Fr::from(5u64)
+            )
+        );
+
+        // Terms that cancel out are dropped.
+        let lc = lc - (Fr::from(5u64), a) - x;
+        assert_eq!(format!("{:?}", lc), "LinearCombination()");
+    }
+
     #[test]
     fn test_add_simplify() {
         let n = 5;
@@ -410,6 +593,166 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_iter_terms() {
+        let fr = |v: u64| <Bls12 as Engine>::Fr::from(v);
+        let a = Variable::new_unchecked(Index::Aux(0));
+        let b = Variable::new_unchecked(Index::Aux(1));
+        let x = Variable::new_unchecked(Index::Input(0));
+
+        let lc = LinearCombination::<Bls12>::zero();
+        assert!(lc.is_empty());
+        assert_eq!(lc.len(), 0);
+        assert_eq!(lc.iter_terms().count(), 0);
+
+        let lc = lc + (fr(3), b) + a + (fr(2), x) + a;
+        assert!(!lc.is_empty());
+        assert_eq!(lc.len(), 3);
+
+        let terms = lc.iter_terms().map(|(v, c)| (v, *c)).collect::<Vec<_>>();
+        assert_eq!(terms, vec![(x, fr(2)), (a, fr(2)), (b, fr(3))]);
+    }
+
+    #[test]
+    fn test_scale() {
+        let fr = |v: u64| <Bls12 as Engine>::Fr::from(v);
+        let a = Variable::new_unchecked(Index::Aux(0));
+        let x = Variable::new_unchecked(Index::Input(1));
+
+        let lc = LinearCombination::<Bls12>::zero() + (fr(3), a) + (fr(5), x);
+
+        let scaled = lc.clone() * fr(7);
+        let terms = scaled
+            .iter_terms()
+            .map(|(v, c)| (v, *c))
+            .collect::<Vec<_>>();
+        assert_eq!(terms, vec![(x, fr(35)), (a, fr(21))]);
+
+        let mut scaled = lc;
+        scaled.scale(&fr(0));
+        let terms = scaled
+            .iter_terms()
+            .map(|(v, c)| (v, *c))
+            .collect::<Vec<_>>();
+        assert_eq!(terms, vec![(x, fr(0)), (a, fr(0))]);
+
+        assert!((LinearCombination::<Bls12>::zero() * fr(7)).is_empty());
+    }
+
+    #[test]
+    fn test_negate() {
+        let fr = |v: u64| <Bls12 as Engine>::Fr::from(v);
+        let terms = |lc: &LinearCombination<Bls12>| {
+            lc.iter_terms().map(|(v, c)| (v, *c)).collect::<Vec<_>>()
+        };
+        let a = Variable::new_unchecked(Index::Aux(0));
+        let b = Variable::new_unchecked(Index::Aux(1));
+        let x = Variable::new_unchecked(Index::Input(0));
+
+        assert!((-LinearCombination::<Bls12>::zero()).is_empty());
+
+        let lc = LinearCombination::<Bls12>::zero() + (fr(3), a) + (fr(5), x);
+        let mut negated = lc.clone();
+        negated.negate();
+        assert_eq!(terms(&negated), vec![(x, -fr(5)), (a, -fr(3))]);
+        assert_eq!(terms(&-negated), terms(&lc));
+
+        let other = LinearCombination::<Bls12>::zero() + (fr(2), a) + (fr(7), b);
+        let mut difference = lc.clone() - &other;
+        let mut sum = lc + &(-other);
+        difference.normalize();
+        sum.normalize();
+        assert_eq!(terms(&difference), terms(&sum));
+        assert_eq!(terms(&sum), vec![(x, fr(5)), (a, fr(1)), (b, -fr(7))]);
+    }
+
+    #[test]
+    fn test_variable_ordering() {
+        let input = |i| Variable::new_unchecked(Index::Input(i));
+        let aux = |i| Variable::new_unchecked(Index::Aux(i));
+
+        assert!(input(5) < aux(0));
+        assert!(input(0) < input(1));
+        assert!(aux(1) < aux(2));
+        assert_eq!(aux(3).cmp(&aux(3)), std::cmp::Ordering::Equal);
+
+        let mut vars = vec![aux(1), input(2), aux(0), input(0)];
+        vars.sort();
+        assert_eq!(vars, vec![input(0), input(2), aux(0), aux(1)]);
+    }
+
+    #[test]
+    fn test_eval() {
+        let fr = |v: u64| <Bls12 as Engine>::Fr::from(v);
+        let inputs = [fr(1), fr(2)];
+        let aux = [fr(3), fr(4), fr(5)];
+
+        let lc = LinearCombination::<Bls12>::zero()
+            + (fr(7), Variable::new_unchecked(Index::Input(0)))
+            + Variable::new_unchecked(Index::Input(1))
+            + (fr(2), Variable::new_unchecked(Index::Aux(2)))
+            - Variable::new_unchecked(Index::Aux(0));
+
+        // 7 * 1 + 2 + 2 * 5 - 3
+        assert_eq!(lc.eval(&inputs, &aux), fr(16));
+        assert_eq!(
+            LinearCombination::<Bls12>::zero().eval(&inputs, &aux),
+            fr(0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "aux variable 3 is out of range")]
+    fn test_eval_out_of_range() {
+        let lc = LinearCombination::<Bls12>::zero() + Variable::new_unchecked(Index::Aux(3));
+        let values = [<Bls12 as Engine>::Fr::one(); 3];
+
+        lc.eval(&values, &values);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let fr = |v: u64| <Bls12 as Engine>::Fr::from(v);
+        let a = Variable::new_unchecked(Index::Aux(0));
+        let b = Variable::new_unchecked(Index::Aux(1));
+        let x = Variable::new_unchecked(Index::Input(0));
+
+        let mut lc = LinearCombination::<Bls12>::zero() + (fr(2), a) + x + b - b + (fr(3), a);
+        assert!(!lc.is_normalized());
+
+        lc.normalize();
+        assert!(lc.is_normalized());
+        assert_eq!(lc.len(), 2);
+        let terms = lc.iter().map(|(v, c)| (v, *c)).collect::<Vec<_>>();
+        assert_eq!(terms, vec![(x, fr(1)), (a, fr(5))]);
+
+        // Duplicate and unsorted entries are merged.
+        let mut lc = LinearCombination::<Bls12>::zero();
+        lc.aux.values = vec![(3, fr(1)), (1, fr(2)), (3, fr(4)), (2, fr(0))];
+        assert!(!lc.is_normalized());
+        lc.normalize();
+        assert!(lc.is_normalized());
+        assert_eq!(&lc.aux.values, &[(1, fr(2)), (3, fr(5))]);
+
+        // Insertion still works after normalizing.
+        lc = lc + (fr(1), Variable::new_unchecked(Index::Aux(2)));
+        assert_eq!(&lc.aux.values, &[(1, fr(2)), (2, fr(1)), (3, fr(5))]);
+
+        assert!(LinearCombination::<Bls12>::zero().is_normalized());
+    }
+
+    #[test]
+    fn test_variable_fmt() {
+        let input = Variable::new_unchecked(Index::Input(3));
+        let aux = Variable::new_unchecked(Index::Aux(7));
+
+        assert_eq!(format!("{:?}", input), "Input(3)");
+        assert_eq!(format!("{:?}", aux), "Aux(7)");
+        assert_eq!(input.to_string(), "Input(3)");
+        assert_eq!(aux.to_string(), "Aux(7)");
+        assert_eq!(format!("{:?}", vec![input, aux]), "[Input(3), Aux(7)]");
+    }
+
     #[test]
     fn test_insert_or_update() {
         let mut indexer = Indexer::default();
@@ -208,12 +208,38 @@ pub enum SynthesisError {
     MalformedProofs(String),
     #[error("malformed SRS")]
     MalformedSrs,
+    #[error("malformed CRS shards: {0}")]
+    MalformedShards(String),
     #[error("non power of two proofs given for aggregation")]
     NonPowerOfTwo,
     #[error("incompatible vector length: {0}")]
     IncompatibleLengthVector(String),
     #[error("invalid pairing")]
     InvalidPairing,
+    /// During synthesis, a value's bit-length exceeded the capacity of the field
+    #[error("{0} bits do not fit into the field capacity")]
+    ExceedsFieldCapacity(usize),
+    /// Wraps an error with the namespace path in which it occurred
+    #[error("{1} (at `{0}`)")]
+    WithContext(String, Box<SynthesisError>),
+}
+
+impl SynthesisError {
+    /// Attaches the namespace path at which this error occurred.
+    pub fn in_namespace(self, path: &str) -> SynthesisError {
+        SynthesisError::WithContext(path.to_string(), Box::new(self))
+    }
+}
+
+/// Whether a circuit is being synthesized to generate parameters, without a
+/// witness, or to compute its assignment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SynthesisMode {
+    /// The assignments of the variables are never evaluated, so circuits can
+    /// skip computing their witness.
+    Setup,
+    /// The assignments of the variables may be evaluated.
+    Prove,
 }
 
 /// Represents a constraint system which can have new variables
@@ -252,6 +278,26 @@ pub trait ConstraintSystem<E: Engine>: Sized + Send {
         A: FnOnce() -> AR,
         AR: Into<String>;
 
+    /// Allocate a private variable for each of the provided functions, in order. The
+    /// functions must not depend on each other, so that implementations can compute the
+    /// assignments in parallel; the resulting variables are the same as if each function
+    /// was passed to `alloc` in turn. `annotation` is invoked with the index of the variable.
+    fn alloc_parallel<F, A, AR>(
+        &mut self,
+        annotation: A,
+        fs: Vec<F>,
+    ) -> Result<Vec<Variable>, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError> + Send,
+        A: Fn(usize) -> AR,
+        AR: Into<String>,
+    {
+        fs.into_iter()
+            .enumerate()
+            .map(|(i, f)| self.alloc(|| annotation(i), f))
+            .collect()
+    }
+
     /// Enforce that `A` * `B` = `C`. The `annotation` function is invoked in testing contexts
     /// in order to derive a unique name for the constraint in the current namespace.
     fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
@@ -288,6 +334,13 @@ pub trait ConstraintSystem<E: Engine>: Sized + Send {
         Namespace(self.get_root(), Default::default())
     }
 
+    /// Whether this constraint system evaluates the assignments of the variables.
+    /// Constraint systems which only record the shape of a circuit return
+    /// `SynthesisMode::Setup`, so circuits can skip computing their witness.
+    fn mode(&self) -> SynthesisMode {
+        SynthesisMode::Prove
+    }
+
     /// Most implementations of ConstraintSystem are not 'extensible': they won't implement a specialized
     /// version of `extend` and should therefore also keep the default implementation of `is_extensible`
     /// so callers which optionally make use of `extend` can know to avoid relying on it when unimplemented.
@@ -337,6 +390,19 @@ impl<'cs, E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for Namespace<
         self.0.alloc_input(annotation, f)
     }
 
+    fn alloc_parallel<F, A, AR>(
+        &mut self,
+        annotation: A,
+        fs: Vec<F>,
+    ) -> Result<Vec<Variable>, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError> + Send,
+        A: Fn(usize) -> AR,
+        AR: Into<String>,
+    {
+        self.0.alloc_parallel(annotation, fs)
+    }
+
     fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
     where
         A: FnOnce() -> AR,
@@ -367,6 +433,10 @@ impl<'cs, E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for Namespace<
     fn get_root(&mut self) -> &mut Self::Root {
         self.0.get_root()
     }
+
+    fn mode(&self) -> SynthesisMode {
+        self.0.mode()
+    }
 }
 
 impl<'a, E: Engine, CS: ConstraintSystem<E>> Drop for Namespace<'a, E, CS> {
@@ -402,6 +472,19 @@ impl<'cs, E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for &'cs mut C
         (**self).alloc_input(annotation, f)
     }
 
+    fn alloc_parallel<F, A, AR>(
+        &mut self,
+        annotation: A,
+        fs: Vec<F>,
+    ) -> Result<Vec<Variable>, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError> + Send,
+        A: Fn(usize) -> AR,
+        AR: Into<String>,
+    {
+        (**self).alloc_parallel(annotation, fs)
+    }
+
     fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
     where
         A: FnOnce() -> AR,
@@ -428,6 +511,10 @@ impl<'cs, E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for &'cs mut C
     fn get_root(&mut self) -> &mut Self::Root {
         (**self).get_root()
     }
+
+    fn mode(&self) -> SynthesisMode {
+        (**self).mode()
+    }
 }
 
 pub(crate) fn le_bytes_to_u64s(le_bytes: &[u8]) -> Vec<u64> {
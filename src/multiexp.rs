@@ -255,11 +255,30 @@ fn shr(le_bytes: &mut [u8], mut n: u32) {
     }
 }
 
+/// Returns the bit length of the largest of `exponents`. Windows above it only
+/// ever hit the zero bucket, so the multiexp can skip them.
+fn max_num_bits<F: PrimeField>(exponents: &[F::Repr]) -> u32 {
+    exponents
+        .iter()
+        .map(|exp| {
+            let bytes = exp.as_ref();
+            bytes
+                .iter()
+                .rposition(|byte| *byte != 0)
+                .map_or(0, |i| 8 * (i as u32 + 1) - bytes[i].leading_zeros())
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Multiexp over the lowest `num_bits` bits of the exponents, which must not
+/// have any higher bits set.
 fn multiexp_inner<Q, D, G, S>(
     bases: S,
     density_map: D,
     exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
     c: u32,
+    num_bits: u32,
 ) -> Result<<G as PrimeCurveAffine>::Curve, SynthesisError>
 where
     for<'a> &'a Q: QueryDensity,
@@ -326,7 +345,7 @@ where
         Ok(acc)
     };
 
-    let parts = (0..<G::Scalar as PrimeField>::NUM_BITS)
+    let parts = (0..num_bits)
         .into_par_iter()
         .step_by(c as usize)
         .map(|skip| this(bases.clone(), density_map.clone(), exponents.clone(), skip))
@@ -373,11 +392,7 @@ where
         }
     }
 
-    let c = if exponents.len() < 32 {
-        3u32
-    } else {
-        (f64::from(exponents.len() as u32)).ln().ceil() as u32
-    };
+    let c = window_size(exponents.len());
 
     if let Some(query_size) = density_map.as_ref().get_query_size() {
         // If the density map has a known query size, it should not be
@@ -386,7 +401,10 @@ where
     }
 
     #[allow(clippy::let_and_return)]
-    let result = pool.compute(move || multiexp_inner(bases, density_map, exponents, c));
+    let result = pool.compute(move || {
+        let num_bits = max_num_bits::<G::Scalar>(&exponents);
+        multiexp_inner(bases, density_map, exponents, c, num_bits)
+    });
     #[cfg(any(feature = "cuda", feature = "opencl"))]
     {
         // Do not give the control back to the caller till the
@@ -399,6 +417,38 @@ where
     result
 }
 
+/// Perform multi-exponentiation over all of `bases` on the CPU, adding the
+/// result into `acc`.
+pub fn multiexp_accumulate<G, S>(
+    pool: &Worker,
+    bases: S,
+    exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
+    acc: &mut <G as PrimeCurveAffine>::Curve,
+) -> Result<(), SynthesisError>
+where
+    G: PrimeCurveAffine,
+    S: SourceBuilder<G>,
+{
+    let c = window_size(exponents.len());
+    let result = pool
+        .compute(move || {
+            let num_bits = max_num_bits::<G::Scalar>(&exponents);
+            multiexp_inner(bases, FullDensity, exponents, c, num_bits)
+        })
+        .wait()?;
+    acc.add_assign(&result);
+
+    Ok(())
+}
+
+pub(crate) fn window_size(num_exponents: usize) -> u32 {
+    if num_exponents < 32 {
+        3u32
+    } else {
+        (f64::from(num_exponents as u32)).ln().ceil() as u32
+    }
+}
+
 #[test]
 fn test_with_bls12() {
     fn naive_multiexp<G: PrimeCurveAffine>(
@@ -448,6 +498,103 @@ fn test_with_bls12() {
     assert_eq!(naive, fast);
 }
 
+#[test]
+fn test_multiexp_accumulate() {
+    use blstrs::Bls12;
+    use group::Curve;
+
+    const SAMPLES: usize = 1 << 8;
+
+    let rng = &mut rand::thread_rng();
+    let pool = Worker::new();
+
+    let mut bases = Vec::new();
+    let mut exponents = Vec::new();
+    for _ in 0..2 {
+        bases.push(Arc::new(
+            (0..SAMPLES)
+                .map(|_| <Bls12 as Engine>::G1::random(&mut *rng).to_affine())
+                .collect::<Vec<_>>(),
+        ));
+        exponents.push(Arc::new(
+            (0..SAMPLES)
+                .map(|_| <Bls12 as Engine>::Fr::random(&mut *rng).to_repr())
+                .collect::<Vec<_>>(),
+        ));
+    }
+
+    let mut expected = <Bls12 as Engine>::G1::identity();
+    for (g, v) in bases.iter().zip(exponents.iter()) {
+        let res =
+            multiexp::<_, _, _, Bls12, _>(&pool, (g.clone(), 0), FullDensity, v.clone(), &mut None)
+                .wait()
+                .unwrap();
+        expected.add_assign(&res);
+    }
+
+    let mut acc = <Bls12 as Engine>::G1::identity();
+    for (g, v) in bases.iter().zip(exponents.iter()) {
+        multiexp_accumulate(&pool, (g.clone(), 0), v.clone(), &mut acc).unwrap();
+    }
+
+    assert_eq!(acc, expected);
+}
+
+#[test]
+fn test_multiexp_small_exponents() {
+    use blstrs::Bls12;
+    use group::Curve;
+    use rand::Rng;
+
+    type Fr = <Bls12 as Engine>::Fr;
+
+    assert_eq!(max_num_bits::<Fr>(&[]), 0);
+    assert_eq!(max_num_bits::<Fr>(&[Fr::zero().to_repr()]), 0);
+    assert_eq!(max_num_bits::<Fr>(&[Fr::one().to_repr()]), 1);
+    assert_eq!(max_num_bits::<Fr>(&[Fr::from(255u64).to_repr()]), 8);
+    assert_eq!(
+        max_num_bits::<Fr>(&[Fr::from(255u64).to_repr(), Fr::from(256u64).to_repr()]),
+        9
+    );
+    assert_eq!(max_num_bits::<Fr>(&[(-Fr::one()).to_repr()]), Fr::NUM_BITS);
+
+    const SAMPLES: usize = 1 << 8;
+
+    let rng = &mut rand::thread_rng();
+    let bases = Arc::new(
+        (0..SAMPLES)
+            .map(|_| <Bls12 as Engine>::G1::random(&mut *rng).to_affine())
+            .collect::<Vec<_>>(),
+    );
+    // Small coefficients, including the trivial ones.
+    let exponents = Arc::new(
+        (0..SAMPLES)
+            .map(|i| match i % 8 {
+                0 => Fr::zero(),
+                1 => Fr::one(),
+                _ => Fr::from(rng.gen::<u32>() as u64),
+            })
+            .map(|fr| fr.to_repr())
+            .collect::<Vec<_>>(),
+    );
+
+    let num_bits = max_num_bits::<Fr>(&exponents);
+    assert!(num_bits <= 32);
+
+    let c = window_size(SAMPLES);
+    let full = multiexp_inner(
+        (bases.clone(), 0),
+        FullDensity,
+        exponents.clone(),
+        c,
+        Fr::NUM_BITS,
+    )
+    .unwrap();
+    let bounded = multiexp_inner((bases, 0), FullDensity, exponents, c, num_bits).unwrap();
+
+    assert_eq!(full, bounded);
+}
+
 pub fn create_multiexp_kernel<E>(_log_d: usize, priority: bool) -> Option<gpu::MultiexpKernel<E>>
 where
     E: Engine + gpu::GpuEngine,
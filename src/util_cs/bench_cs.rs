@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 
-use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, SynthesisMode, Variable};
 use pairing::Engine;
 
 #[allow(clippy::upper_case_acronyms)]
@@ -97,4 +97,8 @@ impl<E: Engine> ConstraintSystem<E> for BenchCS<E> {
     fn get_root(&mut self) -> &mut Self::Root {
         self
     }
+
+    fn mode(&self) -> SynthesisMode {
+        SynthesisMode::Setup
+    }
 }
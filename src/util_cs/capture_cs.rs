@@ -0,0 +1,227 @@
+use std::marker::PhantomData;
+
+use crate::{
+    Circuit, ConstraintSystem, LinearCombination, SynthesisError, SynthesisMode, Variable,
+};
+use pairing::Engine;
+
+/// Constraint system wrapper which allocates the public inputs of a circuit as
+/// private variables of the inner constraint system, and records them in order.
+///
+/// This allows a circuit to be synthesized as a part of a larger circuit, which
+/// can then constrain the values the circuit would have exposed.
+#[allow(clippy::upper_case_acronyms)]
+pub struct InputCaptureCS<E: Engine, CS: ConstraintSystem<E>> {
+    inner: CS,
+    inputs: Vec<Variable>,
+    _e: PhantomData<E>,
+}
+
+impl<E: Engine, CS: ConstraintSystem<E>> InputCaptureCS<E, CS> {
+    pub fn new(inner: CS) -> Self {
+        InputCaptureCS {
+            inner,
+            inputs: vec![],
+            _e: PhantomData,
+        }
+    }
+
+    /// The variables allocated through `alloc_input` so far.
+    pub fn inputs(&self) -> &[Variable] {
+        &self.inputs
+    }
+
+    pub fn into_inputs(self) -> Vec<Variable> {
+        self.inputs
+    }
+}
+
+impl<E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for InputCaptureCS<E, CS> {
+    type Root = Self;
+
+    fn one() -> Variable {
+        CS::one()
+    }
+
+    fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.alloc(annotation, f)
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let var = self.inner.alloc(annotation, f)?;
+        self.inputs.push(var);
+
+        Ok(var)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.inner.enforce(annotation, a, b, c)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.inner.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self) {
+        self.inner.get_root().pop_namespace()
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn mode(&self) -> SynthesisMode {
+        self.inner.mode()
+    }
+}
+
+/// Synthesizes both circuits, in the namespaces `a` and `b`, and enforces that
+/// the public inputs they allocate are pairwise equal, in allocation order. The
+/// inputs are allocated as private variables of `cs`.
+///
+/// Fails with `IncompatibleLengthVector` if the circuits allocate a different
+/// number of inputs.
+pub fn enforce_circuits_agree<E, CS, A, B>(mut cs: CS, a: A, b: B) -> Result<(), SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+    A: Circuit<E>,
+    B: Circuit<E>,
+{
+    let mut capture = InputCaptureCS::new(cs.namespace(|| "a"));
+    a.synthesize(&mut capture)?;
+    let inputs_a = capture.into_inputs();
+
+    let mut capture = InputCaptureCS::new(cs.namespace(|| "b"));
+    b.synthesize(&mut capture)?;
+    let inputs_b = capture.into_inputs();
+
+    if inputs_a.len() != inputs_b.len() {
+        return Err(SynthesisError::IncompatibleLengthVector(format!(
+            "circuits allocate {} and {} inputs",
+            inputs_a.len(),
+            inputs_b.len()
+        )));
+    }
+
+    for (i, (a, b)) in inputs_a.into_iter().zip(inputs_b).enumerate() {
+        cs.enforce(
+            || format!("input {} agrees", i),
+            |lc| lc + a,
+            |lc| lc + CS::one(),
+            |lc| lc + b,
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util_cs::test_cs::TestConstraintSystem;
+    use blstrs::{Bls12, Scalar as Fr};
+    use ff::Field;
+
+    /// Exposes `x * x`.
+    struct Square(Fr);
+
+    impl Circuit<Bls12> for Square {
+        fn synthesize<CS: ConstraintSystem<Bls12>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            let x = cs.alloc(|| "x", || Ok(self.0))?;
+            let out = cs.alloc_input(|| "out", || Ok(self.0.square()))?;
+            cs.enforce(|| "square", |lc| lc + x, |lc| lc + x, |lc| lc + out);
+
+            Ok(())
+        }
+    }
+
+    /// Exposes `x + 6`, and `x` itself if `expose_x` is set.
+    struct AddSix(Fr, bool);
+
+    impl Circuit<Bls12> for AddSix {
+        fn synthesize<CS: ConstraintSystem<Bls12>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            let x = cs.alloc(|| "x", || Ok(self.0))?;
+            let out = cs.alloc_input(|| "out", || Ok(self.0 + Fr::from(6u64)))?;
+            cs.enforce(
+                || "add",
+                |lc| lc + x + (Fr::from(6u64), CS::one()),
+                |lc| lc + CS::one(),
+                |lc| lc + out,
+            );
+            if self.1 {
+                let input = cs.alloc_input(|| "exposed x", || Ok(self.0))?;
+                cs.enforce(
+                    || "expose x",
+                    |lc| lc + x,
+                    |lc| lc + CS::one(),
+                    |lc| lc + input,
+                );
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_enforce_circuits_agree() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        enforce_circuits_agree(
+            cs.namespace(|| "agree"),
+            Square(Fr::from(3u64)),
+            AddSix(Fr::from(3u64), false),
+        )
+        .unwrap();
+        assert!(cs.is_satisfied());
+        // The inputs of the circuits are not inputs of the outer system.
+        assert_eq!(cs.num_inputs(), 1);
+        assert_eq!(cs.num_constraints(), 3);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        enforce_circuits_agree(
+            cs.namespace(|| "disagree"),
+            Square(Fr::from(3u64)),
+            AddSix(Fr::from(4u64), false),
+        )
+        .unwrap();
+        assert!(!cs.is_satisfied());
+        assert_eq!(cs.which_is_unsatisfied(), Some("disagree/input 0 agrees"));
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        assert!(matches!(
+            enforce_circuits_agree(
+                &mut cs,
+                Square(Fr::from(3u64)),
+                AddSix(Fr::from(3u64), true),
+            ),
+            Err(SynthesisError::IncompatibleLengthVector(_))
+        ));
+    }
+}
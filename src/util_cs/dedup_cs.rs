@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use crate::{ConstraintSystem, LinearCombination, SynthesisError, SynthesisMode, Variable};
+use blake2s_simd::State as Blake2s;
+use pairing::Engine;
+
+use super::test_cs::hash_lc;
+
+/// Constraint system wrapper which forwards everything to an inner constraint
+/// system, except for constraints that are identical to one already enforced.
+///
+/// Constraints are compared after normalizing their linear combinations (merging
+/// duplicate terms and dropping zero coefficients), so two constraints are only
+/// considered equal if their A, B and C terms are equal in that order.
+#[allow(clippy::upper_case_acronyms)]
+pub struct DedupCS<E: Engine, CS: ConstraintSystem<E>> {
+    inner: CS,
+    seen: HashSet<Vec<u8>>,
+    skipped: usize,
+    _e: PhantomData<E>,
+}
+
+impl<E: Engine, CS: ConstraintSystem<E>> DedupCS<E, CS> {
+    pub fn new(inner: CS) -> Self {
+        DedupCS {
+            inner,
+            seen: HashSet::new(),
+            skipped: 0,
+            _e: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &CS {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> CS {
+        self.inner
+    }
+
+    /// Number of constraints which were not forwarded because they were duplicates.
+    pub fn num_skipped(&self) -> usize {
+        self.skipped
+    }
+}
+
+fn hash_constraint<E: Engine>(
+    a: &LinearCombination<E>,
+    b: &LinearCombination<E>,
+    c: &LinearCombination<E>,
+) -> Vec<u8> {
+    let mut h = Blake2s::new();
+    hash_lc::<E>(a, &mut h);
+    hash_lc::<E>(b, &mut h);
+    hash_lc::<E>(c, &mut h);
+
+    h.finalize().as_ref().to_vec()
+}
+
+impl<E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for DedupCS<E, CS> {
+    type Root = Self;
+
+    fn one() -> Variable {
+        CS::one()
+    }
+
+    fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.alloc(annotation, f)
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.alloc_input(annotation, f)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        let a = a(LinearCombination::zero());
+        let b = b(LinearCombination::zero());
+        let c = c(LinearCombination::zero());
+
+        if !self.seen.insert(hash_constraint(&a, &b, &c)) {
+            self.skipped += 1;
+            return;
+        }
+
+        self.inner.enforce(annotation, |_| a, |_| b, |_| c);
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.inner.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self) {
+        self.inner.get_root().pop_namespace()
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn mode(&self) -> SynthesisMode {
+        self.inner.mode()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util_cs::test_cs::TestConstraintSystem;
+    use blstrs::{Bls12, Scalar as Fr};
+
+    #[test]
+    fn test_dedup_cs() {
+        let mut cs = DedupCS::new(TestConstraintSystem::<Bls12>::new());
+
+        let a = cs.alloc(|| "a", || Ok(Fr::from(3u64))).unwrap();
+        let b = cs.alloc(|| "b", || Ok(Fr::from(4u64))).unwrap();
+        let c = cs.alloc(|| "c", || Ok(Fr::from(12u64))).unwrap();
+
+        cs.enforce(|| "mult", |lc| lc + a, |lc| lc + b, |lc| lc + c);
+        {
+            let mut cs = cs.namespace(|| "again");
+            cs.enforce(|| "mult", |lc| lc + a, |lc| lc + b, |lc| lc + c);
+        }
+        // Equal after normalization.
+        cs.enforce(
+            || "mult normalized",
+            |lc| lc + a + b - b,
+            |lc| lc + b,
+            |lc| lc + c,
+        );
+        assert_eq!(cs.num_skipped(), 2);
+
+        // Semantically distinct constraints are kept.
+        cs.enforce(|| "swapped", |lc| lc + b, |lc| lc + a, |lc| lc + c);
+        cs.enforce(
+            || "scaled",
+            |lc| lc + (Fr::from(2u64), a),
+            |lc| lc + b,
+            |lc| lc + (Fr::from(2u64), c),
+        );
+        let one = TestConstraintSystem::<Bls12>::one();
+        cs.enforce(|| "one", |lc| lc + a, |lc| lc + one, |lc| lc + a);
+        assert_eq!(cs.num_skipped(), 2);
+
+        let cs = cs.into_inner();
+        assert_eq!(cs.num_constraints(), 4);
+        assert!(cs.is_satisfied());
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use crate::gadgets::num::AllocatedNum;
+use crate::{ConstraintSystem, SynthesisError};
+use pairing::Engine;
+
+/// Collects numbers a circuit wants to expose as public inputs, so that it can
+/// compute them in whatever order is convenient. `finalize` allocates them as
+/// inputs ordered by name, so the order the verifier expects does not depend
+/// on the order in which the circuit computed them.
+pub struct DeferredInputs<E: Engine> {
+    inputs: BTreeMap<String, AllocatedNum<E>>,
+}
+
+impl<E: Engine> DeferredInputs<E> {
+    pub fn new() -> Self {
+        DeferredInputs::default()
+    }
+
+    /// Registers `num` to be exposed as the input named `name`.
+    ///
+    /// Panics if an input named `name` was already registered.
+    pub fn register<N: Into<String>>(&mut self, name: N, num: &AllocatedNum<E>) {
+        let name = name.into();
+        if self.inputs.contains_key(&name) {
+            panic!("input `{}` registered twice", name);
+        }
+        self.inputs.insert(name, num.clone());
+    }
+
+    /// The values of the registered inputs, in the order `finalize` allocates
+    /// them, or `None` if any of them is unknown.
+    pub fn values(&self) -> Option<Vec<E::Fr>> {
+        self.inputs.values().map(|num| num.get_value()).collect()
+    }
+
+    /// Allocates the registered inputs ordered by name, each in a namespace
+    /// named after it, and enforces that they equal the registered numbers.
+    pub fn finalize<CS: ConstraintSystem<E>>(self, mut cs: CS) -> Result<(), SynthesisError> {
+        for (name, num) in self.inputs {
+            num.inputize(cs.namespace(|| name))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<E: Engine> Default for DeferredInputs<E> {
+    fn default() -> Self {
+        DeferredInputs {
+            inputs: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util_cs::test_cs::TestConstraintSystem;
+    use crate::Circuit;
+    use blstrs::{Bls12, Scalar as Fr};
+
+    /// Exposes `x^3` and `x^2`, computing `x^3` last but registering it under
+    /// the name that sorts first.
+    #[derive(Clone)]
+    struct Powers {
+        x: Option<Fr>,
+    }
+
+    impl Circuit<Bls12> for Powers {
+        fn synthesize<CS: ConstraintSystem<Bls12>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            let mut inputs = DeferredInputs::new();
+
+            let x = AllocatedNum::alloc(cs.namespace(|| "x"), || {
+                self.x.ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let square = x.square(cs.namespace(|| "square"))?;
+            inputs.register("b square", &square);
+            let cube = square.mul(cs.namespace(|| "cube"), &x)?;
+            inputs.register("a cube", &cube);
+
+            inputs.finalize(cs.namespace(|| "inputs"))
+        }
+    }
+
+    #[test]
+    fn test_deferred_inputs() {
+        let x = Fr::from(3u64);
+        let inputs = [Fr::from(27u64), Fr::from(9u64)];
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        Powers { x: Some(x) }.synthesize(&mut cs).unwrap();
+        assert!(cs.is_satisfied());
+        assert!(cs.verify(&inputs));
+        assert!(!cs.verify(&[inputs[1], inputs[0]]));
+        assert_eq!(cs.get_input(1, "inputs/a cube/input variable"), inputs[0]);
+    }
+
+    #[cfg(feature = "groth16")]
+    #[test]
+    fn test_deferred_inputs_proof() {
+        use crate::groth16::test_utils::prove_and_check;
+        use rand_core::SeedableRng;
+        use rand_xorshift::XorShiftRng;
+
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let circuit = Powers {
+            x: Some(Fr::from(3u64)),
+        };
+        let inputs = [Fr::from(27u64), Fr::from(9u64)];
+        assert!(prove_and_check::<Bls12, _, _>(circuit, &inputs, &mut rng).unwrap());
+    }
+
+    #[test]
+    fn test_deferred_inputs_values() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut inputs = DeferredInputs::new();
+        let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::from(1u64))).unwrap();
+        let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::from(2u64))).unwrap();
+        inputs.register("z", &a);
+        inputs.register("y", &b);
+        assert_eq!(inputs.values(), Some(vec![Fr::from(2u64), Fr::from(1u64)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "input `y` registered twice")]
+    fn test_deferred_inputs_duplicate() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut inputs = DeferredInputs::new();
+        let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::from(1u64))).unwrap();
+        inputs.register("y", &a);
+        inputs.register("y", &a);
+    }
+}
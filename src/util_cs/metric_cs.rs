@@ -1,13 +1,10 @@
-use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, SynthesisMode, Variable};
 use ff::{Field, PrimeField};
 use pairing::Engine;
-use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
 use std::ops::AddAssign;
 
-#[derive(Clone, Copy)]
-struct OrderedVariable(Variable);
-
 #[derive(Debug)]
 enum NamedObject {
     Constraint(usize),
@@ -15,32 +12,6 @@ enum NamedObject {
     Namespace,
 }
 
-impl Eq for OrderedVariable {}
-impl PartialEq for OrderedVariable {
-    fn eq(&self, other: &OrderedVariable) -> bool {
-        match (self.0.get_unchecked(), other.0.get_unchecked()) {
-            (Index::Input(ref a), Index::Input(ref b)) => a == b,
-            (Index::Aux(ref a), Index::Aux(ref b)) => a == b,
-            _ => false,
-        }
-    }
-}
-impl PartialOrd for OrderedVariable {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-impl Ord for OrderedVariable {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self.0.get_unchecked(), other.0.get_unchecked()) {
-            (Index::Input(ref a), Index::Input(ref b)) => a.cmp(b),
-            (Index::Aux(ref a), Index::Aux(ref b)) => a.cmp(b),
-            (Index::Input(_), Index::Aux(_)) => Ordering::Less,
-            (Index::Aux(_), Index::Input(_)) => Ordering::Greater,
-        }
-    }
-}
-
 #[allow(clippy::upper_case_acronyms)]
 pub struct MetricCS<E: Engine> {
     named_objects: HashMap<String, NamedObject>,
@@ -56,10 +27,10 @@ pub struct MetricCS<E: Engine> {
     aux: Vec<String>,
 }
 
-fn proc_lc<E: Engine>(terms: &LinearCombination<E>) -> BTreeMap<OrderedVariable, E::Fr> {
+fn proc_lc<E: Engine>(terms: &LinearCombination<E>) -> BTreeMap<Variable, E::Fr> {
     let mut map = BTreeMap::new();
     for (var, &coeff) in terms.iter() {
-        map.entry(OrderedVariable(var))
+        map.entry(var)
             .or_insert_with(E::Fr::zero)
             .add_assign(&coeff);
     }
@@ -92,6 +63,10 @@ impl<E: Engine> MetricCS<E> {
         self.inputs.len()
     }
 
+    pub fn num_aux(&self) -> usize {
+        self.aux.len()
+    }
+
     pub fn pretty_print_list(&self) -> Vec<String> {
         let mut result = Vec::new();
 
@@ -144,7 +119,7 @@ impl<E: Engine> MetricCS<E> {
                     s.push_str(&format!("{:?} . ", coeff))
                 }
 
-                match var.0.get_unchecked() {
+                match var.get_unchecked() {
                     Index::Input(i) => {
                         s.push_str(&format!("`I{}`", &self.inputs[i]));
                     }
@@ -263,6 +238,10 @@ impl<E: Engine> ConstraintSystem<E> for MetricCS<E> {
     fn get_root(&mut self) -> &mut Self::Root {
         self
     }
+
+    fn mode(&self) -> SynthesisMode {
+        SynthesisMode::Setup
+    }
 }
 
 fn compute_path(ns: &[String], this: &str) -> String {
@@ -284,3 +263,258 @@ fn compute_path(ns: &[String], this: &str) -> String {
 
     name
 }
+
+/// Constraint system which only counts the inputs, auxiliary variables and
+/// constraints of a circuit. Unlike `MetricCS`, neither the linear
+/// combinations nor the names are kept, so it can measure circuits far too
+/// large to be stored.
+///
+/// With `with_namespaces`, the number of constraints enforced directly in each
+/// namespace is counted as well. This keeps one counter per distinct namespace.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+pub struct CountingCS<E: Engine> {
+    num_inputs: usize,
+    num_aux: usize,
+    num_constraints: usize,
+    namespaces: Option<Namespaces>,
+    _e: PhantomData<E>,
+}
+
+/// Per-namespace constraint counts. The counts of the open namespaces are kept
+/// on a stack, and only added to `totals` when the namespace is popped.
+#[derive(Debug, Default)]
+struct Namespaces {
+    stack: Vec<(String, usize)>,
+    totals: BTreeMap<String, usize>,
+}
+
+impl<E: Engine> CountingCS<E> {
+    pub fn new() -> Self {
+        CountingCS::default()
+    }
+
+    /// Like `new`, but also counts the constraints of each namespace, see
+    /// `constraints_by_namespace`.
+    pub fn with_namespaces() -> Self {
+        CountingCS {
+            namespaces: Some(Namespaces {
+                stack: vec![(String::new(), 0)],
+                totals: BTreeMap::new(),
+            }),
+            ..CountingCS::default()
+        }
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    /// Number of inputs, including the implicit "one" input.
+    pub fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    pub fn num_aux(&self) -> usize {
+        self.num_aux
+    }
+
+    /// Returns the number of constraints enforced directly in each namespace,
+    /// not counting those of its children, keyed by the namespace's path. The
+    /// root namespace's path is the empty string. Namespaces without
+    /// constraints are left out.
+    ///
+    /// Returns `None` unless created with `with_namespaces`.
+    pub fn constraints_by_namespace(&self) -> Option<BTreeMap<String, usize>> {
+        self.namespaces.as_ref().map(|namespaces| {
+            let mut totals = namespaces.totals.clone();
+            for (path, count) in &namespaces.stack {
+                if *count > 0 {
+                    *totals.entry(path.clone()).or_insert(0) += count;
+                }
+            }
+            totals
+        })
+    }
+}
+
+impl<E: Engine> Default for CountingCS<E> {
+    fn default() -> Self {
+        CountingCS {
+            // The "one" input.
+            num_inputs: 1,
+            num_aux: 0,
+            num_constraints: 0,
+            namespaces: None,
+            _e: PhantomData,
+        }
+    }
+}
+
+impl<E: Engine> ConstraintSystem<E> for CountingCS<E> {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_aux += 1;
+
+        Ok(Variable::new_unchecked(Index::Aux(self.num_aux - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_inputs += 1;
+
+        Ok(Variable::new_unchecked(Index::Input(self.num_inputs - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, _a: LA, _b: LB, _c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.num_constraints += 1;
+        if let Some(namespaces) = self.namespaces.as_mut() {
+            namespaces.stack.last_mut().expect("root namespace").1 += 1;
+        }
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        if let Some(namespaces) = self.namespaces.as_mut() {
+            let parent = &namespaces.stack.last().expect("root namespace").0;
+            let name = name_fn().into();
+            let path = if parent.is_empty() {
+                name
+            } else {
+                format!("{}/{}", parent, name)
+            };
+            namespaces.stack.push((path, 0));
+        }
+    }
+
+    fn pop_namespace(&mut self) {
+        if let Some(namespaces) = self.namespaces.as_mut() {
+            assert!(namespaces.stack.len() > 1, "popped the root namespace");
+            let (path, count) = namespaces.stack.pop().unwrap();
+            if count > 0 {
+                *namespaces.totals.entry(path).or_insert(0) += count;
+            }
+        }
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn mode(&self) -> SynthesisMode {
+        SynthesisMode::Setup
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::multipack;
+    use crate::gadgets::sha256::sha256;
+    use crate::util_cs::streaming_cs::StreamingR1CSWriter;
+    use crate::util_cs::test_cs::TestConstraintSystem;
+    use blstrs::Bls12;
+
+    /// The SHA-256d circuit of the crate's example.
+    fn sha256d_circuit<CS: ConstraintSystem<Bls12>>(cs: &mut CS) {
+        let preimage = [42u8; 80];
+        let bits = preimage
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8))
+            .enumerate()
+            .map(|(i, b)| {
+                Boolean::from(
+                    AllocatedBit::alloc(cs.namespace(|| format!("preimage bit {}", i)), Some(b))
+                        .unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let input = bits
+            .chunks(8)
+            .flat_map(|c| c.iter().rev())
+            .cloned()
+            .collect::<Vec<_>>();
+        let mid = sha256(cs.namespace(|| "SHA-256(input)"), &input).unwrap();
+        let hash = sha256(cs.namespace(|| "SHA-256(mid)"), &mid).unwrap();
+
+        multipack::pack_into_inputs(cs.namespace(|| "pack hash"), &hash).unwrap();
+    }
+
+    #[test]
+    fn test_metric_cs() {
+        let mut expected = TestConstraintSystem::<Bls12>::new();
+        sha256d_circuit(&mut expected);
+        assert!(expected.is_satisfied());
+        let mut streaming = StreamingR1CSWriter::<Bls12, _>::new(std::io::sink());
+        sha256d_circuit(&mut streaming);
+
+        let mut cs = MetricCS::<Bls12>::new();
+        sha256d_circuit(&mut cs);
+        assert_eq!(cs.num_constraints(), expected.num_constraints());
+        assert_eq!(cs.num_inputs(), expected.num_inputs());
+        assert_eq!(cs.num_aux(), streaming.num_aux());
+        assert_eq!(
+            cs.pretty_print_list().len(),
+            cs.num_inputs() + cs.num_aux() + cs.num_constraints()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "tried to create object at existing path: a")]
+    fn test_metric_cs_duplicate_path() {
+        let mut cs = MetricCS::<Bls12>::new();
+        cs.push_namespace(|| "a");
+        cs.pop_namespace();
+        cs.push_namespace(|| "a");
+    }
+
+    #[test]
+    fn test_counting_cs() {
+        let mut expected = TestConstraintSystem::<Bls12>::new();
+        sha256d_circuit(&mut expected);
+        let mut metric = MetricCS::<Bls12>::new();
+        sha256d_circuit(&mut metric);
+
+        let mut cs = CountingCS::<Bls12>::new();
+        sha256d_circuit(&mut cs);
+        assert_eq!(cs.num_constraints(), expected.num_constraints());
+        assert_eq!(cs.num_inputs(), expected.num_inputs());
+        assert_eq!(cs.num_aux(), metric.num_aux());
+        assert_eq!(cs.constraints_by_namespace(), None);
+
+        let mut cs = CountingCS::<Bls12>::with_namespaces();
+        sha256d_circuit(&mut cs);
+        assert_eq!(cs.num_constraints(), expected.num_constraints());
+        let by_namespace = cs.constraints_by_namespace().unwrap();
+        assert_eq!(
+            by_namespace.values().sum::<usize>(),
+            expected.num_constraints()
+        );
+        assert_eq!(by_namespace.get("preimage bit 0"), Some(&1));
+        assert!(by_namespace.keys().all(|path| path.starts_with("SHA-256(")
+            || path.starts_with("preimage bit ")
+            || path.starts_with("pack hash")));
+    }
+}
@@ -1,3 +1,11 @@
 pub mod bench_cs;
+pub mod capture_cs;
+pub mod dedup_cs;
+pub mod deferred_inputs;
 pub mod metric_cs;
+pub mod r1cs_export;
+pub mod reorder_cs;
+pub mod streaming_cs;
 pub mod test_cs;
+pub mod with_inputs;
+pub mod witness_cs;
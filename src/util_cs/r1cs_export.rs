@@ -0,0 +1,360 @@
+use std::io::{self, Read, Write};
+
+use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, SynthesisMode, Variable};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use ff::{PrimeField, PrimeFieldBits};
+use pairing::Engine;
+
+const MAGIC: &[u8; 4] = b"r1cs";
+const VERSION: u32 = 1;
+
+const SECTION_HEADER: u32 = 1;
+const SECTION_CONSTRAINTS: u32 = 2;
+const SECTION_WIRE_TO_LABEL: u32 = 3;
+
+/// Constraint system which exports a circuit's R1CS in the `.r1cs` binary format
+/// used by circom and snarkjs. Variable values are never computed.
+///
+/// Wire 0 is the "one" input, followed by the public inputs and then the
+/// auxiliary variables, so that `Index::Input(i)` is wire `i` and `Index::Aux(i)`
+/// is wire `num_inputs + i`. The public inputs are exported as public inputs
+/// rather than outputs, every auxiliary variable as an internal wire, and every
+/// wire is its own label.
+///
+/// The constraints are kept in memory until `finish` writes the file, since the
+/// format needs the final counts before the constraints.
+#[allow(clippy::upper_case_acronyms)]
+pub struct R1CSWriter<E: Engine, W: Write> {
+    writer: W,
+    num_inputs: usize,
+    num_aux: usize,
+    #[allow(clippy::type_complexity)]
+    constraints: Vec<(
+        LinearCombination<E>,
+        LinearCombination<E>,
+        LinearCombination<E>,
+    )>,
+}
+
+impl<E, W> R1CSWriter<E, W>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    W: Write,
+{
+    pub fn new(writer: W) -> Self {
+        R1CSWriter {
+            writer,
+            // The "one" input.
+            num_inputs: 1,
+            num_aux: 0,
+            constraints: vec![],
+        }
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    pub fn num_aux(&self) -> usize {
+        self.num_aux
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Writes the header, constraints and wire to label sections, and returns
+    /// the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let field_size = field_size::<E>();
+        let num_wires = self.num_inputs + self.num_aux;
+
+        let mut constraints = Vec::new();
+        for (a, b, c) in &self.constraints {
+            for lc in &[a, b, c] {
+                constraints.write_u32::<LittleEndian>(lc.len() as u32)?;
+                for (var, coeff) in lc.iter_terms() {
+                    let wire = match var.get_unchecked() {
+                        Index::Input(i) => i,
+                        Index::Aux(i) => self.num_inputs + i,
+                    };
+                    constraints.write_u32::<LittleEndian>(wire as u32)?;
+                    constraints.write_all(&to_le_bytes(coeff.to_le_bits(), field_size))?;
+                }
+            }
+        }
+
+        self.writer.write_all(MAGIC)?;
+        self.writer.write_u32::<LittleEndian>(VERSION)?;
+        self.writer.write_u32::<LittleEndian>(3)?;
+
+        let header = R1CSHeader {
+            field_size: field_size as u32,
+            prime: to_le_bytes(E::Fr::char_le_bits(), field_size),
+            num_wires: num_wires as u32,
+            num_pub_out: 0,
+            num_pub_in: self.num_inputs as u32 - 1,
+            num_prv_in: 0,
+            num_labels: num_wires as u64,
+            num_constraints: self.constraints.len() as u32,
+        };
+        self.writer.write_u32::<LittleEndian>(SECTION_HEADER)?;
+        self.writer
+            .write_u64::<LittleEndian>(4 + field_size as u64 + 4 * 4 + 8 + 4)?;
+        header.write(&mut self.writer)?;
+
+        self.writer.write_u32::<LittleEndian>(SECTION_CONSTRAINTS)?;
+        self.writer
+            .write_u64::<LittleEndian>(constraints.len() as u64)?;
+        self.writer.write_all(&constraints)?;
+
+        self.writer
+            .write_u32::<LittleEndian>(SECTION_WIRE_TO_LABEL)?;
+        self.writer
+            .write_u64::<LittleEndian>(8 * num_wires as u64)?;
+        for wire in 0..num_wires {
+            self.writer.write_u64::<LittleEndian>(wire as u64)?;
+        }
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// The header section of an `.r1cs` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1CSHeader {
+    /// Size in bytes of a field element.
+    pub field_size: u32,
+    /// The field's modulus, in little-endian bytes.
+    pub prime: Vec<u8>,
+    pub num_wires: u32,
+    pub num_pub_out: u32,
+    pub num_pub_in: u32,
+    pub num_prv_in: u32,
+    pub num_labels: u64,
+    pub num_constraints: u32,
+}
+
+impl R1CSHeader {
+    /// Reads the header section of an `.r1cs` file, skipping the sections before
+    /// it.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an r1cs file",
+            ));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported r1cs version {}", version),
+            ));
+        }
+
+        let num_sections = reader.read_u32::<LittleEndian>()?;
+        for _ in 0..num_sections {
+            let section = reader.read_u32::<LittleEndian>()?;
+            let size = reader.read_u64::<LittleEndian>()?;
+            if section == SECTION_HEADER {
+                return Self::read_section(&mut reader);
+            }
+            io::copy(&mut (&mut reader).take(size), &mut io::sink())?;
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing r1cs header section",
+        ))
+    }
+
+    fn read_section<R: Read>(mut reader: R) -> io::Result<Self> {
+        let field_size = reader.read_u32::<LittleEndian>()?;
+        let mut prime = vec![0u8; field_size as usize];
+        reader.read_exact(&mut prime)?;
+
+        Ok(R1CSHeader {
+            field_size,
+            prime,
+            num_wires: reader.read_u32::<LittleEndian>()?,
+            num_pub_out: reader.read_u32::<LittleEndian>()?,
+            num_pub_in: reader.read_u32::<LittleEndian>()?,
+            num_prv_in: reader.read_u32::<LittleEndian>()?,
+            num_labels: reader.read_u64::<LittleEndian>()?,
+            num_constraints: reader.read_u32::<LittleEndian>()?,
+        })
+    }
+
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u32::<LittleEndian>(self.field_size)?;
+        writer.write_all(&self.prime)?;
+        writer.write_u32::<LittleEndian>(self.num_wires)?;
+        writer.write_u32::<LittleEndian>(self.num_pub_out)?;
+        writer.write_u32::<LittleEndian>(self.num_pub_in)?;
+        writer.write_u32::<LittleEndian>(self.num_prv_in)?;
+        writer.write_u64::<LittleEndian>(self.num_labels)?;
+        writer.write_u32::<LittleEndian>(self.num_constraints)?;
+
+        Ok(())
+    }
+}
+
+/// Size in bytes of a field element: the format uses a multiple of 8 bytes.
+fn field_size<E: Engine>() -> usize {
+    (E::Fr::NUM_BITS as usize + 63) / 64 * 8
+}
+
+fn to_le_bytes<B: IntoIterator<Item = bool>>(bits: B, size: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; size];
+    for (i, _) in bits.into_iter().enumerate().filter(|(_, bit)| *bit) {
+        bytes[i / 8] |= 1 << (i % 8);
+    }
+    bytes
+}
+
+impl<E, W> ConstraintSystem<E> for R1CSWriter<E, W>
+where
+    E: Engine,
+    E::Fr: PrimeFieldBits,
+    W: Write + Send,
+{
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_aux += 1;
+
+        Ok(Variable::new_unchecked(Index::Aux(self.num_aux - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_inputs += 1;
+
+        Ok(Variable::new_unchecked(Index::Input(self.num_inputs - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        let a = a(LinearCombination::zero());
+        let b = b(LinearCombination::zero());
+        let c = c(LinearCombination::zero());
+
+        self.constraints.push((a, b, c));
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn mode(&self) -> SynthesisMode {
+        SynthesisMode::Setup
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::multipack;
+    use crate::gadgets::sha256::sha256;
+    use crate::util_cs::metric_cs::MetricCS;
+    use blstrs::{Bls12, Scalar as Fr};
+    use ff::Field;
+
+    fn synthesize<CS: ConstraintSystem<Bls12>>(cs: &mut CS) {
+        let bits = (0..64)
+            .map(|i| {
+                Boolean::from(
+                    AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), Some(i % 3 == 0))
+                        .unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let hash = sha256(cs.namespace(|| "sha256"), &bits).unwrap();
+        multipack::pack_into_inputs(cs.namespace(|| "pack"), &hash).unwrap();
+    }
+
+    #[test]
+    fn test_r1cs_writer() {
+        let mut metric = MetricCS::<Bls12>::new();
+        synthesize(&mut metric);
+
+        let mut cs = R1CSWriter::<Bls12, _>::new(vec![]);
+        synthesize(&mut cs);
+        let bytes = cs.finish().unwrap();
+
+        let header = R1CSHeader::read(&bytes[..]).unwrap();
+        assert_eq!(header.field_size, 32);
+        // The BLS12-381 scalar field modulus, little-endian.
+        assert_eq!(
+            header.prime,
+            hex::decode("01000000fffffffffe5bfeff02a4bd5305d8a10908d83933487d9d2953a7ed73")
+                .unwrap()
+        );
+        assert_eq!(header.num_constraints as usize, metric.num_constraints());
+        assert_eq!(header.num_pub_in as usize, metric.num_inputs() - 1);
+        assert_eq!(
+            header.num_wires as usize,
+            metric.num_inputs() + metric.num_aux()
+        );
+        assert_eq!(header.num_labels, u64::from(header.num_wires));
+
+        // The constraints section follows the header, starting with the first
+        // bit's booleanity constraint: (1 - bit) * (bit) = 0.
+        let mut reader = &bytes[12 + 12 + 64..];
+        assert_eq!(
+            reader.read_u32::<LittleEndian>().unwrap(),
+            SECTION_CONSTRAINTS
+        );
+        reader.read_u64::<LittleEndian>().unwrap();
+        let bit = metric.num_inputs() as u32;
+        let one = to_le_bytes(Fr::one().to_le_bits(), 32);
+        let minus_one = to_le_bytes((-Fr::one()).to_le_bits(), 32);
+
+        fn read_lc(reader: &mut &[u8], terms: &[(u32, &[u8])]) {
+            assert_eq!(
+                reader.read_u32::<LittleEndian>().unwrap() as usize,
+                terms.len()
+            );
+            for (wire, coeff) in terms {
+                assert_eq!(reader.read_u32::<LittleEndian>().unwrap(), *wire);
+                assert_eq!(&reader[..32], *coeff);
+                *reader = &reader[32..];
+            }
+        }
+        read_lc(&mut reader, &[(0, &one[..]), (bit, &minus_one[..])]);
+        read_lc(&mut reader, &[(bit, &one[..])]);
+        read_lc(&mut reader, &[]);
+
+        assert!(R1CSHeader::read(&bytes[1..]).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use crate::{ConstraintSystem, LinearCombination, SynthesisError, SynthesisMode, Variable};
+use pairing::Engine;
+
+/// The order in which `ReorderCS` emits its buffered constraints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintOrder {
+    /// The order in which the constraints were enforced.
+    Synthesis,
+    /// Constraints of the same namespace are emitted together, with namespaces
+    /// in order of their first constraint.
+    Namespace,
+    /// Constraints with fewer terms are emitted first.
+    Density,
+}
+
+struct BufferedConstraint<E: Engine> {
+    namespace: Vec<String>,
+    annotation: String,
+    a: LinearCombination<E>,
+    b: LinearCombination<E>,
+    c: LinearCombination<E>,
+}
+
+impl<E: Engine> BufferedConstraint<E> {
+    fn num_terms(&self) -> usize {
+        self.a.len() + self.b.len() + self.c.len()
+    }
+}
+
+/// Constraint system wrapper which forwards allocations to an inner constraint
+/// system immediately, but buffers constraints and emits them in the configured
+/// `ConstraintOrder` when flushed or dropped.
+///
+/// Buffered constraints are emitted in the inner constraint system's current
+/// namespace, annotated with their full path joined by `:`.
+///
+/// This is experimental: the order constraints are emitted in affects the memory
+/// access patterns of the prover.
+#[allow(clippy::upper_case_acronyms)]
+pub struct ReorderCS<E: Engine, CS: ConstraintSystem<E>> {
+    inner: CS,
+    order: ConstraintOrder,
+    current_namespace: Vec<String>,
+    constraints: Vec<BufferedConstraint<E>>,
+    _e: PhantomData<E>,
+}
+
+impl<E: Engine, CS: ConstraintSystem<E>> ReorderCS<E, CS> {
+    pub fn new(inner: CS, order: ConstraintOrder) -> Self {
+        ReorderCS {
+            inner,
+            order,
+            current_namespace: vec![],
+            constraints: vec![],
+            _e: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &CS {
+        &self.inner
+    }
+
+    /// Number of constraints waiting to be emitted.
+    pub fn num_buffered(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Emits all buffered constraints to the inner constraint system.
+    pub fn flush(&mut self) {
+        let mut constraints = std::mem::take(&mut self.constraints);
+
+        match self.order {
+            ConstraintOrder::Synthesis => {}
+            ConstraintOrder::Namespace => {
+                let mut groups = HashMap::new();
+                for constraint in &constraints {
+                    let next = groups.len();
+                    groups.entry(constraint.namespace.clone()).or_insert(next);
+                }
+                constraints.sort_by_key(|constraint| groups[&constraint.namespace]);
+            }
+            ConstraintOrder::Density => {
+                constraints.sort_by_key(|constraint| constraint.num_terms());
+            }
+        }
+
+        for constraint in constraints {
+            let BufferedConstraint {
+                mut namespace,
+                annotation,
+                a,
+                b,
+                c,
+            } = constraint;
+            namespace.push(annotation);
+
+            self.inner
+                .enforce(|| namespace.join(":"), |_| a, |_| b, |_| c);
+        }
+    }
+}
+
+impl<E: Engine, CS: ConstraintSystem<E>> Drop for ReorderCS<E, CS> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for ReorderCS<E, CS> {
+    type Root = Self;
+
+    fn one() -> Variable {
+        CS::one()
+    }
+
+    fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.alloc(annotation, f)
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.alloc_input(annotation, f)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.constraints.push(BufferedConstraint {
+            namespace: self.current_namespace.clone(),
+            annotation: annotation().into(),
+            a: a(LinearCombination::zero()),
+            b: b(LinearCombination::zero()),
+            c: c(LinearCombination::zero()),
+        });
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = name_fn().into();
+        self.current_namespace.push(name.clone());
+        self.inner.get_root().push_namespace(|| name)
+    }
+
+    fn pop_namespace(&mut self) {
+        self.current_namespace.pop();
+        self.inner.get_root().pop_namespace()
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn mode(&self) -> SynthesisMode {
+        self.inner.mode()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util_cs::test_cs::TestConstraintSystem;
+    use blstrs::{Bls12, Scalar as Fr};
+    use ff::Field;
+
+    fn synthesize<CS: ConstraintSystem<Bls12>>(cs: &mut CS) {
+        let mut acc = vec![cs.alloc(|| "x", || Ok(Fr::from(2u64))).unwrap()];
+
+        for i in 0..10 {
+            {
+                let mut cs = cs.namespace(|| format!("square {}", i));
+                let prev = *acc.last().unwrap();
+                let value = Fr::from(2u64).pow_vartime(&[1 << (i + 1)]);
+                let square = cs.alloc(|| "square", || Ok(value)).unwrap();
+                cs.enforce(
+                    || "square constraint",
+                    |lc| lc + prev,
+                    |lc| lc + prev,
+                    |lc| lc + square,
+                );
+                acc.push(square);
+            }
+            {
+                let mut cs = cs.namespace(|| format!("sum {}", i));
+                let sum = cs
+                    .alloc(
+                        || "sum",
+                        || {
+                            let mut sum = Fr::zero();
+                            for k in 0..=i + 1 {
+                                sum += Fr::from(2u64).pow_vartime(&[1 << k]);
+                            }
+                            Ok(sum)
+                        },
+                    )
+                    .unwrap();
+                let terms = acc.clone();
+                cs.enforce(
+                    || "sum constraint",
+                    |lc| terms.iter().fold(lc, |lc, v| lc + *v),
+                    |lc| lc + CS::one(),
+                    |lc| lc + sum,
+                );
+                cs.enforce(
+                    || "sum again",
+                    |lc| lc + sum,
+                    |lc| lc + CS::one(),
+                    |lc| lc + sum,
+                );
+            }
+
+            let last = *acc.last().unwrap();
+            cs.enforce(
+                || format!("root {}", i),
+                |lc| lc + last,
+                |lc| lc + CS::one(),
+                |lc| lc + last,
+            );
+        }
+    }
+
+    #[test]
+    fn test_reorder_cs() {
+        let mut expected = TestConstraintSystem::<Bls12>::new();
+        synthesize(&mut expected);
+        assert!(expected.is_satisfied());
+
+        for &order in &[
+            ConstraintOrder::Synthesis,
+            ConstraintOrder::Namespace,
+            ConstraintOrder::Density,
+        ] {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            {
+                let mut reorder = ReorderCS::new(&mut cs, order);
+                synthesize(&mut reorder);
+                assert_eq!(reorder.num_buffered(), expected.num_constraints());
+                assert_eq!(reorder.inner().num_constraints(), 0);
+            }
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), expected.num_constraints());
+            assert_eq!(cs.canonical_hash(), expected.canonical_hash());
+            assert_eq!(
+                cs.hash() == expected.hash(),
+                order == ConstraintOrder::Synthesis
+            );
+        }
+    }
+}
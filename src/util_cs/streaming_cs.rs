@@ -0,0 +1,284 @@
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, SynthesisMode, Variable};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use ff::PrimeField;
+use pairing::Engine;
+
+const TAG_INPUT: u8 = 0;
+const TAG_AUX: u8 = 1;
+
+/// Constraint system which writes every constraint to `W` as soon as it is
+/// enforced, keeping only counters in memory. Variable values are never
+/// computed.
+///
+/// Each constraint is written as its A, B and C linear combinations, each of
+/// them as a big-endian `u32` number of terms followed by the terms. A term is
+/// a tag byte (`0` for inputs, `1` for aux variables), the variable's index as a
+/// big-endian `u64`, and the coefficient's `to_repr` bytes. Use
+/// `read_constraint` to read them back.
+///
+/// Writes are not buffered, so `W` should usually be a `BufWriter`. Since
+/// `enforce` cannot fail, the first write error is kept and returned by `finish`,
+/// and nothing is written after it.
+#[allow(clippy::upper_case_acronyms)]
+pub struct StreamingR1CSWriter<E: Engine, W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+    num_inputs: usize,
+    num_aux: usize,
+    num_constraints: usize,
+    _e: PhantomData<E>,
+}
+
+impl<E: Engine, W: Write> StreamingR1CSWriter<E, W> {
+    pub fn new(writer: W) -> Self {
+        StreamingR1CSWriter {
+            writer,
+            error: None,
+            // The "one" input.
+            num_inputs: 1,
+            num_aux: 0,
+            num_constraints: 0,
+            _e: PhantomData,
+        }
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    pub fn num_aux(&self) -> usize {
+        self.num_aux
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    /// Flushes and returns the writer, or the first error encountered while
+    /// writing constraints.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn write_constraint(
+        &mut self,
+        a: &LinearCombination<E>,
+        b: &LinearCombination<E>,
+        c: &LinearCombination<E>,
+    ) -> io::Result<()> {
+        for lc in &[a, b, c] {
+            self.writer.write_u32::<BigEndian>(lc.len() as u32)?;
+            for (var, coeff) in lc.iter_terms() {
+                let (tag, index) = match var.get_unchecked() {
+                    Index::Input(i) => (TAG_INPUT, i),
+                    Index::Aux(i) => (TAG_AUX, i),
+                };
+                self.writer.write_u8(tag)?;
+                self.writer.write_u64::<BigEndian>(index as u64)?;
+                self.writer.write_all(coeff.to_repr().as_ref())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a constraint written by `StreamingR1CSWriter`, returning `None` at the
+/// end of the stream.
+#[allow(clippy::type_complexity)]
+pub fn read_constraint<E: Engine, R: Read>(
+    mut reader: R,
+) -> io::Result<
+    Option<(
+        LinearCombination<E>,
+        LinearCombination<E>,
+        LinearCombination<E>,
+    )>,
+> {
+    let mut first = [0u8; 1];
+    if reader.read(&mut first)? == 0 {
+        return Ok(None);
+    }
+    let mut rest = [0u8; 3];
+    reader.read_exact(&mut rest)?;
+    let len_a = u32::from_be_bytes([first[0], rest[0], rest[1], rest[2]]);
+
+    let a = read_lc(&mut reader, len_a)?;
+    let len_b = reader.read_u32::<BigEndian>()?;
+    let b = read_lc(&mut reader, len_b)?;
+    let len_c = reader.read_u32::<BigEndian>()?;
+    let c = read_lc(&mut reader, len_c)?;
+
+    Ok(Some((a, b, c)))
+}
+
+fn read_lc<E: Engine, R: Read>(reader: &mut R, len: u32) -> io::Result<LinearCombination<E>> {
+    let mut lc = LinearCombination::zero();
+    for _ in 0..len {
+        let tag = reader.read_u8()?;
+        let index = reader.read_u64::<BigEndian>()? as usize;
+        let index = match tag {
+            TAG_INPUT => Index::Input(index),
+            TAG_AUX => Index::Aux(index),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid variable tag",
+                ))
+            }
+        };
+
+        let mut repr = <E::Fr as PrimeField>::Repr::default();
+        reader.read_exact(repr.as_mut())?;
+        let coeff = Option::from(E::Fr::from_repr(repr))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid coefficient"))?;
+
+        lc = lc + (coeff, Variable::new_unchecked(index));
+    }
+
+    Ok(lc)
+}
+
+impl<E: Engine, W: Write + Send> ConstraintSystem<E> for StreamingR1CSWriter<E, W> {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_aux += 1;
+
+        Ok(Variable::new_unchecked(Index::Aux(self.num_aux - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_inputs += 1;
+
+        Ok(Variable::new_unchecked(Index::Input(self.num_inputs - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.num_constraints += 1;
+        if self.error.is_some() {
+            return;
+        }
+
+        let a = a(LinearCombination::zero());
+        let b = b(LinearCombination::zero());
+        let c = c(LinearCombination::zero());
+
+        if let Err(err) = self.write_constraint(&a, &b, &c) {
+            self.error = Some(err);
+        }
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn mode(&self) -> SynthesisMode {
+        SynthesisMode::Setup
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::sha256::sha256;
+    use crate::util_cs::test_cs::TestConstraintSystem;
+    use blstrs::{Bls12, Scalar as Fr};
+    use ff::Field;
+
+    fn synthesize<CS: ConstraintSystem<Bls12>>(cs: &mut CS) {
+        let x = cs.alloc(|| "x", || Ok(Fr::from(3u64))).unwrap();
+        let y = cs.alloc_input(|| "y", || Ok(Fr::from(9u64))).unwrap();
+        cs.enforce(|| "x * x = y", |lc| lc + x, |lc| lc + x, |lc| lc + y);
+
+        let bits = (0..64)
+            .map(|i| {
+                Boolean::from(
+                    AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), Some(i % 3 == 0))
+                        .unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        sha256(cs.namespace(|| "sha256"), &bits).unwrap();
+    }
+
+    #[test]
+    fn test_streaming_r1cs_writer() {
+        let mut expected = TestConstraintSystem::<Bls12>::new();
+        synthesize(&mut expected);
+
+        let mut cs = StreamingR1CSWriter::<Bls12, _>::new(vec![]);
+        synthesize(&mut cs);
+        assert_eq!(cs.num_constraints(), expected.num_constraints());
+        assert_eq!(cs.num_inputs(), expected.num_inputs());
+        let num_aux = cs.num_aux();
+
+        let bytes = cs.finish().unwrap();
+        let mut reader = &bytes[..];
+
+        let (a, b, c) = read_constraint::<Bls12, _>(&mut reader).unwrap().unwrap();
+        let x = Variable::new_unchecked(Index::Aux(0));
+        let y = Variable::new_unchecked(Index::Input(1));
+        assert_eq!(a.iter_terms().collect::<Vec<_>>(), vec![(x, &Fr::one())]);
+        assert_eq!(b.iter_terms().collect::<Vec<_>>(), vec![(x, &Fr::one())]);
+        assert_eq!(c.iter_terms().collect::<Vec<_>>(), vec![(y, &Fr::one())]);
+
+        let mut count = 1;
+        while let Some((a, b, c)) = read_constraint::<Bls12, _>(&mut reader).unwrap() {
+            for lc in &[a, b, c] {
+                for (var, _) in lc.iter_terms() {
+                    match var.get_unchecked() {
+                        Index::Input(i) => assert!(i < expected.num_inputs()),
+                        Index::Aux(i) => assert!(i < num_aux),
+                    }
+                }
+            }
+            count += 1;
+        }
+        assert_eq!(count, expected.num_constraints());
+    }
+
+    #[test]
+    fn test_streaming_r1cs_writer_error() {
+        let mut buf = [0u8; 16];
+        let mut cs = StreamingR1CSWriter::<Bls12, _>::new(&mut buf[..]);
+        synthesize(&mut cs);
+
+        assert!(cs.finish().is_err());
+    }
+}
@@ -1,8 +1,9 @@
-use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::ops::{AddAssign, MulAssign};
 
+use crate::multicore::Worker;
 use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
 use blake2s_simd::State as Blake2s;
 use byteorder::{BigEndian, ByteOrder};
@@ -20,50 +21,29 @@ enum NamedObject {
 pub struct TestConstraintSystem<E: Engine> {
     named_objects: HashMap<String, NamedObject>,
     current_namespace: Vec<String>,
-    #[allow(clippy::type_complexity)]
-    constraints: Vec<(
-        LinearCombination<E>,
-        LinearCombination<E>,
-        LinearCombination<E>,
-        String,
-    )>,
+    constraints: Vec<Constraint<E>>,
     inputs: Vec<(E::Fr, String)>,
     aux: Vec<(E::Fr, String)>,
+    incremental: Option<IncrementalCheck>,
 }
 
-#[derive(Clone, Copy)]
-struct OrderedVariable(Variable);
-
-impl Eq for OrderedVariable {}
-impl PartialEq for OrderedVariable {
-    fn eq(&self, other: &OrderedVariable) -> bool {
-        match (self.0.get_unchecked(), other.0.get_unchecked()) {
-            (Index::Input(ref a), Index::Input(ref b)) => a == b,
-            (Index::Aux(ref a), Index::Aux(ref b)) => a == b,
-            _ => false,
-        }
-    }
-}
-impl PartialOrd for OrderedVariable {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-impl Ord for OrderedVariable {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self.0.get_unchecked(), other.0.get_unchecked()) {
-            (Index::Input(ref a), Index::Input(ref b)) => a.cmp(b),
-            (Index::Aux(ref a), Index::Aux(ref b)) => a.cmp(b),
-            (Index::Input(_), Index::Aux(_)) => Ordering::Less,
-            (Index::Aux(_), Index::Input(_)) => Ordering::Greater,
-        }
-    }
+/// State kept by `is_satisfied_incremental` between calls.
+#[derive(Default)]
+struct IncrementalCheck {
+    /// The constraints referencing each variable.
+    constraints_by_var: BTreeMap<Variable, Vec<usize>>,
+    /// Number of constraints in `constraints_by_var`.
+    num_indexed: usize,
+    /// Constraints which were unsatisfied when last evaluated.
+    unsatisfied: BTreeSet<usize>,
+    /// Variables changed through `set` since the last check.
+    changed: BTreeSet<Variable>,
 }
 
-fn proc_lc<E: Engine>(terms: &LinearCombination<E>) -> BTreeMap<OrderedVariable, E::Fr> {
+fn proc_lc<E: Engine>(terms: &LinearCombination<E>) -> BTreeMap<Variable, E::Fr> {
     let mut map = BTreeMap::new();
     for (var, &coeff) in terms.iter() {
-        map.entry(OrderedVariable(var))
+        map.entry(var)
             .or_insert_with(E::Fr::zero)
             .add_assign(&coeff);
     }
@@ -83,7 +63,7 @@ fn proc_lc<E: Engine>(terms: &LinearCombination<E>) -> BTreeMap<OrderedVariable,
     map
 }
 
-fn hash_lc<E: Engine>(terms: &LinearCombination<E>, h: &mut Blake2s) {
+pub(crate) fn hash_lc<E: Engine>(terms: &LinearCombination<E>, h: &mut Blake2s) {
     let map = proc_lc::<E>(terms);
 
     let mut buf = [0u8; 9 + 32];
@@ -91,7 +71,7 @@ fn hash_lc<E: Engine>(terms: &LinearCombination<E>, h: &mut Blake2s) {
     h.update(&buf[0..8]);
 
     for (var, coeff) in map {
-        match var.0.get_unchecked() {
+        match var.get_unchecked() {
             Index::Input(i) => {
                 buf[0] = b'I';
                 BigEndian::write_u64(&mut buf[1..9], i as u64);
@@ -111,6 +91,15 @@ fn hash_lc<E: Engine>(terms: &LinearCombination<E>, h: &mut Blake2s) {
     }
 }
 
+/// Returns a BLAKE2s fingerprint of `lc`. Repeated variables are merged and
+/// zero terms dropped first, so equal linear combinations hash the same
+/// regardless of how their terms were added.
+pub fn hash_linear_combination<E: Engine>(lc: &LinearCombination<E>) -> [u8; 32] {
+    let mut h = Blake2s::new();
+    hash_lc::<E>(lc, &mut h);
+    *h.finalize().as_array()
+}
+
 fn _eval_lc2<E: Engine>(terms: &LinearCombination<E>, inputs: &[E::Fr], aux: &[E::Fr]) -> E::Fr {
     let mut acc = E::Fr::zero();
 
@@ -147,6 +136,71 @@ fn eval_lc<E: Engine>(
     acc
 }
 
+/// A constraint with its path, as stored by the test constraint systems.
+pub(crate) type Constraint<E> = (
+    LinearCombination<E>,
+    LinearCombination<E>,
+    LinearCombination<E>,
+    String,
+);
+
+/// Returns whether `A * B = C` holds for `constraint` under the assignments.
+pub(crate) fn constraint_satisfied<E: Engine>(
+    constraint: &Constraint<E>,
+    inputs: &[(E::Fr, String)],
+    aux: &[(E::Fr, String)],
+) -> bool {
+    let (ref a, ref b, ref c, _) = *constraint;
+    let mut a = eval_lc::<E>(a, inputs, aux);
+    let b = eval_lc::<E>(b, inputs, aux);
+    let c = eval_lc::<E>(c, inputs, aux);
+
+    a.mul_assign(&b);
+
+    a == c
+}
+
+/// Returns the paths of the unsatisfied constraints, in order. Backs
+/// `which_are_unsatisfied` of both test constraint systems.
+pub(crate) fn unsatisfied_paths<E: Engine>(
+    constraints: &[Constraint<E>],
+    inputs: &[(E::Fr, String)],
+    aux: &[(E::Fr, String)],
+) -> Vec<String> {
+    constraints
+        .iter()
+        .filter(|constraint| !constraint_satisfied(constraint, inputs, aux))
+        .map(|constraint| constraint.3.clone())
+        .collect()
+}
+
+/// Like `unsatisfied_paths`, but evaluates the constraints in parallel.
+pub(crate) fn unsatisfied_paths_parallel<E: Engine>(
+    constraints: &[Constraint<E>],
+    inputs: &[(E::Fr, String)],
+    aux: &[(E::Fr, String)],
+) -> Vec<String> {
+    let mut satisfied = vec![true; constraints.len()];
+
+    Worker::new().scope(satisfied.len(), |scope, chunk_size| {
+        for (i, satisfied) in satisfied.chunks_mut(chunk_size).enumerate() {
+            scope.execute(move || {
+                for (j, satisfied) in satisfied.iter_mut().enumerate() {
+                    *satisfied =
+                        constraint_satisfied(&constraints[i * chunk_size + j], inputs, aux);
+                }
+            });
+        }
+    });
+
+    constraints
+        .iter()
+        .zip(satisfied)
+        .filter(|(_, satisfied)| !satisfied)
+        .map(|(constraint, _)| constraint.3.clone())
+        .collect()
+}
+
 impl<E: Engine> Default for TestConstraintSystem<E> {
     fn default() -> Self {
         let mut map = HashMap::new();
@@ -161,6 +215,7 @@ impl<E: Engine> Default for TestConstraintSystem<E> {
             constraints: vec![],
             inputs: vec![(E::Fr::one(), "ONE".into())],
             aux: vec![],
+            incremental: None,
         }
     }
 }
@@ -218,22 +273,109 @@ impl<E: Engine> TestConstraintSystem<E> {
         s
     }
 
-    pub fn which_is_unsatisfied(&self) -> Option<&str> {
-        for &(ref a, ref b, ref c, ref path) in &self.constraints {
-            let mut a = eval_lc::<E>(a, &self.inputs, &self.aux);
-            let b = eval_lc::<E>(b, &self.inputs, &self.aux);
-            let c = eval_lc::<E>(c, &self.inputs, &self.aux);
+    /// Like `hash`, but independent of the order in which the constraints were
+    /// enforced.
+    pub fn canonical_hash(&self) -> String {
+        let mut constraint_hashes = self
+            .constraints
+            .iter()
+            .map(|constraint| {
+                let mut h = Blake2s::new();
+                hash_lc::<E>(&constraint.0, &mut h);
+                hash_lc::<E>(&constraint.1, &mut h);
+                hash_lc::<E>(&constraint.2, &mut h);
+                h.finalize()
+            })
+            .collect::<Vec<_>>();
+        constraint_hashes.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+
+        let mut h = Blake2s::new();
+        {
+            let mut buf = [0u8; 24];
+
+            BigEndian::write_u64(&mut buf[0..8], self.inputs.len() as u64);
+            BigEndian::write_u64(&mut buf[8..16], self.aux.len() as u64);
+            BigEndian::write_u64(&mut buf[16..24], self.constraints.len() as u64);
+            h.update(&buf);
+        }
 
-            a.mul_assign(&b);
+        for constraint_hash in &constraint_hashes {
+            h.update(constraint_hash.as_bytes());
+        }
 
-            if a != c {
-                return Some(&*path);
+        let mut s = String::new();
+        for b in h.finalize().as_ref() {
+            s += &format!("{:02x}", b);
+        }
+
+        s
+    }
+
+    pub fn which_is_unsatisfied(&self) -> Option<&str> {
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            if !self.is_constraint_satisfied(i) {
+                return Some(&*constraint.3);
             }
         }
 
         None
     }
 
+    /// Returns the paths of all unsatisfied constraints, in the order they were
+    /// enforced.
+    pub fn which_are_unsatisfied(&self) -> Vec<String> {
+        unsatisfied_paths(&self.constraints, &self.inputs, &self.aux)
+    }
+
+    /// Like `which_are_unsatisfied`, but evaluates the constraints in parallel.
+    pub fn which_are_unsatisfied_parallel(&self) -> Vec<String> {
+        unsatisfied_paths_parallel(&self.constraints, &self.inputs, &self.aux)
+    }
+
+    fn is_constraint_satisfied(&self, index: usize) -> bool {
+        constraint_satisfied(&self.constraints[index], &self.inputs, &self.aux)
+    }
+
+    /// Like `is_satisfied`, but only re-evaluates the constraints referencing
+    /// variables changed through `set` (and any constraints added) since the
+    /// previous call. The first call evaluates every constraint.
+    pub fn is_satisfied_incremental(&mut self) -> bool {
+        let mut state = self.incremental.take().unwrap_or_default();
+
+        let mut to_check = BTreeSet::new();
+        for var in &state.changed {
+            if let Some(constraints) = state.constraints_by_var.get(var) {
+                to_check.extend(constraints.iter().copied());
+            }
+        }
+        state.changed.clear();
+
+        for index in state.num_indexed..self.constraints.len() {
+            let (ref a, ref b, ref c, _) = self.constraints[index];
+            for (var, _) in a.iter().chain(b.iter()).chain(c.iter()) {
+                let constraints = state.constraints_by_var.entry(var).or_insert_with(Vec::new);
+                if constraints.last() != Some(&index) {
+                    constraints.push(index);
+                }
+            }
+            to_check.insert(index);
+        }
+        state.num_indexed = self.constraints.len();
+
+        for index in to_check {
+            if self.is_constraint_satisfied(index) {
+                state.unsatisfied.remove(&index);
+            } else {
+                state.unsatisfied.insert(index);
+            }
+        }
+
+        let satisfied = state.unsatisfied.is_empty();
+        self.incremental = Some(state);
+
+        satisfied
+    }
+
     pub fn is_satisfied(&self) -> bool {
         match self.which_is_unsatisfied() {
             Some(b) => {
@@ -251,10 +393,15 @@ impl<E: Engine> TestConstraintSystem<E> {
 
     pub fn set(&mut self, path: &str, to: E::Fr) {
         match self.named_objects.get(path) {
-            Some(&NamedObject::Var(ref v)) => match v.get_unchecked() {
-                Index::Input(index) => self.inputs[index].0 = to,
-                Index::Aux(index) => self.aux[index].0 = to,
-            },
+            Some(&NamedObject::Var(ref v)) => {
+                match v.get_unchecked() {
+                    Index::Input(index) => self.inputs[index].0 = to,
+                    Index::Aux(index) => self.aux[index].0 = to,
+                }
+                if let Some(ref mut state) = self.incremental {
+                    state.changed.insert(*v);
+                }
+            }
             Some(e) => panic!(
                 "tried to set path `{}` to value, but `{:?}` already exists there.",
                 path, e
@@ -304,6 +451,30 @@ impl<E: Engine> TestConstraintSystem<E> {
         }
     }
 
+    /// Panics if the constraint at `path` uses a variable which was not
+    /// allocated by this constraint system, e.g. one kept from a different
+    /// system. Using variables allocated in other namespaces, including popped
+    /// ones, is fine.
+    #[cfg(debug_assertions)]
+    fn check_allocated(&self, path: &str, lcs: &[&LinearCombination<E>]) {
+        for (var, _) in lcs.iter().flat_map(|lc| lc.iter()) {
+            let allocated = match var.get_unchecked() {
+                Index::Input(i) => i < self.inputs.len(),
+                Index::Aux(i) => i < self.aux.len(),
+            };
+            assert!(
+                allocated,
+                "constraint `{}` uses {:?}, which was not allocated by this constraint system \
+                 (current namespace `{}`, {} inputs and {} aux variables allocated)",
+                path,
+                var,
+                self.current_namespace.join("/"),
+                self.inputs.len(),
+                self.aux.len()
+            );
+        }
+    }
+
     fn set_named_obj(&mut self, path: String, to: NamedObject) {
         if self.named_objects.contains_key(&path) {
             panic!("tried to create object at existing path: {}", path);
@@ -376,6 +547,9 @@ impl<E: Engine> ConstraintSystem<E> for TestConstraintSystem<E> {
         let b = b(LinearCombination::zero());
         let c = c(LinearCombination::zero());
 
+        #[cfg(debug_assertions)]
+        self.check_allocated(&path, &[&a, &b, &c]);
+
         self.constraints.push((a, b, c, path));
     }
 
@@ -418,6 +592,30 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "constraint `outer/inner/eq` uses Aux(1), which was not allocated by this \
+                    constraint system (current namespace `outer/inner`"
+    )]
+    fn test_enforce_foreign_variable() {
+        use blstrs::{Bls12, Scalar as Fr};
+
+        // A variable kept around after its constraint system is gone.
+        let foreign = {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            cs.alloc(|| "a", || Ok(Fr::one())).unwrap();
+            let mut ns = cs.namespace(|| "ns");
+            ns.alloc(|| "b", || Ok(Fr::one())).unwrap()
+        };
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let a = cs.alloc(|| "a", || Ok(Fr::one())).unwrap();
+        let mut outer = cs.namespace(|| "outer");
+        let mut inner = outer.namespace(|| "inner");
+        inner.enforce(|| "eq", |lc| lc + a, |lc| lc + foreign, |lc| lc + a);
+    }
+
     #[test]
     fn test_cs() {
         use blstrs::{Bls12, Scalar as Fr};
@@ -460,4 +658,117 @@ mod tests {
 
         assert!(cs.get("test1/test2/hehe") == Fr::one());
     }
+
+    #[test]
+    fn test_which_are_unsatisfied_parallel() {
+        use blstrs::{Bls12, Scalar as Fr};
+
+        // x_{i+1} = x_i * x_i
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut value = Fr::from(3u64);
+        let mut prev = cs.alloc(|| "x 0", || Ok(value)).unwrap();
+        for i in 1..100_000 {
+            value = value.square();
+            let cur = cs.alloc(|| format!("x {}", i), || Ok(value)).unwrap();
+            cs.enforce(
+                || format!("square {}", i),
+                |lc| lc + prev,
+                |lc| lc + prev,
+                |lc| lc + cur,
+            );
+            prev = cur;
+        }
+
+        assert!(cs.which_are_unsatisfied().is_empty());
+        assert!(cs.which_are_unsatisfied_parallel().is_empty());
+
+        for &i in &[99_999, 3, 50_000, 1] {
+            cs.set(&format!("x {}", i), Fr::from(7u64));
+        }
+
+        let expected = [1, 2, 3, 4, 50_000, 50_001, 99_999]
+            .iter()
+            .map(|i| format!("square {}", i))
+            .collect::<Vec<_>>();
+        assert_eq!(cs.which_are_unsatisfied(), expected);
+        assert_eq!(cs.which_are_unsatisfied_parallel(), expected);
+        assert_eq!(cs.which_is_unsatisfied(), Some("square 1"));
+    }
+
+    #[test]
+    fn test_is_satisfied_incremental() {
+        use blstrs::{Bls12, Scalar as Fr};
+
+        // x_{i+1} = x_i * x_i
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut value = Fr::from(3u64);
+        let mut prev = cs.alloc(|| "x 0", || Ok(value)).unwrap();
+        for i in 1..1000 {
+            value = value.square();
+            let cur = cs.alloc(|| format!("x {}", i), || Ok(value)).unwrap();
+            cs.enforce(
+                || format!("square {}", i),
+                |lc| lc + prev,
+                |lc| lc + prev,
+                |lc| lc + cur,
+            );
+            prev = cur;
+        }
+
+        assert!(cs.is_satisfied_incremental());
+
+        let old = cs.get("x 500");
+        cs.set("x 500", Fr::from(7u64));
+        assert!(!cs.is_satisfied());
+        assert!(!cs.is_satisfied_incremental());
+
+        // Unrelated changes keep the earlier failure around.
+        let other = cs.get("x 10");
+        cs.set("x 10", other);
+        assert!(!cs.is_satisfied_incremental());
+
+        cs.set("x 500", old);
+        assert!(cs.is_satisfied());
+        assert!(cs.is_satisfied_incremental());
+
+        // Constraints added after the first check are picked up.
+        let one = TestConstraintSystem::<Bls12>::one();
+        cs.enforce(|| "broken", |lc| lc + prev, |lc| lc + one, |lc| lc + one);
+        assert_eq!(cs.is_satisfied_incremental(), cs.is_satisfied());
+        assert!(!cs.is_satisfied_incremental());
+    }
+
+    #[test]
+    fn test_hash_linear_combination() {
+        use blstrs::{Bls12, Scalar as Fr};
+
+        let a = Variable::new_unchecked(Index::Aux(0));
+        let b = Variable::new_unchecked(Index::Aux(1));
+        let x = Variable::new_unchecked(Index::Input(1));
+
+        let lc = LinearCombination::<Bls12>::zero() + (Fr::from(2u64), a) + b + x;
+        let reordered = LinearCombination::<Bls12>::zero()
+            + x
+            + (Fr::from(3u64), b)
+            + a
+            + a
+            + (Fr::from(5u64), x)
+            - (Fr::from(2u64), b)
+            - (Fr::from(5u64), x);
+        assert_eq!(
+            hash_linear_combination(&lc),
+            hash_linear_combination(&reordered)
+        );
+
+        let other = LinearCombination::<Bls12>::zero() + (Fr::from(2u64), a) + b;
+        assert_ne!(
+            hash_linear_combination(&lc),
+            hash_linear_combination(&other)
+        );
+        let other = LinearCombination::<Bls12>::zero() + a + b + x;
+        assert_ne!(
+            hash_linear_combination(&lc),
+            hash_linear_combination(&other)
+        );
+    }
 }
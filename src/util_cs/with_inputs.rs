@@ -0,0 +1,71 @@
+use crate::{Circuit, ConstraintSystem, SynthesisError};
+use pairing::Engine;
+
+/// Circuit adapter which allocates the given public inputs, in order, before
+/// synthesizing the wrapped circuit. This lets a test harness run the same
+/// circuit logic against different public inputs.
+///
+/// The inputs are allocated first, so the wrapped circuit can refer to the
+/// `i`-th of them as `Variable::new_unchecked(Index::Input(i + 1))`, the input
+/// at index 0 being the implicit "one".
+pub struct WithInputs<E: Engine, C>(pub C, pub Vec<E::Fr>);
+
+impl<E: Engine, C: Circuit<E>> Circuit<E> for WithInputs<E, C> {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        for (i, input) in self.1.into_iter().enumerate() {
+            cs.alloc_input(|| format!("input {}", i), || Ok(input))?;
+        }
+
+        self.0.synthesize(cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util_cs::test_cs::TestConstraintSystem;
+    use crate::{Index, Variable};
+    use blstrs::{Bls12, Scalar as Fr};
+
+    /// Enforces that its first two inputs multiply to the third one.
+    struct Product;
+
+    impl Circuit<Bls12> for Product {
+        fn synthesize<CS: ConstraintSystem<Bls12>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            let input = |i: usize| Variable::new_unchecked(Index::Input(i + 1));
+            cs.enforce(
+                || "product",
+                |lc| lc + input(0),
+                |lc| lc + input(1),
+                |lc| lc + input(2),
+            );
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_with_inputs() {
+        let fr = |v: u64| Fr::from(v);
+
+        for inputs in &[vec![fr(2), fr(3), fr(6)], vec![fr(5), fr(7), fr(35)]] {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            WithInputs(Product, inputs.clone())
+                .synthesize(&mut cs)
+                .unwrap();
+            assert!(cs.is_satisfied());
+            assert!(cs.verify(inputs));
+            assert!(!cs.verify(&[fr(2), fr(3), fr(7)]));
+        }
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        WithInputs(Product, vec![fr(2), fr(3), fr(7)])
+            .synthesize(&mut cs)
+            .unwrap();
+        assert!(cs.verify(&[fr(2), fr(3), fr(7)]));
+        assert_eq!(cs.which_is_unsatisfied(), Some("product"));
+    }
+}
@@ -0,0 +1,94 @@
+use std::marker::PhantomData;
+
+use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use ff::Field;
+use pairing::Engine;
+
+/// Constraint system which only computes the assignment of a circuit. The
+/// constraints and names are discarded, which makes it a cheap way to derive
+/// the public inputs a circuit produces for a given witness.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+pub struct WitnessCS<E: Engine> {
+    input_assignment: Vec<E::Fr>,
+    aux_assignment: Vec<E::Fr>,
+    _e: PhantomData<E>,
+}
+
+impl<E: Engine> WitnessCS<E> {
+    pub fn new() -> Self {
+        WitnessCS::default()
+    }
+
+    /// The values of the inputs, starting with the implicit "one" input.
+    pub fn input_assignment(&self) -> &[E::Fr] {
+        &self.input_assignment
+    }
+
+    pub fn aux_assignment(&self) -> &[E::Fr] {
+        &self.aux_assignment
+    }
+}
+
+impl<E: Engine> Default for WitnessCS<E> {
+    fn default() -> Self {
+        WitnessCS {
+            input_assignment: vec![E::Fr::one()],
+            aux_assignment: vec![],
+            _e: PhantomData,
+        }
+    }
+}
+
+impl<E: Engine> ConstraintSystem<E> for WitnessCS<E> {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux_assignment.push(f()?);
+
+        Ok(Variable::new_unchecked(Index::Aux(
+            self.aux_assignment.len() - 1,
+        )))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.input_assignment.push(f()?);
+
+        Ok(Variable::new_unchecked(Index::Input(
+            self.input_assignment.len() - 1,
+        )))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, _a: LA, _b: LB, _c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+    }
+
+    fn push_namespace<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
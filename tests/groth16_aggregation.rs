@@ -1,10 +1,12 @@
 use bellperson::gadgets::num::AllocatedNum;
 use bellperson::groth16::{
     aggregate::{
-        aggregate_proofs, setup_fake_srs, verify_aggregate_proof, AggregateProof, GenericSRS,
+        aggregate_proofs, aggregate_proofs_padded, aggregate_proofs_with_digest, setup_fake_srs,
+        verify_aggregate_proof, verify_aggregate_proof_padded, verify_aggregate_proof_with_digest,
+        AggregateProof, AggregatorState, GenericSRS, ProverSRS, VerifierSRS,
     },
     create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
-    verify_proofs_batch, Parameters, Proof,
+    verify_proofs_batch, Parameters, PreparedVerifyingKey, Proof,
 };
 use bellperson::{Circuit, ConstraintSystem, SynthesisError};
 use blstrs::{Bls12, Scalar as Fr};
@@ -20,6 +22,7 @@ use std::ops::{AddAssign, MulAssign};
 use std::time::{Duration, Instant};
 
 const MIMC_ROUNDS: usize = 322;
+const NUM_PUBLIC_INPUTS: usize = 4;
 
 /// This is an implementation of MiMC, specifically a
 /// variant named `LongsightF322p3` for BLS12-381.
@@ -421,6 +424,64 @@ fn generate_proof<R: SeedableRng + RngCore>(
     (create_random_proof(c, p, &mut rng).unwrap(), statement)
 }
 
+/// Like `generate_proof`, but with random inputs, so no two proofs share a
+/// statement.
+fn generate_distinct_proof<R: RngCore>(
+    publics: usize,
+    p: &Parameters<Bls12>,
+    mut rng: &mut R,
+) -> (Proof<Bls12>, Vec<Fr>) {
+    let inputs = (0..publics)
+        .map(|_| Fr::random(&mut rng))
+        .collect::<Vec<_>>();
+    let w = Fr::random(&mut rng);
+    let mut product = w;
+    for x in &inputs {
+        product.mul_assign(x);
+    }
+
+    let c = TestCircuit {
+        public_inputs: inputs.iter().copied().map(Some).collect(),
+        public_product: Some(product),
+        witness_input: Some(w),
+    };
+    let proof = create_random_proof(c, p, &mut rng).unwrap();
+
+    let mut statement = inputs;
+    statement.push(product);
+    (proof, statement)
+}
+
+/// Creates the aggregation SRS specialized for `num_proofs` proofs and the
+/// parameters of a `TestCircuit` with `NUM_PUBLIC_INPUTS` inputs.
+fn setup(
+    num_proofs: usize,
+) -> (
+    ProverSRS<Bls12>,
+    VerifierSRS<Bls12>,
+    Parameters<Bls12>,
+    PreparedVerifyingKey<Bls12>,
+    rand_chacha::ChaChaRng,
+) {
+    let mut rng = rand_chacha::ChaChaRng::seed_from_u64(0u64);
+
+    let generic = setup_fake_srs(&mut rng, num_proofs);
+    let (pk, vk) = generic.specialize(num_proofs);
+
+    let params = {
+        let c = TestCircuit::<Bls12> {
+            public_inputs: vec![Default::default(); NUM_PUBLIC_INPUTS],
+            public_product: Default::default(),
+            witness_input: Default::default(),
+        };
+
+        generate_random_parameters(c, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+
+    (pk, vk, params, pvk, rng)
+}
+
 /// This test creates and aggregates some valid proofs, then tries a bunch of
 /// failing test case scenarios
 #[test]
@@ -587,6 +648,226 @@ fn test_groth16_aggregation() {
     .expect("no synthesis");
     assert_eq!(res, false);
     aggregate_proof.tmipp.gipa.final_a = old_finala;
+
+    // 6. verify with an SRS specialized for a different number of proofs
+    let (_, vk_half) = generic.specialize(NUM_PROOFS / 2);
+    let err = verify_aggregate_proof(
+        &vk_half,
+        &pvk,
+        &mut rng,
+        &statements,
+        &aggregate_proof,
+        &to_include,
+    )
+    .unwrap_err();
+    match err {
+        SynthesisError::MalformedProofs(msg) => assert_eq!(
+            msg,
+            "verifier SRS is specialized for 4 proofs but the aggregate proof contains 8"
+        ),
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn test_groth16_aggregation_distinct_inputs() {
+    const NUM_PROOFS: usize = 8;
+    let (pk, vk, params, pvk, mut rng) = setup(NUM_PROOFS);
+
+    // Every proof gets its own inputs.
+    let (proofs, mut statements): (Vec<_>, Vec<_>) = (0..NUM_PROOFS)
+        .map(|_| generate_distinct_proof(NUM_PUBLIC_INPUTS, &params, &mut rng))
+        .unzip();
+    for (proof, statement) in proofs.iter().zip(&statements) {
+        assert!(verify_proof(&pvk, proof, statement).unwrap());
+    }
+    assert!(statements.iter().tuple_windows().all(|(a, b)| a != b));
+
+    let to_include = vec![1, 2, 3];
+    let aggregate_proof =
+        aggregate_proofs::<Bls12>(&pk, &to_include, &proofs).expect("failed to aggregate proofs");
+    assert!(verify_aggregate_proof(
+        &vk,
+        &pvk,
+        &mut rng,
+        &statements,
+        &aggregate_proof,
+        &to_include,
+    )
+    .unwrap());
+
+    // The versioned encoding round-trips to a proof which still verifies.
+    let mut bytes = Vec::new();
+    aggregate_proof.write_versioned(&mut bytes).unwrap();
+    let read_proof = AggregateProof::<Bls12>::read_versioned(&bytes[..]).unwrap();
+    assert!(read_proof == aggregate_proof);
+    assert!(
+        verify_aggregate_proof(&vk, &pvk, &mut rng, &statements, &read_proof, &to_include,)
+            .unwrap()
+    );
+
+    // The inputs are bound to their proofs: swapping two statements fails.
+    statements.swap(0, 1);
+    assert!(!verify_aggregate_proof(
+        &vk,
+        &pvk,
+        &mut rng,
+        &statements,
+        &aggregate_proof,
+        &to_include,
+    )
+    .unwrap());
+}
+
+#[test]
+fn test_groth16_aggregation_incremental() {
+    const NUM_PROOFS: usize = 8;
+    let (pk, vk, params, pvk, mut rng) = setup(NUM_PROOFS);
+
+    let (proofs, statements): (Vec<_>, Vec<_>) = (0..NUM_PROOFS)
+        .map(|_| generate_proof(NUM_PUBLIC_INPUTS, &params, &mut rng))
+        .unzip();
+
+    let to_include = vec![1, 2, 3];
+    let mut state = AggregatorState::new(&pk);
+    for proof in &proofs {
+        state.push(proof).unwrap();
+    }
+    assert_eq!(state.len(), NUM_PROOFS);
+    // The SRS has no room for more proofs.
+    assert!(state.push(&proofs[0]).is_err());
+
+    let incremental = state.finalize(&to_include).unwrap();
+    let aggregate_proof = aggregate_proofs::<Bls12>(&pk, &to_include, &proofs).unwrap();
+    assert!(incremental == aggregate_proof);
+    assert!(
+        verify_aggregate_proof(&vk, &pvk, &mut rng, &statements, &incremental, &to_include,)
+            .unwrap()
+    );
+
+    // Partial aggregations are rejected.
+    let mut state = AggregatorState::new(&pk);
+    for proof in &proofs[..3] {
+        state.push(proof).unwrap();
+    }
+    assert!(matches!(
+        state.finalize(&to_include),
+        Err(SynthesisError::NonPowerOfTwo)
+    ));
+}
+
+#[test]
+fn test_groth16_aggregation_with_digest() {
+    const NUM_PROOFS: usize = 8;
+    let (pk, vk, params, pvk, mut rng) = setup(NUM_PROOFS);
+
+    let (proofs, statements): (Vec<_>, Vec<_>) = (0..NUM_PROOFS)
+        .map(|_| generate_proof(NUM_PUBLIC_INPUTS, &params, &mut rng))
+        .unzip();
+
+    let digest = [7u8; 32];
+    let aggregate_proof = aggregate_proofs_with_digest::<Bls12>(&pk, digest, &proofs).unwrap();
+    assert!(verify_aggregate_proof_with_digest(
+        &vk,
+        &pvk,
+        &mut rng,
+        &statements,
+        &aggregate_proof,
+        digest
+    )
+    .unwrap());
+
+    let mut other_digest = digest;
+    other_digest[31] ^= 1;
+    assert!(!verify_aggregate_proof_with_digest(
+        &vk,
+        &pvk,
+        &mut rng,
+        &statements,
+        &aggregate_proof,
+        other_digest
+    )
+    .unwrap());
+
+    // A digest is not interchangeable with the same bytes as transcript include.
+    assert!(
+        !verify_aggregate_proof(&vk, &pvk, &mut rng, &statements, &aggregate_proof, &digest)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_groth16_aggregation_padded() {
+    const NUM_PROOFS: usize = 5;
+    // The SRS is specialized for the padded number of proofs.
+    let (pk, vk, params, pvk, mut rng) = setup(8);
+
+    let (proofs, statements): (Vec<_>, Vec<_>) = (0..NUM_PROOFS)
+        .map(|_| generate_distinct_proof(NUM_PUBLIC_INPUTS, &params, &mut rng))
+        .unzip();
+
+    let to_include = vec![1, 2, 3];
+    assert!(matches!(
+        aggregate_proofs::<Bls12>(&pk, &to_include, &proofs),
+        Err(SynthesisError::NonPowerOfTwo)
+    ));
+    let aggregate_proof = aggregate_proofs_padded::<Bls12>(&pk, &to_include, &proofs).unwrap();
+    assert_eq!(aggregate_proof.tmipp.gipa.nproofs, 8);
+    assert!(verify_aggregate_proof_padded(
+        &vk,
+        &pvk,
+        &mut rng,
+        &statements,
+        &aggregate_proof,
+        &to_include,
+    )
+    .unwrap());
+
+    // A tampered real statement is rejected.
+    let mut tampered = statements.clone();
+    tampered[4][0] += Fr::one();
+    assert!(!verify_aggregate_proof_padded(
+        &vk,
+        &pvk,
+        &mut rng,
+        &tampered,
+        &aggregate_proof,
+        &to_include,
+    )
+    .unwrap());
+
+    // The number of real proofs is bound to the proof, even when the extra
+    // statement is the one the padding proves.
+    let mut extended = statements.clone();
+    extended.push(statements[4].clone());
+    assert!(!verify_aggregate_proof_padded(
+        &vk,
+        &pvk,
+        &mut rng,
+        &extended,
+        &aggregate_proof,
+        &to_include,
+    )
+    .unwrap());
+
+    // The padded proof is not a regular aggregate proof of the padded statements.
+    let mut padded = statements.clone();
+    padded.resize(8, statements[4].clone());
+    assert!(
+        !verify_aggregate_proof(&vk, &pvk, &mut rng, &padded, &aggregate_proof, &to_include,)
+            .unwrap()
+    );
+
+    // Too few statements for the padded size.
+    assert!(verify_aggregate_proof_padded(
+        &vk,
+        &pvk,
+        &mut rng,
+        &statements[..4],
+        &aggregate_proof,
+        &to_include,
+    )
+    .is_err());
 }
 
 #[test]
@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bellperson::groth16::{
+    create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    Parameters,
+};
+use bellperson::{Circuit, ConstraintSystem, SynthesisError};
+use blstrs::{Bls12, Scalar as Fr};
+use ff::Field;
+use pairing::Engine;
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+/// Proves knowledge of `x` such that `x^(2^iterations)` is the public input.
+#[derive(Clone)]
+struct RepeatedSquaring<E: Engine> {
+    x: Option<E::Fr>,
+    iterations: usize,
+}
+
+impl<E: Engine> Circuit<E> for RepeatedSquaring<E> {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let mut x_val = self.x;
+        let mut x = cs.alloc(|| "x", || x_val.ok_or(SynthesisError::AssignmentMissing))?;
+
+        for i in 0..self.iterations {
+            let x2_val = x_val.map(|x| x.square());
+            let x2 = if i + 1 == self.iterations {
+                cs.alloc_input(|| "out", || x2_val.ok_or(SynthesisError::AssignmentMissing))?
+            } else {
+                cs.alloc(
+                    || format!("x^2^{}", i + 1),
+                    || x2_val.ok_or(SynthesisError::AssignmentMissing),
+                )?
+            };
+            cs.enforce(
+                || format!("square {}", i),
+                |lc| lc + x,
+                |lc| lc + x,
+                |lc| lc + x2,
+            );
+
+            x = x2;
+            x_val = x2_val;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_read_sharded() {
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params = {
+        let c = RepeatedSquaring::<Bls12> {
+            x: None,
+            iterations: 10,
+        };
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let paths = (0..3)
+        .map(|i| dir.path().join(format!("params.{}", i)))
+        .collect::<Vec<PathBuf>>();
+    let paths = paths.iter().map(|path| path.as_path()).collect::<Vec<_>>();
+    params.write_sharded(&paths).unwrap();
+
+    // Every shard holds a part of the parameters.
+    let mut whole = vec![];
+    params.write(&mut whole).unwrap();
+    for path in &paths {
+        let len = fs::metadata(path).unwrap().len() as usize;
+        assert!(len > 0 && len < whole.len(), "{}", len);
+    }
+
+    // The order of the shards does not matter.
+    let read = Parameters::<Bls12>::read_sharded(&[paths[2], paths[0], paths[1]]).unwrap();
+    assert!(read == params);
+
+    let x = Fr::from(3u64);
+    let out = (0..10).fold(x, |x, _| x.square());
+    let proof = create_random_proof(
+        RepeatedSquaring::<Bls12> {
+            x: Some(x),
+            iterations: 10,
+        },
+        &read,
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key(&read.vk);
+    assert!(verify_proof(&pvk, &proof, &[out]).unwrap());
+
+    // Missing shards leave gaps.
+    assert!(Parameters::<Bls12>::read_sharded(&[paths[0], paths[2]]).is_err());
+    assert!(Parameters::<Bls12>::read_sharded(&paths[1..]).is_err());
+    // A missing last shard leaves the rest contiguous, but short, even when the
+    // number of shards in the manifests is changed to match.
+    assert!(Parameters::<Bls12>::read_sharded(&paths[..2]).is_err());
+    let truncated = (0..2)
+        .map(|i| {
+            let path = dir.path().join(format!("truncated.{}", i));
+            let mut shard = fs::read(paths[i]).unwrap();
+            shard[..4].copy_from_slice(&2u32.to_be_bytes());
+            fs::write(&path, shard).unwrap();
+            path
+        })
+        .collect::<Vec<PathBuf>>();
+    let truncated = truncated
+        .iter()
+        .map(|path| path.as_path())
+        .collect::<Vec<_>>();
+    assert!(Parameters::<Bls12>::read_sharded(&truncated).is_err());
+    // Shards given twice overlap.
+    assert!(Parameters::<Bls12>::read_sharded(&[paths[0], paths[1], paths[1]]).is_err());
+}
@@ -0,0 +1,103 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Cursor;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bellperson::groth16::{generate_random_parameters, Parameters};
+use bellperson::{Circuit, ConstraintSystem, SynthesisError};
+use blstrs::{Bls12, G1Affine, G2Affine};
+use ff::Field;
+use pairing::Engine;
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+/// Allocator which keeps track of the current and the peak number of allocated
+/// bytes.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Clone)]
+struct DummyDemo {
+    iterations: u64,
+}
+
+impl<E: Engine> Circuit<E> for DummyDemo {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let mut x_val = E::Fr::from(2u64);
+        let mut x = cs.alloc(|| "", || Ok(x_val))?;
+
+        for _ in 0..self.iterations {
+            let x2_val = x_val.square();
+            let x2 = cs.alloc(|| "", || Ok(x2_val))?;
+            cs.enforce(|| "", |lc| lc + x, |lc| lc + x, |lc| lc + x2);
+
+            x = x2;
+            x_val = x2_val;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_read_streaming() {
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    let params =
+        generate_random_parameters::<Bls12, _, _>(DummyDemo { iterations: 5000 }, &mut rng)
+            .unwrap();
+
+    let mut bytes = vec![];
+    params.write(&mut bytes).unwrap();
+
+    let num_g1 = params.h.len() + params.l.len() + params.a.len() + params.b_g1.len();
+    let in_memory =
+        num_g1 * mem::size_of::<G1Affine>() + params.b_g2.len() * mem::size_of::<G2Affine>();
+
+    let base = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(base, Ordering::SeqCst);
+
+    let read = Parameters::<Bls12>::read_streaming(Cursor::new(&bytes[..]), true).unwrap();
+
+    let peak = PEAK.load(Ordering::SeqCst) - base;
+    assert!(read == params);
+    assert!(read == Parameters::read(&bytes[..], true).unwrap());
+
+    // Apart from the points themselves, only the verifying key and a single
+    // chunk of encoded points are held at once.
+    assert!(
+        peak <= in_memory + 512 * 1024,
+        "peak allocation {} for {} bytes of points",
+        peak,
+        in_memory
+    );
+
+    // Truncated streams are rejected up front.
+    assert!(
+        Parameters::<Bls12>::read_streaming(Cursor::new(&bytes[..bytes.len() - 1]), true).is_err()
+    );
+}
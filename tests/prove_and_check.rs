@@ -0,0 +1,87 @@
+use bellperson::gadgets::{
+    boolean::{AllocatedBit, Boolean},
+    multipack,
+    sha256::sha256,
+};
+use bellperson::groth16::test_utils::prove_and_check;
+use bellperson::{Circuit, ConstraintSystem, SynthesisError};
+use blstrs::Bls12;
+use pairing::Engine;
+use rand::SeedableRng;
+use sha2::{Digest, Sha256};
+
+/// SHA-256d gadget of the crate's example. Input and output are in
+/// little-endian bit order.
+fn sha256d<E: Engine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    data: &[Boolean],
+) -> Result<Vec<Boolean>, SynthesisError> {
+    let input: Vec<_> = data
+        .chunks(8)
+        .flat_map(|c| c.iter().rev())
+        .cloned()
+        .collect();
+
+    let mid = sha256(cs.namespace(|| "SHA-256(input)"), &input)?;
+    let res = sha256(cs.namespace(|| "SHA-256(mid)"), &mid)?;
+
+    Ok(res
+        .chunks(8)
+        .flat_map(|c| c.iter().rev())
+        .cloned()
+        .collect())
+}
+
+/// Proves knowledge of the preimage of a SHA-256d hash, see the crate's example.
+#[derive(Clone)]
+struct MyCircuit {
+    preimage: Option<[u8; 80]>,
+}
+
+impl<E: Engine> Circuit<E> for MyCircuit {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let bit_values = if let Some(preimage) = self.preimage {
+            preimage
+                .iter()
+                .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8))
+                .map(Some)
+                .collect()
+        } else {
+            vec![None; 80 * 8]
+        };
+
+        let preimage_bits = bit_values
+            .into_iter()
+            .enumerate()
+            .map(|(i, b)| AllocatedBit::alloc(cs.namespace(|| format!("preimage bit {}", i)), b))
+            .map(|b| b.map(Boolean::from))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let hash = sha256d(cs.namespace(|| "SHA-256d(preimage)"), &preimage_bits)?;
+
+        multipack::pack_into_inputs(cs.namespace(|| "pack hash"), &hash)
+    }
+}
+
+#[test]
+fn test_prove_and_check_sha256d() {
+    let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+
+    let preimage = [42; 80];
+    let hash = Sha256::digest(&Sha256::digest(&preimage));
+    let inputs = multipack::compute_multipacking::<Bls12>(&multipack::bytes_to_bits_le(&hash));
+
+    let c = MyCircuit {
+        preimage: Some(preimage),
+    };
+    assert!(prove_and_check::<Bls12, _, _>(c.clone(), &inputs, &mut rng).unwrap());
+
+    // Inputs the circuit does not expose are caught before proving.
+    let mut other = inputs.clone();
+    other[0] = other[1];
+    let result = std::panic::catch_unwind(move || {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(43);
+        prove_and_check::<Bls12, _, _>(c, &other, &mut rng)
+    });
+    assert!(result.is_err(), "the harness accepted the wrong inputs");
+}
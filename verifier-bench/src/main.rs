@@ -6,6 +6,7 @@
 // --gpu                    Enables GPU
 // --samples                Number of runs
 // --dummy                  Skip param generation and generate dummy params/proofs
+// --uncompressed           Read proofs from their uncompressed encoding when verifying
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -103,6 +104,27 @@ fn dummy_proofs<E: Engine, R: RngCore>(count: usize, mut rng: R) -> Vec<Proof<E>
         .collect()
 }
 
+/// Encodes the proofs back to back, the way they are stored before verification.
+fn encode_proofs<E: Engine>(proofs: &[Proof<E>], uncompressed: bool) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for proof in proofs {
+        if uncompressed {
+            proof.write_uncompressed(&mut bytes).unwrap();
+        } else {
+            proof.write(&mut bytes).unwrap();
+        }
+    }
+    bytes
+}
+
+fn decode_proofs<E: Engine>(bytes: &[u8], count: usize, uncompressed: bool) -> Vec<Proof<E>> {
+    if uncompressed {
+        Proof::read_many_uncompressed(bytes, count).unwrap()
+    } else {
+        Proof::read_many(bytes, count).unwrap()
+    }
+}
+
 fn dummy_inputs<E: Engine, R: RngCore>(count: usize, mut rng: R) -> Vec<<E as Engine>::Fr> {
     (0..count)
         .map(|_| <E as Engine>::Fr::random(&mut rng))
@@ -159,6 +181,8 @@ struct Opts {
     dummy: bool,
     #[structopt(long = "aggregate")]
     aggregate: bool,
+    #[structopt(long = "uncompressed")]
+    uncompressed: bool,
 }
 
 fn main() {
@@ -246,20 +270,27 @@ fn main() {
             (pis, proofs, agg_proof)
         };
 
+        let proof_bytes = encode_proofs(&proofs, opts.uncompressed);
+
         for _ in 0..opts.samples {
-            let pref = proofs.iter().collect::<Vec<&_>>();
             println!(
                 "{} proofs, each having {} public inputs...",
                 opts.proofs, opts.public
             );
 
-            let (valid, took) =
-                timer!(verify_proofs_batch(&pvk, &mut rng, &pref[..], &inputs).unwrap());
+            // Decoding is part of verification, and is what the encoding changes.
+            let (valid, took) = timer!({
+                let proofs = decode_proofs::<Bls12>(&proof_bytes, opts.proofs, opts.uncompressed);
+                let pref = proofs.iter().collect::<Vec<&_>>();
+                verify_proofs_batch(&pvk, &mut rng, &pref[..], &inputs).unwrap()
+            });
             println!(
-                "Verification finished in {}ms (Valid: {}) (Proof Size: {} bytes)",
+                "Verification finished in {}ms, {:.3}ms per proof (Valid: {}) (Proof Size: {} bytes, {})",
                 took,
+                took as f64 / opts.proofs as f64,
                 valid,
-                proofs.len() * Proof::<Bls12>::size(),
+                proof_bytes.len() / opts.proofs,
+                if opts.uncompressed { "uncompressed" } else { "compressed" },
             );
 
             if let Some(ref agg_proof) = agg_proof {
@@ -279,3 +310,29 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uncompressed_flag() {
+        let opts = Opts::from_iter(&["verifier-bench", "--verify", "--uncompressed"]);
+        assert!(opts.verify);
+        assert!(opts.uncompressed);
+        assert!(!Opts::from_iter(&["verifier-bench"]).uncompressed);
+
+        let mut rng = rand::thread_rng();
+        let proofs = dummy_proofs::<Bls12, _>(3, &mut rng);
+        for &uncompressed in &[false, true] {
+            let bytes = encode_proofs(&proofs, uncompressed);
+            let size = if uncompressed {
+                Proof::<Bls12>::uncompressed_size()
+            } else {
+                Proof::<Bls12>::size()
+            };
+            assert_eq!(bytes.len(), 3 * size);
+            assert_eq!(decode_proofs::<Bls12>(&bytes, 3, uncompressed), proofs);
+        }
+    }
+}